@@ -0,0 +1,51 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use eth_staking_smith::{KeyDerivationParams, Validators};
+
+const PHRASE: &str = "entire habit bottom mention spoil clown finger wheat motion fox axis mechanic country make garment bar blind stadium sugar water scissors canyon often ketchup";
+const NUM_VALIDATORS: u32 = 16;
+
+/// Derive validator keys and encrypt keystores with default pbkdf2 parameters,
+/// on thread pools of different size.
+fn key_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("key_generation");
+    group.sample_size(10);
+
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut thread_counts = vec![1, max_threads];
+    thread_counts.dedup();
+    for threads in thread_counts {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        group.bench_with_input(
+            BenchmarkId::new(
+                "keystores",
+                format!("{NUM_VALIDATORS} validators, {threads} threads"),
+            ),
+            &threads,
+            |b, _| {
+                b.iter(|| {
+                    pool.install(|| {
+                        Validators::new(
+                            Some(PHRASE.as_bytes()),
+                            None,
+                            Some(b"testtest".to_vec()),
+                            Some(NUM_VALIDATORS),
+                            None,
+                            &KeyDerivationParams {
+                                derive_withdrawal: true,
+                                ..Default::default()
+                            },
+                        )
+                        .unwrap()
+                    })
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, key_generation);
+criterion_main!(benches);
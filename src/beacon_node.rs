@@ -1,9 +1,15 @@
+use ssz::Encode;
+use types::{Config, Hash256, PublicKeyBytes};
+
 #[derive(Debug)]
 pub enum BeaconNodeError {
     InvalidBeaconNodeURI,
     ClientConfigurationError,
     NodeCommunicationError,
     Non200Response,
+    InvalidResponse,
+    NotFound,
+    ValidatorIndexMismatch { supplied: u64, found: u64 },
 }
 
 /// A trait for types that can be sent to beacon node as-is
@@ -42,3 +48,110 @@ pub trait BeaconNodeExportable {
         }
     }
 }
+
+/// Fetch JSON document from the given path of beacon node API
+pub(crate) fn get_beacon_json(
+    beacon_node_uri: &url::Url,
+    path: &str,
+) -> Result<serde_json::Value, BeaconNodeError> {
+    let reqwc = reqwest::blocking::Client::builder()
+        .build()
+        .map_err(|_| BeaconNodeError::ClientConfigurationError)?;
+    let joined_url = beacon_node_uri
+        .join(path)
+        .map_err(|_| BeaconNodeError::InvalidBeaconNodeURI)?;
+    let resp = reqwc
+        .get(joined_url)
+        .header("Accept", "application/json")
+        .send();
+
+    match resp {
+        Ok(response) => {
+            let code = response.status().as_u16();
+            if code == 404 {
+                Err(BeaconNodeError::NotFound)
+            } else if code != 200 {
+                Err(BeaconNodeError::Non200Response)
+            } else {
+                let body = response
+                    .text()
+                    .map_err(|_| BeaconNodeError::NodeCommunicationError)?;
+                serde_json::from_str(&body).map_err(|_| BeaconNodeError::InvalidResponse)
+            }
+        }
+        Err(_) => Err(BeaconNodeError::NodeCommunicationError),
+    }
+}
+
+/// Validator as known to beacon node at the head state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct BeaconValidator {
+    pub index: u64,
+    pub withdrawal_credentials: String,
+}
+
+/// Look up on-chain index and withdrawal credentials of the validator
+/// with given public key at the head state of beacon node.
+pub(crate) fn validator_by_pubkey(
+    beacon_node_uri: &url::Url,
+    pubkey: &PublicKeyBytes,
+) -> Result<BeaconValidator, BeaconNodeError> {
+    let validator = get_beacon_json(
+        beacon_node_uri,
+        &format!(
+            "/eth/v1/beacon/states/head/validators/0x{}",
+            hex::encode(pubkey.as_ssz_bytes())
+        ),
+    )?;
+    let index = validator["data"]["index"]
+        .as_str()
+        .and_then(|index| index.parse().ok())
+        .ok_or(BeaconNodeError::InvalidResponse)?;
+    let withdrawal_credentials = validator["data"]["validator"]["withdrawal_credentials"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or(BeaconNodeError::InvalidResponse)?;
+    Ok(BeaconValidator {
+        index,
+        withdrawal_credentials,
+    })
+}
+
+/// Look up validator at beacon node, and make sure its index
+/// matches the one supplied by user, if any.
+pub(crate) fn resolve_validator(
+    beacon_node_uri: &url::Url,
+    pubkey: &PublicKeyBytes,
+    supplied_index: Option<u64>,
+) -> Result<BeaconValidator, BeaconNodeError> {
+    let validator = validator_by_pubkey(beacon_node_uri, pubkey)?;
+    match supplied_index {
+        Some(supplied) if supplied != validator.index => {
+            Err(BeaconNodeError::ValidatorIndexMismatch {
+                supplied,
+                found: validator.index,
+            })
+        }
+        _ => Ok(validator),
+    }
+}
+
+/// Fetch genesis validators root of the chain followed by beacon node
+pub(crate) fn genesis_validators_root(
+    beacon_node_uri: &url::Url,
+) -> Result<Hash256, BeaconNodeError> {
+    let genesis = get_beacon_json(beacon_node_uri, "/eth/v1/beacon/genesis")?;
+    genesis["data"]["genesis_validators_root"]
+        .as_str()
+        .and_then(|root| hex::decode(root.strip_prefix("0x").unwrap_or(root)).ok())
+        .filter(|root| root.len() == 32)
+        .map(|root| Hash256::from_slice(root.as_slice()))
+        .ok_or(BeaconNodeError::InvalidResponse)
+}
+
+/// Fetch chain config, including fork schedule, of the chain
+/// followed by beacon node
+pub(crate) fn chain_config(beacon_node_uri: &url::Url) -> Result<Config, BeaconNodeError> {
+    let spec = get_beacon_json(beacon_node_uri, "/eth/v1/config/spec")?;
+    serde_json::from_value(spec["data"].clone()).map_err(|_| BeaconNodeError::InvalidResponse)
+}
@@ -1,47 +1,101 @@
 pub(crate) mod operations;
 
+use bip39::Seed as Bip39Seed;
 use types::{Address, BlsToExecutionChange};
 
-use crate::key_material::VotingKeyMaterial;
+use crate::key_material::{DerivationPaths, VotingKeyMaterial};
+use crate::DepositError;
 
 pub fn bls_execution_change_from_mnemonic(
     mnemonic_phrase: &[u8],
+    passphrase: Option<&str>,
     validator_start_index: u64,
     validator_beacon_index: u64,
     execution_address: &str,
-) -> (BlsToExecutionChange, VotingKeyMaterial) {
-    let (seed, _) = crate::seed::get_eth2_seed(Some(mnemonic_phrase));
+    derivation_paths: Option<&DerivationPaths>,
+) -> Result<(BlsToExecutionChange, VotingKeyMaterial), DepositError> {
+    Ok(bls_execution_changes_from_mnemonic(
+        mnemonic_phrase,
+        passphrase,
+        &[(
+            validator_start_index,
+            validator_beacon_index,
+            execution_address.to_string(),
+        )],
+        derivation_paths,
+    )?
+    .remove(0))
+}
+
+/// Create BLS to execution changes for a batch of validators derived from the same mnemonic,
+/// given validator seed index, validator beacon index and execution address for every validator.
+pub fn bls_execution_changes_from_mnemonic(
+    mnemonic_phrase: &[u8],
+    passphrase: Option<&str>,
+    validators: &[(u64, u64, String)],
+    derivation_paths: Option<&DerivationPaths>,
+) -> Result<Vec<(BlsToExecutionChange, VotingKeyMaterial)>, DepositError> {
+    let (seed, _) = crate::seed::get_eth2_seed(Some(mnemonic_phrase), passphrase)?;
+    let derivation_paths = derivation_paths.cloned().unwrap_or_default();
+    derivation_paths.check(validators.len(), true)?;
+
+    validators
+        .iter()
+        .map(
+            |(validator_start_index, validator_beacon_index, execution_address)| {
+                bls_execution_change_from_seed(
+                    &seed,
+                    *validator_start_index,
+                    *validator_beacon_index,
+                    execution_address,
+                    &derivation_paths,
+                )
+            },
+        )
+        .collect()
+}
 
+fn bls_execution_change_from_seed(
+    seed: &Bip39Seed,
+    validator_start_index: u64,
+    validator_beacon_index: u64,
+    execution_address: &str,
+    derivation_paths: &DerivationPaths,
+) -> Result<(BlsToExecutionChange, VotingKeyMaterial), DepositError> {
+    // Regex guarantees 0x prefix and 20 bytes of valid hex
     if !crate::utils::EXECUTION_ADDR_REGEX.is_match(execution_address) {
-        panic!(
-            "Invalid execution address: Please pass in a valid execution address with the correct format"
-        );
+        return Err(DepositError::InvalidExecutionAddress(
+            "Invalid execution address: Please pass in a valid execution address with the correct format".to_string(),
+        ));
     }
 
     let execution_address = Address::from_slice(
-        hex::decode(
-            execution_address
-                .strip_prefix("0x")
-                .expect("0x prefix is required for execution_address"),
-        )
-        .expect("Invalid hex passed as execution_address")
-        .as_slice(),
+        hex::decode(&execution_address[2..])
+            .map_err(|_| {
+                DepositError::InvalidExecutionAddress(
+                    "Invalid hex passed as execution_address".to_string(),
+                )
+            })?
+            .as_slice(),
     );
 
     let key_materials = crate::key_material::seed_to_key_material(
-        &seed,
+        seed,
         1,
         validator_start_index as u32,
         None,
         true,
         None,
-    );
+        derivation_paths,
+    )?;
 
-    let key_material = key_materials
-        .first()
-        .expect("Error deriving key material from mnemonic");
+    let key_material = key_materials.first().ok_or_else(|| {
+        DepositError::KeyDerivationError("Error deriving key material from mnemonic".to_string())
+    })?;
 
-    let key_pair = key_material.withdrawal_keypair.clone().unwrap();
+    let key_pair = key_material.withdrawal_keypair.clone().ok_or_else(|| {
+        DepositError::KeyDerivationError("Error deriving withdrawal key from mnemonic".to_string())
+    })?;
 
     let bls_to_execution_change = BlsToExecutionChange {
         validator_index: validator_beacon_index,
@@ -49,7 +103,7 @@ pub fn bls_execution_change_from_mnemonic(
         to_execution_address: execution_address,
     };
 
-    (bls_to_execution_change, key_material.clone())
+    Ok((bls_to_execution_change, key_material.clone()))
 }
 
 #[cfg(test)]
@@ -5,7 +5,7 @@ use types::{
     SignedRoot,
 };
 
-use crate::{beacon_node::BeaconNodeExportable, utils::get_withdrawal_credentials};
+use crate::{beacon_node::BeaconNodeExportable, utils::get_withdrawal_credentials, DepositError};
 
 pub(crate) trait SignedBlsToExecutionChangeValidator {
     fn validate(
@@ -14,21 +14,34 @@ pub(crate) trait SignedBlsToExecutionChangeValidator {
         to_execution_address: &str,
         spec: &ChainSpec,
         genesis_validators_root: &Hash256,
-    );
+    ) -> Result<(), DepositError>;
+}
+
+fn export_bls_to_execution_change(change: &SignedBlsToExecutionChange) -> serde_json::Value {
+    serde_json::json!({
+        "message": {
+            "validator_index": change.message.validator_index.to_string(),
+            "from_bls_pubkey": change.message.from_bls_pubkey,
+            "to_execution_address": format!("0x{}", hex::encode(change.message.to_execution_address)),
+        },
+        "signature": change.signature.to_string()
+    })
 }
 
 impl BeaconNodeExportable for SignedBlsToExecutionChange {
     fn export(&self) -> serde_json::Value {
-        serde_json::json!([
-            {
-                "message": {
-                    "validator_index": self.message.validator_index.to_string(),
-                    "from_bls_pubkey": self.message.from_bls_pubkey,
-                    "to_execution_address": format!("0x{}", hex::encode(self.message.to_execution_address)),
-                },
-                "signature": self.signature.to_string()
-            }
-        ])
+        serde_json::json!([export_bls_to_execution_change(self)])
+    }
+
+    fn beacon_node_path(&self) -> String {
+        "/eth/v1/beacon/pool/bls_to_execution_changes".to_string()
+    }
+}
+
+/// Batch of changes is sent to beacon node in a single request
+impl BeaconNodeExportable for Vec<SignedBlsToExecutionChange> {
+    fn export(&self) -> serde_json::Value {
+        serde_json::Value::Array(self.iter().map(export_bls_to_execution_change).collect())
     }
 
     fn beacon_node_path(&self) -> String {
@@ -43,27 +56,31 @@ impl SignedBlsToExecutionChangeValidator for SignedBlsToExecutionChange {
         execution_address: &str,
         spec: &ChainSpec,
         genesis_validators_root: &Hash256,
-    ) {
+    ) -> Result<(), DepositError> {
         // execution address is same as input
         let msg_execution_address = &self.message.to_execution_address;
-        assert_eq!(
-            execution_address.to_lowercase(),
-            format!("0x{}", hex::encode(msg_execution_address))
-        );
+        if execution_address.to_lowercase() != format!("0x{}", hex::encode(msg_execution_address)) {
+            return Err(DepositError::InvalidExecutionAddress(
+                "Execution address does not match bls to execution change".to_string(),
+            ));
+        }
 
         // withdrawal credentials are the same as input
         let withdrawal_pubkey = &self.message.from_bls_pubkey;
-        let withdrawal_pubkey = PublicKey::from_str(&withdrawal_pubkey.to_string()).unwrap();
+        let withdrawal_pubkey =
+            PublicKey::from_str(&withdrawal_pubkey.to_string()).map_err(|e| {
+                DepositError::InvalidSignature(format!("Invalid BLS public key: {}", e))
+            })?;
 
         let withdrawal = get_withdrawal_credentials(&withdrawal_pubkey.clone().into(), 0);
         let withdrawal_credentials = hex::encode(withdrawal);
-        let withdrawal_credentials =
-            std::str::from_utf8(withdrawal_credentials.as_bytes()).unwrap();
 
-        assert_eq!(
-            from_bls_withdrawal_credentials,
-            format!("0x{}", withdrawal_credentials)
-        );
+        if from_bls_withdrawal_credentials != format!("0x{}", withdrawal_credentials) {
+            return Err(DepositError::InvalidWithdrawalCredentials(
+                "BLS withdrawal credentials do not match the withdrawal key derived from mnemonic"
+                    .to_string(),
+            ));
+        }
 
         // verify signature
         let domain = spec.compute_domain(
@@ -79,7 +96,10 @@ impl SignedBlsToExecutionChangeValidator for SignedBlsToExecutionChange {
         };
         let signing_root = bls_to_execution_change.signing_root(domain);
         if !self.signature.verify(&withdrawal_pubkey, signing_root) {
-            panic!("Invalid bls to execution change signature")
+            return Err(DepositError::InvalidSignature(
+                "Invalid bls to execution change signature".to_string(),
+            ));
         }
+        Ok(())
     }
 }
@@ -26,10 +26,13 @@ fn it_generates_signed_bls_to_execution_change() {
     let (bls_to_execution_change, keypair) =
         crate::bls_to_execution_change::bls_execution_change_from_mnemonic(
             PHRASE.as_bytes(),
+            None,
             0,
             100,
             EXECUTION_WITHDRAWAL_ADDRESS,
-        );
+            None,
+        )
+        .unwrap();
     let signed_bls_to_execution_change = bls_to_execution_change.clone().sign(
         &keypair.withdrawal_keypair.unwrap().sk,
         validators_root_for(&crate::networks::SupportedNetworks::Mainnet),
@@ -54,3 +57,55 @@ fn it_generates_signed_bls_to_execution_change() {
         withdrawal_creds_from_pk(&withdrawal_pub_key)
     );
 }
+
+#[test]
+fn it_generates_signed_bls_to_execution_changes_batch() {
+    let other_execution_address = "0x0000000000000000000000000000000000000001";
+    let changes = crate::bls_to_execution_change::bls_execution_changes_from_mnemonic(
+        PHRASE.as_bytes(),
+        None,
+        &[
+            (0, 100, EXECUTION_WITHDRAWAL_ADDRESS.to_string()),
+            (1, 101, other_execution_address.to_string()),
+        ],
+        None,
+    )
+    .unwrap();
+    assert_eq!(2, changes.len());
+
+    let (first_change, _) = &changes[0];
+    let (single_change, _) = crate::bls_to_execution_change::bls_execution_change_from_mnemonic(
+        PHRASE.as_bytes(),
+        None,
+        0,
+        100,
+        EXECUTION_WITHDRAWAL_ADDRESS,
+        None,
+    )
+    .unwrap();
+    assert_eq!(&single_change, first_change);
+
+    let (second_change, _) = &changes[1];
+    assert_eq!(101, second_change.validator_index);
+    assert_eq!(
+        other_execution_address,
+        format!("0x{}", hex::encode(second_change.to_execution_address))
+    );
+    assert_ne!(first_change.from_bls_pubkey, second_change.from_bls_pubkey);
+}
+
+#[test]
+fn it_rejects_invalid_execution_address() {
+    let result = crate::bls_to_execution_change::bls_execution_change_from_mnemonic(
+        PHRASE.as_bytes(),
+        None,
+        0,
+        100,
+        "71C7656EC7ab88b098defB751B7401B5f6d8976F",
+        None,
+    );
+    assert!(matches!(
+        result,
+        Err(crate::DepositError::InvalidExecutionAddress(_))
+    ));
+}
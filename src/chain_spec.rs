@@ -1,24 +1,24 @@
 use std::path::Path;
 
 use eth2_network_config::Eth2NetworkConfig;
-use types::{ChainSpec, Config, GnosisEthSpec, Hash256, MainnetEthSpec, MinimalEthSpec};
+use types::{
+    Address, ChainSpec, Config, Epoch, GnosisEthSpec, Hash256, MainnetEthSpec, MinimalEthSpec,
+};
 
-use crate::{networks::SupportedNetworks, DepositError};
+use crate::{beacon_node, networks::SupportedNetworks, DepositError};
 
 pub fn chain_spec_for_network(network: &SupportedNetworks) -> Result<ChainSpec, DepositError> {
     let network_name = network.to_string();
-    if ["goerli", "prater", "mainnet", "holesky"].contains(&network_name.as_str()) {
-        Ok(Eth2NetworkConfig::constant(&network_name)
-            .unwrap()
-            .unwrap()
+    if ["goerli", "prater", "mainnet", "holesky", "sepolia"].contains(&network_name.as_str()) {
+        built_in_network_config(&network_name)?
             .chain_spec::<MainnetEthSpec>()
-            .unwrap())
-    } else if network_name.as_str() == "gnosis" {
-        Ok(Eth2NetworkConfig::constant(&network_name)
-            .unwrap()
-            .unwrap()
+            .map_err(DepositError::InvalidNetworkName)
+    } else if ["gnosis", "chiado"].contains(&network_name.as_str()) {
+        built_in_network_config(&network_name)?
             .chain_spec::<GnosisEthSpec>()
-            .unwrap())
+            .map_err(DepositError::InvalidNetworkName)
+    } else if network_name.as_str() == "hoodi" {
+        Ok(hoodi_chain_spec())
     } else {
         Err(DepositError::InvalidNetworkName(format!(
             "unknown chain name: {network_name}"
@@ -26,18 +26,67 @@ pub fn chain_spec_for_network(network: &SupportedNetworks) -> Result<ChainSpec,
     }
 }
 
+/// Whether the chain spec is built on gnosis preset, as for Gnosis chain and Chiado.
+///
+/// Chain spec does not record the preset it is built on, so it is told apart
+/// by a preset value which chain config can not override.
+pub fn is_gnosis_preset(spec: &ChainSpec) -> bool {
+    let gnosis_base_reward_factor = ChainSpec::gnosis().base_reward_factor;
+    gnosis_base_reward_factor != ChainSpec::mainnet().base_reward_factor
+        && spec.base_reward_factor == gnosis_base_reward_factor
+}
+
+/// Maximum effective balance of compounding validator in gwei, introduced with EIP-7251.
+///
+/// It is not a part of chain spec in lighthouse version we use yet, so the value
+/// of consensus specs preset is used, which is 2048 ETH for mainnet and minimal
+/// presets, and 64_000_000_000 gwei for gnosis preset.
+pub fn max_effective_balance_electra(spec: &ChainSpec) -> u64 {
+    if is_gnosis_preset(spec) {
+        64_000_000_000
+    } else {
+        2_048_000_000_000
+    }
+}
+
+fn built_in_network_config(network_name: &str) -> Result<Eth2NetworkConfig, DepositError> {
+    Eth2NetworkConfig::constant(network_name)
+        .map_err(DepositError::InvalidNetworkName)?
+        .ok_or_else(|| {
+            DepositError::InvalidNetworkName(format!("unknown chain name: {network_name}"))
+        })
+}
+
+/// Hoodi config is not bundled with lighthouse version we use,
+/// so it is built on top of mainnet spec from the values in
+/// https://github.com/eth-clients/hoodi/blob/main/metadata/config.yaml
+fn hoodi_chain_spec() -> ChainSpec {
+    let mut spec = ChainSpec::mainnet();
+    spec.config_name = Some("hoodi".to_string());
+    spec.min_genesis_time = 1742212800;
+    spec.genesis_delay = 600;
+    spec.genesis_fork_version = [0x10, 0x00, 0x09, 0x10];
+    spec.altair_fork_version = [0x20, 0x00, 0x09, 0x10];
+    spec.altair_fork_epoch = Some(Epoch::new(0));
+    spec.bellatrix_fork_version = [0x30, 0x00, 0x09, 0x10];
+    spec.bellatrix_fork_epoch = Some(Epoch::new(0));
+    spec.capella_fork_version = [0x40, 0x00, 0x09, 0x10];
+    spec.capella_fork_epoch = Some(Epoch::new(0));
+    spec.deneb_fork_version = [0x50, 0x00, 0x09, 0x10];
+    spec.deneb_fork_epoch = Some(Epoch::new(0));
+    spec.deposit_chain_id = 560048;
+    spec.deposit_network_id = 560048;
+    spec.deposit_contract_address = Address::from_slice(
+        hex::decode("00000000219ab540356cBB839Cbe05303d7705Fa")
+            .unwrap()
+            .as_slice(),
+    );
+    spec
+}
+
 pub fn chain_spec_from_file(chain_spec_file: String) -> Result<ChainSpec, DepositError> {
     match Config::from_file(Path::new(chain_spec_file.as_str())) {
-        Ok(cfg) => {
-            let spec = if cfg.preset_base == "minimal" {
-                cfg.apply_to_chain_spec::<MinimalEthSpec>(&ChainSpec::minimal())
-                    .unwrap()
-            } else {
-                cfg.apply_to_chain_spec::<MainnetEthSpec>(&ChainSpec::mainnet())
-                    .unwrap()
-            };
-            Ok(spec)
-        }
+        Ok(cfg) => chain_spec_from_config(&cfg),
         Err(e) => {
             log::error!("Unable to load chain spec config: {:?}", e);
             Err(DepositError::NoCustomConfig(
@@ -47,29 +96,164 @@ pub fn chain_spec_from_file(chain_spec_file: String) -> Result<ChainSpec, Deposi
     }
 }
 
+/// Build chain spec from chain config, as found in testnet config file
+/// or reported by beacon node, applying it on top of config preset.
+pub fn chain_spec_from_config(cfg: &Config) -> Result<ChainSpec, DepositError> {
+    let spec = match cfg.preset_base.as_str() {
+        "minimal" => cfg.apply_to_chain_spec::<MinimalEthSpec>(&ChainSpec::minimal()),
+        "gnosis" => cfg.apply_to_chain_spec::<GnosisEthSpec>(&ChainSpec::gnosis()),
+        _ => cfg.apply_to_chain_spec::<MainnetEthSpec>(&ChainSpec::mainnet()),
+    };
+    spec.ok_or_else(|| {
+        DepositError::NoCustomConfig(format!(
+            "Chain config does not match {} preset",
+            cfg.preset_base
+        ))
+    })
+}
+
+/// Fetch genesis validators root and chain spec from beacon node.
+///
+/// If custom testnet config or genesis validators root are supplied as well,
+/// they are compared against the values reported by beacon node, and warning
+/// is logged for every mismatch. Values reported by beacon node take precedence.
+pub fn validators_root_and_spec_from_beacon_node(
+    beacon_node_uri: &url::Url,
+    testnet_properties: (Option<String>, Option<String>),
+) -> Result<(Hash256, ChainSpec), DepositError> {
+    let genesis_validators_root = beacon_node::genesis_validators_root(beacon_node_uri)?;
+    let config = beacon_node::chain_config(beacon_node_uri)?;
+    let spec = chain_spec_from_config(&config)?;
+
+    let (genesis_validators_root_str, testnet_config_path) = testnet_properties;
+    if let Some(genesis_validators_root_str) = genesis_validators_root_str {
+        let supplied_root = decode_genesis_validators_root(&genesis_validators_root_str)?;
+        if supplied_root != genesis_validators_root {
+            log::warn!(
+                "Supplied genesis validators root {:?} does not match {:?} reported by beacon node",
+                supplied_root,
+                genesis_validators_root
+            );
+        }
+    }
+    if let Some(testnet_config_path) = testnet_config_path {
+        let supplied_spec = chain_spec_from_file(testnet_config_path)?;
+        for mismatch in fork_schedule_mismatches(&supplied_spec, &spec) {
+            log::warn!(
+                "Supplied testnet config disagrees with beacon node on {}",
+                mismatch
+            );
+        }
+    }
+    Ok((genesis_validators_root, spec))
+}
+
+/// List fork schedule and deposit contract parameters that differ between two chain specs,
+/// formatted as `NAME: <supplied> != <reported>`.
+pub(crate) fn fork_schedule_mismatches(supplied: &ChainSpec, reported: &ChainSpec) -> Vec<String> {
+    let mut mismatches = vec![];
+    let mut compare = |name: &str, supplied: String, reported: String| {
+        if supplied != reported {
+            mismatches.push(format!("{name}: {supplied} != {reported}"));
+        }
+    };
+    compare(
+        "GENESIS_FORK_VERSION",
+        hex::encode(supplied.genesis_fork_version),
+        hex::encode(reported.genesis_fork_version),
+    );
+    compare(
+        "ALTAIR_FORK_VERSION",
+        hex::encode(supplied.altair_fork_version),
+        hex::encode(reported.altair_fork_version),
+    );
+    compare(
+        "ALTAIR_FORK_EPOCH",
+        format!("{:?}", supplied.altair_fork_epoch),
+        format!("{:?}", reported.altair_fork_epoch),
+    );
+    compare(
+        "BELLATRIX_FORK_VERSION",
+        hex::encode(supplied.bellatrix_fork_version),
+        hex::encode(reported.bellatrix_fork_version),
+    );
+    compare(
+        "BELLATRIX_FORK_EPOCH",
+        format!("{:?}", supplied.bellatrix_fork_epoch),
+        format!("{:?}", reported.bellatrix_fork_epoch),
+    );
+    compare(
+        "CAPELLA_FORK_VERSION",
+        hex::encode(supplied.capella_fork_version),
+        hex::encode(reported.capella_fork_version),
+    );
+    compare(
+        "CAPELLA_FORK_EPOCH",
+        format!("{:?}", supplied.capella_fork_epoch),
+        format!("{:?}", reported.capella_fork_epoch),
+    );
+    compare(
+        "DENEB_FORK_VERSION",
+        hex::encode(supplied.deneb_fork_version),
+        hex::encode(reported.deneb_fork_version),
+    );
+    compare(
+        "DENEB_FORK_EPOCH",
+        format!("{:?}", supplied.deneb_fork_epoch),
+        format!("{:?}", reported.deneb_fork_epoch),
+    );
+    compare(
+        "DEPOSIT_CHAIN_ID",
+        supplied.deposit_chain_id.to_string(),
+        reported.deposit_chain_id.to_string(),
+    );
+    compare(
+        "DEPOSIT_CONTRACT_ADDRESS",
+        format!("{:?}", supplied.deposit_contract_address),
+        format!("{:?}", reported.deposit_contract_address),
+    );
+    mismatches
+}
+
+fn decode_genesis_validators_root(
+    genesis_validators_root_str: &str,
+) -> Result<Hash256, DepositError> {
+    let genesis_validators_root_bytes = hex::decode(
+        genesis_validators_root_str
+            .strip_prefix("0x")
+            .unwrap_or(genesis_validators_root_str),
+    )
+    .map_err(|_| {
+        DepositError::InvalidGenesisValidatorsRoot(
+            "Invalid custom genesis validators root".to_string(),
+        )
+    })?;
+    if genesis_validators_root_bytes.len() != 32 {
+        return Err(DepositError::InvalidGenesisValidatorsRoot(
+            "Genesis validators root should be 32 bytes long".to_string(),
+        ));
+    }
+    Ok(Hash256::from_slice(
+        genesis_validators_root_bytes.as_slice(),
+    ))
+}
+
 pub fn validators_root_and_spec(
     chain: Option<SupportedNetworks>,
     testnet_properties: Option<(String, String)>,
-) -> (Hash256, ChainSpec) {
-    if chain.is_some() {
-        let well_known_chain = chain.unwrap();
-        (
+) -> Result<(Hash256, ChainSpec), DepositError> {
+    match (chain, testnet_properties) {
+        (Some(well_known_chain), _) => Ok((
             crate::networks::validators_root_for(&well_known_chain),
-            chain_spec_for_network(&well_known_chain).expect("Invalid chain spec"),
-        )
-    } else {
-        let (genesis_validators_root_str, testnet_config_path) = testnet_properties.expect(
-            "If custom testnet config is passed, genesis validators root value must be included",
-        );
-        let genesis_validators_root_bytes = hex::decode(
-            genesis_validators_root_str
-                .strip_prefix("0x")
-                .unwrap_or(&genesis_validators_root_str),
-        )
-        .expect("Invalid custom genesis validators root");
-        (
-            Hash256::from_slice(genesis_validators_root_bytes.as_slice()),
-            chain_spec_from_file(testnet_config_path).expect("Invalid chain spec in file"),
-        )
+            chain_spec_for_network(&well_known_chain)?,
+        )),
+        (None, Some((genesis_validators_root_str, testnet_config_path))) => Ok((
+            decode_genesis_validators_root(&genesis_validators_root_str)?,
+            chain_spec_from_file(testnet_config_path)?,
+        )),
+        (None, None) => Err(DepositError::NoCustomConfig(
+            "If custom testnet config is passed, genesis validators root value must be included"
+                .to_string(),
+        )),
     }
 }
@@ -0,0 +1,211 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::DepositError;
+
+/// Single validator entry of the batch file, mapping
+/// index of validator key derived from the seed to on-chain beacon index,
+/// optionally with execution address for the validator withdrawals
+/// and BLS withdrawal credentials the validator was deposited with.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct BatchEntry {
+    pub validator_seed_index: u32,
+    pub validator_beacon_index: u64,
+    pub execution_address: Option<String>,
+    pub bls_withdrawal_credentials: Option<String>,
+}
+
+/// Read batch file, which is either JSON array of objects like
+/// [{"validator_seed_index": 0, "validator_beacon_index": 100}, ...]
+/// or CSV with `validator_seed_index,validator_beacon_index[,execution_address[,bls_withdrawal_credentials]]`
+/// columns, where header row is optional, and optional columns may be left empty.
+pub fn read_batch_file(path: &str) -> Result<Vec<BatchEntry>, DepositError> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        DepositError::FilesystemError(format!("Can not read validator index file {path}: {e}"))
+    })?;
+    let entries: Vec<BatchEntry> = if contents.trim_start().starts_with('[') {
+        serde_json::from_str(&contents)
+            .map_err(|e| invalid_batch_file(format!("invalid JSON batch file: {e}")))?
+    } else {
+        parse_csv(&contents).map_err(invalid_batch_file)?
+    };
+    Ok(entries.into_iter().map(normalize_entry).collect())
+}
+
+/// BLS withdrawal credentials are compared as lowercase 0x-prefixed hex,
+/// so bring the ones from the batch file to that form
+fn normalize_entry(mut entry: BatchEntry) -> BatchEntry {
+    entry.bls_withdrawal_credentials = entry
+        .bls_withdrawal_credentials
+        .map(|credentials| credentials.trim().to_lowercase())
+        .filter(|credentials| !credentials.is_empty())
+        .map(|credentials| {
+            format!(
+                "0x{}",
+                credentials.strip_prefix("0x").unwrap_or(&credentials)
+            )
+        });
+    entry
+}
+
+fn invalid_batch_file(reason: String) -> DepositError {
+    DepositError::InvalidInput(format!("Invalid validator index file: {reason}"))
+}
+
+fn parse_csv(contents: &str) -> Result<Vec<BatchEntry>, String> {
+    let mut entries = vec![];
+    for (line_no, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let columns: Vec<&str> = line.split(',').map(str::trim).collect();
+        // Skip header row
+        if line_no == 0 && columns[0].parse::<u32>().is_err() {
+            continue;
+        }
+        if columns.len() < 2 || columns.len() > 4 {
+            return Err(format!(
+                "line {}: expected validator_seed_index,validator_beacon_index\
+                [,execution_address[,bls_withdrawal_credentials]]",
+                line_no + 1
+            ));
+        }
+        let optional_column = |i: usize| {
+            columns
+                .get(i)
+                .filter(|value| !value.is_empty())
+                .map(|value| value.to_string())
+        };
+        entries.push(BatchEntry {
+            validator_seed_index: columns[0]
+                .parse()
+                .map_err(|_| format!("line {}: invalid validator seed index", line_no + 1))?,
+            validator_beacon_index: columns[1]
+                .parse()
+                .map_err(|_| format!("line {}: invalid validator beacon index", line_no + 1))?,
+            execution_address: optional_column(2),
+            bls_withdrawal_credentials: optional_column(3),
+        });
+    }
+    Ok(entries)
+}
+
+/// Select batch entries for `count` validators starting from `start` seed index,
+/// ordered by seed index. Every seed index in the range must be present in the batch.
+pub fn entries_for_seed_range(
+    entries: &[BatchEntry],
+    start: u32,
+    count: u32,
+) -> Result<Vec<BatchEntry>, DepositError> {
+    let end = start.checked_add(count).ok_or_else(|| {
+        DepositError::InvalidInput("Validator seed index range is too large".to_string())
+    })?;
+    let mut by_seed_index = BTreeMap::new();
+    for entry in entries {
+        if by_seed_index
+            .insert(entry.validator_seed_index, entry.clone())
+            .is_some()
+        {
+            return Err(invalid_batch_file(format!(
+                "duplicate validator seed index {}",
+                entry.validator_seed_index
+            )));
+        }
+    }
+    (start..end)
+        .map(|seed_index| {
+            by_seed_index.get(&seed_index).cloned().ok_or_else(|| {
+                invalid_batch_file(format!(
+                    "no beacon index for validator seed index {seed_index}"
+                ))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{entries_for_seed_range, normalize_entry, parse_csv, BatchEntry};
+    use crate::DepositError;
+    use pretty_assertions::assert_eq;
+
+    fn entry(validator_seed_index: u32, validator_beacon_index: u64) -> BatchEntry {
+        BatchEntry {
+            validator_seed_index,
+            validator_beacon_index,
+            execution_address: None,
+            bls_withdrawal_credentials: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = "validator_seed_index,validator_beacon_index\n0,100\n 1 , 101 \n\n";
+        assert_eq!(vec![entry(0, 100), entry(1, 101)], parse_csv(csv).unwrap());
+        // header is optional
+        assert_eq!(vec![entry(2, 102)], parse_csv("2,102").unwrap());
+        assert!(parse_csv("0,100\n1").is_err());
+        assert_eq!(
+            Some("0x71C7656EC7ab88b098defB751B7401B5f6d8976F".to_string()),
+            parse_csv("0,100,0x71C7656EC7ab88b098defB751B7401B5f6d8976F").unwrap()[0]
+                .execution_address
+        );
+        assert!(parse_csv("0,100\nx,101").is_err());
+
+        let entries =
+            parse_csv("0,100,,0x0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d")
+                .unwrap();
+        assert_eq!(None, entries[0].execution_address);
+        assert_eq!(
+            Some("0x0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d".to_string()),
+            entries[0].bls_withdrawal_credentials
+        );
+        assert!(parse_csv("0,100,,,0x01").is_err());
+    }
+
+    #[test]
+    fn test_normalize_entry() {
+        let normalized = |credentials: Option<&str>| {
+            normalize_entry(BatchEntry {
+                bls_withdrawal_credentials: credentials.map(str::to_string),
+                ..entry(0, 100)
+            })
+            .bls_withdrawal_credentials
+        };
+        let expected =
+            Some("0x0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d".to_string());
+        assert_eq!(
+            expected,
+            normalized(Some(
+                "0x0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d"
+            ))
+        );
+        assert_eq!(
+            expected,
+            normalized(Some(
+                " 0045B91B2F60B88E7392D49AE1364B55E713D06F30E563F9F99E10994B26221D "
+            ))
+        );
+        assert_eq!(None, normalized(Some(" ")));
+        assert_eq!(None, normalized(None));
+    }
+
+    #[test]
+    fn test_entries_for_seed_range() {
+        let entries = vec![entry(2, 102), entry(0, 100), entry(1, 101)];
+        assert_eq!(
+            vec![entry(1, 101), entry(2, 102)],
+            entries_for_seed_range(&entries, 1, 2).unwrap()
+        );
+        assert!(entries_for_seed_range(&entries, 1, 3).is_err());
+        assert!(entries_for_seed_range(&[entry(0, 100), entry(0, 101)], 0, 1).is_err());
+        assert_eq!(
+            Err(DepositError::InvalidInput(
+                "Validator seed index range is too large".to_string()
+            )),
+            entries_for_seed_range(&entries, u32::MAX, 2)
+        );
+    }
+}
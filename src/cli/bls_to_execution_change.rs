@@ -1,19 +1,24 @@
+use crate::beacon_node::{self, BeaconNodeExportable};
+use crate::bls_to_execution_change;
 use crate::bls_to_execution_change::operations::SignedBlsToExecutionChangeValidator;
-use crate::chain_spec::validators_root_and_spec;
-use crate::{beacon_node::BeaconNodeExportable, bls_to_execution_change};
+use crate::chain_spec::{validators_root_and_spec, validators_root_and_spec_from_beacon_node};
+use crate::cli::batch;
+use crate::cli::secrets::{MnemonicOpts, PassphraseOpts};
+use crate::key_material::DerivationPaths;
+use crate::DepositError;
 use clap::{arg, Parser};
+use types::SignedBlsToExecutionChange;
 
 #[derive(Clone, Parser)]
 pub struct BlsToExecutionChangeSubcommandOpts {
-    /// The mnemonic that you used to generate your
-    /// keys.
-    ///
-    /// It is recommended not to use this
-    /// argument, and wait for the CLI to ask you
-    ///    for your mnemonic as otherwise it will
-    ///    appear in your shell history.
-    #[arg(long)]
-    pub mnemonic: String,
+    #[command(flatten)]
+    pub mnemonic: MnemonicOpts,
+
+    #[command(flatten)]
+    pub passphrase: PassphraseOpts,
+
+    #[command(flatten)]
+    pub derivation_paths: DerivationPaths,
 
     /// The name of Ethereum PoS chain you are targeting.
     ///
@@ -31,37 +36,92 @@ pub struct BlsToExecutionChangeSubcommandOpts {
     pub validator_seed_index: u32,
 
     /// On-chain beacon index of the validator.
-    #[arg(long, visible_alias = "validator_beacon_index")]
-    pub validator_beacon_index: u32,
+    /// May be omitted if `beacon_node_uri` is set, in which case
+    /// the index is looked up at beacon node by validator public key.
+    #[arg(
+        long,
+        visible_alias = "validator_beacon_index",
+        required_unless_present_any = ["validator_index_file", "beacon_node_uri"]
+    )]
+    pub validator_beacon_index: Option<u64>,
 
     /// BLS withdrawal credentials you used when depositing the validator.
-    #[arg(long, visible_alias = "bls_withdrawal_credentials")]
-    pub bls_withdrawal_credentials: String,
+    #[arg(
+        long,
+        visible_alias = "bls_withdrawal_credentials",
+        required_unless_present = "validator_index_file"
+    )]
+    pub bls_withdrawal_credentials: Option<String>,
 
     /// Execution (0x01) address to which funds withdrawn should be sent to.
-    #[arg(long, visible_alias = "execution_address")]
-    pub execution_address: String,
+    ///
+    /// In batch mode, it is used for validators which have no
+    /// execution address set in the validator index file.
+    #[arg(
+        long,
+        visible_alias = "execution_address",
+        required_unless_present = "validator_index_file"
+    )]
+    pub execution_address: Option<String>,
 
-    /// Path to a custom Eth PoS chain config
+    /// Path to CSV or JSON file mapping validator seed indices to on-chain
+    /// beacon indices, to generate bls-to-execution-change messages in batch
+    /// for `num_validators` validators starting from `validator_seed_index`.
+    ///
+    /// CSV file has `validator_seed_index,validator_beacon_index,execution_address,bls_withdrawal_credentials`
+    /// columns, and JSON file is an array of objects with the same keys,
+    /// where execution address is optional if `execution_address` argument is set,
+    /// and BLS withdrawal credentials are optional if `beacon_node_uri` is set,
+    /// in which case they are looked up at beacon node by validator public key.
+    #[arg(
+        long,
+        visible_alias = "validator_index_file",
+        requires = "num_validators",
+        conflicts_with_all = ["validator_beacon_index", "bls_withdrawal_credentials"]
+    )]
+    pub validator_index_file: Option<String>,
+
+    /// The number of validators to generate bls-to-execution-change messages for
+    /// in batch mode.
+    #[arg(
+        long,
+        visible_alias = "num_validators",
+        requires = "validator_index_file"
+    )]
+    pub num_validators: Option<u32>,
+
+    /// Path to a custom Eth PoS chain config.
+    ///
+    /// May be omitted if `beacon_node_uri` is set, then chain config
+    /// is fetched from beacon node, and the supplied one is only checked against it.
+    /// As the message is then sent to beacon node, pass both `testnet_config`
+    /// and `genesis_validators_root` to only print it.
     #[arg(long, visible_alias = "testnet_config")]
     pub testnet_config: Option<String>,
 
     /// Custom genesis validators root for the custom testnet, passed as hex string.
     /// See https://eth2book.info/capella/part3/containers/state/ for value
-    /// description
+    /// description.
+    ///
+    /// May be omitted for custom testnet if `beacon_node_uri` is set,
+    /// then it is fetched from beacon node.
     #[arg(long, visible_alias = "genesis_validators_root")]
     pub genesis_validators_root: Option<String>,
 
     /// Optional beacon node URL. If set, the bls-to-execution-change message
-    /// will not be printed on stdout, but instead sent to beacon node
+    /// will not be printed on stdout, but instead sent to beacon node.
+    /// Validator beacon index is then checked against the one known
+    /// to beacon node for the validator public key.
     #[arg(long, visible_alias = "beacon_node_uri")]
     pub beacon_node_uri: Option<url::Url>,
 }
 
 impl BlsToExecutionChangeSubcommandOpts {
-    pub fn run(&self) {
+    pub fn run(&self) -> Result<(), DepositError> {
         let chain = if self.chain.is_some() && self.testnet_config.is_some() {
-            panic!("should only pass one of testnet_config or chain")
+            return Err(DepositError::InvalidInput(
+                "should only pass one of testnet_config or chain".to_string(),
+            ));
         } else if self.testnet_config.is_some() {
             // Signalizes custom testnet config will be used
             None
@@ -69,53 +129,149 @@ impl BlsToExecutionChangeSubcommandOpts {
             self.chain.clone()
         };
 
-        let (genesis_validators_root, spec) = validators_root_and_spec(
-            chain.clone(),
-            if chain.is_some() {
-                None
+        let (genesis_validators_root, spec) = match (&chain, &self.beacon_node_uri) {
+            // Custom testnet parameters are fetched from beacon node if possible
+            (None, Some(beacon_node_uri)) => validators_root_and_spec_from_beacon_node(
+                beacon_node_uri,
+                (
+                    self.genesis_validators_root.clone(),
+                    self.testnet_config.clone(),
+                ),
+            )?,
+            _ => validators_root_and_spec(
+                chain.clone(),
+                self.genesis_validators_root
+                    .clone()
+                    .zip(self.testnet_config.clone()),
+            )?,
+        };
+
+        let (validators, mut bls_withdrawal_credentials): (Vec<(u64, u64, String)>, Vec<_>) =
+            if let Some(validator_index_file) = &self.validator_index_file {
+                batch::entries_for_seed_range(
+                    &batch::read_batch_file(validator_index_file)?,
+                    self.validator_seed_index,
+                    self.num_validators.unwrap(),
+                )?
+                .into_iter()
+                .map(|entry| {
+                    let execution_address = entry
+                        .execution_address
+                        .or_else(|| self.execution_address.clone())
+                        .ok_or_else(|| {
+                            DepositError::InvalidInput(format!(
+                                "No execution address for validator seed index {}",
+                                entry.validator_seed_index
+                            ))
+                        })?;
+                    Ok((
+                        (
+                            entry.validator_seed_index as u64,
+                            entry.validator_beacon_index,
+                            execution_address,
+                        ),
+                        entry.bls_withdrawal_credentials,
+                    ))
+                })
+                .collect::<Result<Vec<_>, DepositError>>()?
+                .into_iter()
+                .unzip()
             } else {
-                Some((
-                    self.genesis_validators_root
-                        .clone()
-                        .expect("Genesis validators root parameter must be set"),
-                    self.testnet_config
-                        .clone()
-                        .expect("Testnet config must be set"),
-                ))
-            },
-        );
-
-        let (bls_to_execution_change, keypair) =
-            bls_to_execution_change::bls_execution_change_from_mnemonic(
-                self.mnemonic.as_bytes(),
-                self.validator_seed_index as u64,
-                self.validator_beacon_index as u64,
-                self.execution_address.as_str(),
-            );
-
-        let signed_bls_to_execution_change = bls_to_execution_change.sign(
-            &keypair.withdrawal_keypair.unwrap().sk,
-            genesis_validators_root,
-            &spec,
-        );
-
-        signed_bls_to_execution_change.clone().validate(
-            self.bls_withdrawal_credentials.as_str(),
-            self.execution_address.as_str(),
-            &spec,
-            &genesis_validators_root,
-        );
-
-        if self.beacon_node_uri.is_some() {
-            signed_bls_to_execution_change
-                .send_beacon_payload(self.beacon_node_uri.clone().unwrap())
-                .unwrap_or_else(|e| panic!("Failed sending beacon node payload: {:?}", e))
+                (
+                    vec![(
+                        self.validator_seed_index as u64,
+                        self.validator_beacon_index.unwrap_or_default(),
+                        self.execution_address.clone().unwrap(),
+                    )],
+                    vec![self.bls_withdrawal_credentials.clone()],
+                )
+            };
+
+        let mnemonic = self.mnemonic.read()?;
+        let passphrase = self.passphrase.read(false)?;
+        let mut bls_to_execution_changes =
+            bls_to_execution_change::bls_execution_changes_from_mnemonic(
+                mnemonic.as_bytes(),
+                passphrase.as_deref(),
+                &validators,
+                Some(&self.derivation_paths),
+            )?;
+
+        if let Some(beacon_node_uri) = &self.beacon_node_uri {
+            for ((bls_to_execution_change, keypair), bls_withdrawal_credentials) in
+                bls_to_execution_changes
+                    .iter_mut()
+                    .zip(bls_withdrawal_credentials.iter_mut())
+            {
+                let supplied_index = if self.validator_index_file.is_some() {
+                    Some(bls_to_execution_change.validator_index)
+                } else {
+                    self.validator_beacon_index
+                };
+                let validator = beacon_node::resolve_validator(
+                    beacon_node_uri,
+                    &keypair.keypair.pk.clone().into(),
+                    supplied_index,
+                )?;
+                bls_to_execution_change.validator_index = validator.index;
+                if bls_withdrawal_credentials.is_none() {
+                    *bls_withdrawal_credentials = Some(validator.withdrawal_credentials);
+                }
+            }
+        }
+
+        let signed_bls_to_execution_changes: Vec<SignedBlsToExecutionChange> =
+            bls_to_execution_changes
+                .into_iter()
+                .zip(validators.iter().zip(bls_withdrawal_credentials))
+                .map(
+                    |(
+                        (bls_to_execution_change, keypair),
+                        ((validator_seed_index, _, execution_address), bls_withdrawal_credentials),
+                    )| {
+                        let bls_withdrawal_credentials =
+                            bls_withdrawal_credentials.ok_or_else(|| {
+                                DepositError::InvalidInput(format!(
+                                    "No BLS withdrawal credentials for validator seed index \
+                                    {validator_seed_index}, set them in validator index file \
+                                    or pass beacon_node_uri to look them up"
+                                ))
+                            })?;
+                        let withdrawal_keypair = keypair.withdrawal_keypair.ok_or_else(|| {
+                            DepositError::KeyDerivationError(
+                                "Error deriving withdrawal key from mnemonic".to_string(),
+                            )
+                        })?;
+                        let signed_bls_to_execution_change = bls_to_execution_change.sign(
+                            &withdrawal_keypair.sk,
+                            genesis_validators_root,
+                            &spec,
+                        );
+
+                        signed_bls_to_execution_change.clone().validate(
+                            bls_withdrawal_credentials.as_str(),
+                            execution_address.as_str(),
+                            &spec,
+                            &genesis_validators_root,
+                        )?;
+                        Ok(signed_bls_to_execution_change)
+                    },
+                )
+                .collect::<Result<_, DepositError>>()?;
+
+        if let Some(beacon_node_uri) = &self.beacon_node_uri {
+            signed_bls_to_execution_changes.send_beacon_payload(beacon_node_uri.clone())?;
         } else {
-            let export = signed_bls_to_execution_change.export();
+            let export = signed_bls_to_execution_changes.export();
 
-            let signed_bls_to_execution_change_json =
-                serde_json::to_string_pretty(&export).expect("could not parse validator export");
+            let signed_bls_to_execution_change_json = serde_json::to_string_pretty(&export)
+                .map_err(|_| {
+                    DepositError::SerializationError(
+                        "could not serialize bls to execution change".to_string(),
+                    )
+                })?;
             println!("{}", signed_bls_to_execution_change_json);
         }
+        Ok(())
     }
 }
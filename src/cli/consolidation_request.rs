@@ -0,0 +1,167 @@
+use clap::{arg, Parser};
+
+use crate::beacon_node;
+use crate::cli::request_fee::RequestFeeOpts;
+use crate::cli::secrets::{MnemonicOpts, PassphraseOpts};
+use crate::consolidation_request::{self, check_source_withdrawal_credentials};
+use crate::key_material::DerivationPaths;
+use crate::seed_index::validator_pubkeys;
+use crate::utils::parse_validator_pubkey;
+use crate::DepositError;
+
+#[derive(Clone, Parser)]
+pub struct ConsolidationRequestSubcommandOpts {
+    #[command(flatten)]
+    pub mnemonic: MnemonicOpts,
+
+    #[command(flatten)]
+    pub passphrase: PassphraseOpts,
+
+    /// The index of the first source validator's keys,
+    /// when source validators are derived from the mnemonic.
+    #[arg(
+        long,
+        visible_alias = "source_seed_index",
+        required_unless_present = "source_pubkey"
+    )]
+    pub source_seed_index: Option<u32>,
+
+    /// The number of source validators derived from the mnemonic, 1 by default.
+    #[arg(long, visible_alias = "num_validators", requires = "source_seed_index")]
+    pub num_validators: Option<u32>,
+
+    /// Source validator public key in hex form, instead of deriving it from the mnemonic.
+    ///
+    /// May be repeated, or passed as comma separated list.
+    #[arg(
+        long,
+        visible_alias = "source_pubkey",
+        value_delimiter = ',',
+        conflicts_with = "source_seed_index"
+    )]
+    pub source_pubkey: Vec<String>,
+
+    /// The index of target validator's keys,
+    /// when target validator is derived from the mnemonic.
+    #[arg(
+        long,
+        visible_alias = "target_seed_index",
+        required_unless_present = "target_pubkey"
+    )]
+    pub target_seed_index: Option<u32>,
+
+    /// Target validator public key in hex form, instead of deriving it from the mnemonic.
+    #[arg(
+        long,
+        visible_alias = "target_pubkey",
+        conflicts_with = "target_seed_index"
+    )]
+    pub target_pubkey: Option<String>,
+
+    #[command(flatten)]
+    pub derivation_paths: DerivationPaths,
+
+    /// Current withdrawal credentials of target validator.
+    ///
+    /// Target validator with execution (0x01) credentials is switched
+    /// to compounding (0x02) ones by additional request, before consolidations.
+    #[arg(
+        long,
+        visible_alias = "target_withdrawal_credentials",
+        required_unless_present = "beacon_node_uri"
+    )]
+    pub target_withdrawal_credentials: Option<String>,
+
+    /// Optional beacon node URL, to look up current withdrawal credentials
+    /// of target validator, and to check that source validators
+    /// have the same withdrawal address.
+    #[arg(long, visible_alias = "beacon_node_uri")]
+    pub beacon_node_uri: Option<url::Url>,
+
+    #[command(flatten)]
+    pub fee: RequestFeeOpts,
+}
+
+impl ConsolidationRequestSubcommandOpts {
+    pub fn run(&self) -> Result<(), DepositError> {
+        // Mnemonic is only read if any of validators is derived from it
+        let mnemonic = if self.source_seed_index.is_some() || self.target_seed_index.is_some() {
+            Some((self.mnemonic.read()?, self.passphrase.read(false)?))
+        } else if self.derivation_paths.is_set() {
+            return Err(DepositError::InvalidInput(
+                "Derivation paths can only be set for keys derived from mnemonic".to_string(),
+            ));
+        } else {
+            None
+        };
+        let pubkeys_from_mnemonic = |validator_start_index: u32, num_validators: u32| {
+            let (mnemonic, passphrase) = mnemonic.as_ref().unwrap();
+            validator_pubkeys(
+                mnemonic.as_bytes(),
+                passphrase.as_deref(),
+                validator_start_index,
+                num_validators,
+                Some(&self.derivation_paths),
+            )
+        };
+
+        let source_pubkeys = if let Some(source_seed_index) = self.source_seed_index {
+            pubkeys_from_mnemonic(source_seed_index, self.num_validators.unwrap_or(1))?
+        } else {
+            self.source_pubkey
+                .iter()
+                .map(String::as_str)
+                .map(parse_validator_pubkey)
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        let target_pubkey = if let Some(target_seed_index) = self.target_seed_index {
+            pubkeys_from_mnemonic(target_seed_index, 1)?[0]
+        } else {
+            parse_validator_pubkey(self.target_pubkey.as_deref().unwrap())?
+        };
+
+        let target_withdrawal_credentials = match &self.beacon_node_uri {
+            Some(beacon_node_uri) => {
+                let found = beacon_node::validator_by_pubkey(beacon_node_uri, &target_pubkey)?
+                    .withdrawal_credentials;
+                if let Some(supplied) = &self.target_withdrawal_credentials {
+                    if !supplied.eq_ignore_ascii_case(&found) {
+                        return Err(DepositError::InvalidWithdrawalCredentials(format!(
+                            "Target validator has withdrawal credentials {found} \
+                            at beacon node, instead of {supplied}"
+                        )));
+                    }
+                }
+                for source_pubkey in source_pubkeys.iter() {
+                    let source_withdrawal_credentials =
+                        beacon_node::validator_by_pubkey(beacon_node_uri, source_pubkey)?
+                            .withdrawal_credentials;
+                    check_source_withdrawal_credentials(
+                        source_pubkey,
+                        &source_withdrawal_credentials,
+                        &found,
+                    )?;
+                }
+                found
+            }
+            None => self.target_withdrawal_credentials.clone().unwrap(),
+        };
+
+        let requests = consolidation_request::consolidation_requests(
+            &source_pubkeys,
+            target_pubkey,
+            &target_withdrawal_credentials,
+        )?;
+        let export = requests
+            .iter()
+            .map(|request| request.export(self.fee.fee_wei))
+            .collect::<Vec<_>>();
+        let export_json = serde_json::to_string_pretty(&export).map_err(|_| {
+            DepositError::SerializationError(
+                "could not serialize consolidation requests".to_string(),
+            )
+        })?;
+        println!("{}", export_json);
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use clap::{arg, Parser};
+
+use crate::chain_spec::{chain_spec_for_network, chain_spec_from_file};
+use crate::deposit_transaction::{
+    batch_deposit_transaction, deposit_transactions, BatchDepositAbi,
+};
+use crate::{DepositError, DepositExport};
+
+#[derive(Clone, Parser)]
+pub struct DepositTransactionSubcommandOpts {
+    /// Path to deposit data JSON file, produced by eth-staking-smith
+    /// or staking-deposit-cli, e.g. deposit_data-1720014619.json
+    #[arg(long, visible_alias = "deposit_data_file")]
+    pub deposit_data_file: String,
+
+    /// The name of Ethereum PoS chain the deposits are targeting.
+    #[arg(value_enum, long, required_unless_present = "testnet_config")]
+    pub chain: Option<crate::networks::SupportedNetworks>,
+
+    /// Path to a custom Eth PoS chain config,
+    /// which sets deposit contract address and chain id.
+    #[arg(long, visible_alias = "testnet_config")]
+    pub testnet_config: Option<String>,
+
+    /// Address of batch deposit contract, to pack deposits into
+    /// transactions to this contract instead of one transaction per deposit.
+    #[arg(long, visible_alias = "batch_contract_address")]
+    pub batch_contract_address: Option<String>,
+
+    /// Signature of batch deposit contract function, with parameter names
+    /// telling which deposit data fields are passed in which order.
+    ///
+    /// Supported parameters are bytes pubkeys, bytes signatures and
+    /// bytes withdrawal_credentials, concatenated for all deposits,
+    /// bytes32 withdrawal_credentials shared by all deposits,
+    /// and optional bytes32[] deposit_data_roots.
+    #[arg(
+        long,
+        visible_alias = "batch_function",
+        requires = "batch_contract_address",
+        default_value_t = BatchDepositAbi::default()
+    )]
+    pub batch_function: BatchDepositAbi,
+
+    /// Maximum number of deposits packed into single batch transaction,
+    /// all deposits are packed into one transaction by default.
+    #[arg(
+        long,
+        visible_alias = "batch_size",
+        requires = "batch_contract_address",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub batch_size: Option<u32>,
+}
+
+impl DepositTransactionSubcommandOpts {
+    pub fn run(&self) -> Result<(), DepositError> {
+        let spec = if self.chain.is_some() && self.testnet_config.is_some() {
+            return Err(DepositError::InvalidInput(
+                "should only pass one of testnet_config or chain".to_string(),
+            ));
+        } else if let Some(testnet_config) = &self.testnet_config {
+            chain_spec_from_file(testnet_config.clone())?
+        } else {
+            chain_spec_for_network(self.chain.as_ref().unwrap())?
+        };
+
+        let deposit_data_json = std::fs::read_to_string(&self.deposit_data_file).map_err(|e| {
+            DepositError::FilesystemError(format!("Can not read deposit data file: {}", e))
+        })?;
+        let deposits: Vec<DepositExport> =
+            serde_json::from_str(&deposit_data_json).map_err(|e| {
+                DepositError::InvalidDepositData(format!("Can not parse deposit data file: {}", e))
+            })?;
+
+        let export_json = if let Some(batch_contract_address) = &self.batch_contract_address {
+            let batch_size = self.batch_size.map_or(deposits.len(), |n| n as usize);
+            let export = deposits
+                .chunks(batch_size.max(1))
+                .map(|batch| {
+                    batch_deposit_transaction(
+                        batch,
+                        &spec,
+                        batch_contract_address,
+                        &self.batch_function,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            serde_json::to_string_pretty(&export)
+        } else {
+            serde_json::to_string_pretty(&deposit_transactions(&deposits, &spec)?)
+        }
+        .map_err(|_| {
+            DepositError::SerializationError("could not serialize deposit transactions".to_string())
+        })?;
+        println!("{}", export_json);
+        Ok(())
+    }
+}
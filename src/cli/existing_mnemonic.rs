@@ -1,17 +1,31 @@
-use crate::{key_material::KdfVariant, networks::SupportedNetworks, Validators};
+use crate::{
+    cli::keystores::KeystoreOpts,
+    cli::secrets::{KeystorePasswordOpts, MnemonicOpts, PassphraseOpts},
+    key_material::{DerivationPaths, KdfParams},
+    networks::SupportedNetworks,
+    utils::init_thread_pool,
+    validators::{keystore_file_name, unix_timestamp, write_json_file},
+    DepositError, DepositExport, DepositParams, KeyDerivationParams, Validators,
+};
 use clap::{arg, Parser};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
 
 #[derive(Clone, Parser)]
 pub struct ExistingMnemonicSubcommandOpts {
-    /// The mnemonic that you used to generate your
-    /// keys.
-    ///
-    /// It is recommended not to use this
-    /// argument, and wait for the CLI to ask you
-    ///    for your mnemonic as otherwise it will
-    ///    appear in your shell history.
-    #[arg(long)]
-    pub mnemonic: String,
+    #[command(flatten)]
+    pub mnemonic: MnemonicOpts,
+
+    #[command(flatten)]
+    pub passphrase: PassphraseOpts,
+
+    // Existing keystores to generate deposit data for, e.g. to top up
+    // already active validators, instead of deriving keys from the mnemonic.
+    // Withdrawal credentials have to be set then, and keystores
+    // are exported as they are.
+    #[command(flatten)]
+    pub keystore: KeystoreOpts,
 
     /// The name of Ethereum PoS chain you are targeting.
     ///
@@ -24,23 +38,27 @@ pub struct ExistingMnemonicSubcommandOpts {
     /// generate.
     ///
     /// You can always generate more later
-    #[arg(long, visible_alias = "num_validators")]
-    pub num_validators: u32,
+    #[arg(
+        long,
+        visible_alias = "num_validators",
+        required_unless_present = "keystore",
+        conflicts_with = "keystore"
+    )]
+    pub num_validators: Option<u32>,
 
-    /// The password that will secure your keystores.
-    ///
-    /// You will need to re-enter this to
-    /// decrypt them when you setup your Ethereum
-    /// validators. If omitted, keystores will not be generated.
-    #[arg(long, visible_alias = "keystore_password")]
-    pub keystore_password: Option<String>,
+    #[command(flatten)]
+    pub keystore_password: KeystorePasswordOpts,
 
     /// The index of the first validator's keys you wish to generate the address for
     // e.g. if you generated 3 keys before (index #0, index #1, index #2)
     // and you want to generate for the 2nd validator,
     // the validator_start_index would be 1.
     // If no index specified, it will be set to 0.
-    #[arg(long, visible_alias = "validator_start_index")]
+    #[arg(
+        long,
+        visible_alias = "validator_start_index",
+        conflicts_with = "keystore"
+    )]
     pub validator_start_index: Option<u32>,
 
     /// If this field is set and valid, the given
@@ -49,18 +67,40 @@ pub struct ExistingMnemonicSubcommandOpts {
     /// generate withdrawal credentials with the
     /// mnemonic-derived withdrawal public key. Valid formats are
     /// ^(0x[a-fA-F0-9]{40})$ for execution addresses,
-    /// ^(0x01[0]{22}[a-fA-F0-9]{40})$ for execution withdrawal credentials
+    /// ^(0x01[0]{22}[a-fA-F0-9]{40})$ for execution withdrawal credentials,
+    /// ^(0x02[0]{22}[a-fA-F0-9]{40})$ for compounding withdrawal credentials
     /// and ^(0x00[a-fA-F0-9]{62})$ for BLS withdrawal credentials.
     #[arg(long, visible_alias = "withdrawal_credentials")]
     pub withdrawal_credentials: Option<String>,
 
-    /// Use this argument to select the key derivation function for the keystores.
+    /// Create compounding validators (EIP-7251) by turning the execution address
+    /// passed as withdrawal credentials into 0x02 credentials instead of 0x01.
+    #[arg(long, requires = "withdrawal_credentials")]
+    pub compounding: bool,
+
+    #[command(flatten)]
+    pub kdf: KdfParams,
+
+    #[command(flatten)]
+    pub derivation_paths: DerivationPaths,
+
+    /// Number of threads used to derive keys and encrypt keystores in parallel,
+    /// all available cores are used by default.
+    #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+    pub threads: Option<u16>,
+
+    /// Amount of each deposit in gwei, 32 ETH by default.
     ///
-    /// Depending on your use case with `scrypt` using higher security parameters
-    /// and consequently slower performance vs `pbkdf2`,
-    /// achieving better performance with lower security parameters compared to `scrypt`
-    #[arg(long)]
-    pub kdf: Option<KdfVariant>,
+    /// Must be at least the minimum deposit amount of the chain (1 ETH on mainnet),
+    /// and at most the max effective balance of the chain (32 ETH on mainnet),
+    /// or 2048 ETH for compounding validators.
+    /// Amounts below 32 ETH may be used to top up already active validators.
+    #[arg(
+        long,
+        visible_alias = "deposit_amount_gwei",
+        default_value_t = 32_000_000_000
+    )]
+    pub deposit_amount_gwei: u64,
 
     /// Path to a custom Eth PoS chain config
     #[arg(long, visible_alias = "testnet_config")]
@@ -69,45 +109,378 @@ pub struct ExistingMnemonicSubcommandOpts {
     /// A version of CLI to include into generated deposit data
     #[arg(long, visible_alias = "deposit_cli_version", default_value = "2.7.0")]
     pub deposit_cli_version: String,
+
+    /// Optional directory to write keystores and deposit data into, using
+    /// the `validator_keys` folder layout of staking-deposit-cli.
+    ///
+    /// If set, private keys and mnemonic are not printed on stdout,
+    /// instead the paths of written files are printed.
+    /// Every keystore is written as soon as it is generated, and deposit data
+    /// of generated validators is kept in `deposit_data-<timestamp>.jsonl` file,
+    /// until it is turned into deposit data file once all validators are generated.
+    ///
+    /// Keystore password has to be set, unless existing keystores are used,
+    /// which are then written as they are.
+    #[arg(long, visible_alias = "output_dir")]
+    pub output_dir: Option<String>,
+
+    /// Resume interrupted generation into output directory,
+    /// continuing from the last validator with recorded deposit data.
+    ///
+    /// The same mnemonic, start index, number of validators and deposit parameters
+    /// have to be passed, as recorded deposit data is checked against them.
+    #[arg(long, requires = "output_dir", conflicts_with = "keystore")]
+    pub resume: bool,
+
+    /// Print only keystores and deposit data, omitting private keys
+    /// and mnemonic from the output.
+    #[arg(long, visible_alias = "omit_secrets")]
+    pub omit_secrets: bool,
 }
 
 impl ExistingMnemonicSubcommandOpts {
-    pub fn run(&self) {
+    pub fn run(&self) -> Result<(), DepositError> {
         let chain = if self.chain.is_some() && self.testnet_config.is_some() {
-            panic!("should only pass one of testnet_config or chain")
+            return Err(DepositError::InvalidInput(
+                "should only pass one of testnet_config or chain".to_string(),
+            ));
         } else if self.testnet_config.is_some() {
             // Signalizes custom testnet config will be used
             None
         } else {
             self.chain.clone()
         };
+        let deposit_params = DepositParams {
+            network: chain,
+            withdrawal_credentials: self.withdrawal_credentials.clone(),
+            compounding: self.compounding,
+            deposit_amount_gwei: self.deposit_amount_gwei,
+            deposit_cli_version: self.deposit_cli_version.clone(),
+            chain_spec_file: self.testnet_config.clone(),
+        };
 
-        let password = self
-            .keystore_password
-            .clone()
-            .map(|p| p.as_bytes().to_owned());
+        init_thread_pool(self.threads.map(usize::from))?;
+        let password = self.keystore_password.read()?;
+
+        let keystores = self.keystore.read()?;
+        if let (None, Some(output_dir)) = (&keystores, &self.output_dir) {
+            if password.is_none() {
+                return Err(DepositError::InvalidInput(
+                    "Keystore password must be set to write keystores into output_dir".to_string(),
+                ));
+            }
+            return self.export_stream(Path::new(output_dir), &deposit_params, password);
+        }
+
+        let validators = if let Some(key_material) = keystores {
+            if password.is_some() || self.kdf.is_set() || self.derivation_paths.is_set() {
+                return Err(DepositError::InvalidInput(
+                    "Keystore password, kdf and derivation paths can not be set for existing keystores"
+                        .to_string(),
+                ));
+            }
+            if self.withdrawal_credentials.is_none() {
+                return Err(DepositError::InvalidWithdrawalCredentials(
+                    "Withdrawal credentials must be set for existing keystores".to_string(),
+                ));
+            }
+            Validators::from_key_material(key_material)
+        } else {
+            let mnemonic = self.mnemonic.read()?;
+            let passphrase = self.passphrase.read(false)?;
+            Validators::new(
+                Some(mnemonic.as_bytes()),
+                passphrase.as_deref(),
+                password,
+                self.num_validators,
+                self.validator_start_index,
+                &KeyDerivationParams {
+                    derive_withdrawal: self.withdrawal_credentials.is_none(),
+                    kdf: self.kdf.clone(),
+                    derivation_paths: self.derivation_paths.clone(),
+                },
+            )?
+        };
+        if let Some(output_dir) = &self.output_dir {
+            let written = validators.export_to_dir(Path::new(output_dir), &deposit_params)?;
+            for path in written {
+                println!("{}", path.display());
+            }
+            return Ok(());
+        }
+
+        let mut export = validators.export(&deposit_params)?;
+        if self.omit_secrets {
+            export = export.without_secrets();
+        }
+        // Serialize export directly, so that secrets are not copied
+        // into intermediate JSON value which is not zeroized
+        let export_json = Zeroizing::new(serde_json::to_string_pretty(&export).map_err(|_| {
+            DepositError::SerializationError("could not serialize validator export".to_string())
+        })?);
+        println!("{}", export_json.as_str());
+        Ok(())
+    }
+
+    /// Generate validators one by one into output directory, writing keystore
+    /// and recording deposit data of every validator as soon as it is generated.
+    fn export_stream(
+        &self,
+        output_dir: &Path,
+        deposit_params: &DepositParams,
+        password: Option<Vec<u8>>,
+    ) -> Result<(), DepositError> {
+        // Fail before any keystore or progress file is written
+        deposit_params.validate()?;
+        std::fs::create_dir_all(output_dir).map_err(|e| {
+            DepositError::FilesystemError(format!("Can not create {}: {e}", output_dir.display()))
+        })?;
+        let (timestamp, mut deposits) = match find_progress_file(output_dir)? {
+            Some((path, timestamp)) if self.resume => (timestamp, read_progress_file(&path)?),
+            Some((path, _)) => {
+                return Err(DepositError::InvalidInput(format!(
+                    "Unfinished generation found at {}, use --resume to continue it",
+                    path.display()
+                )))
+            }
+            None if self.resume => {
+                return Err(DepositError::InvalidInput(format!(
+                    "No unfinished generation to resume in {}",
+                    output_dir.display()
+                )))
+            }
+            None => (unix_timestamp()?, vec![]),
+        };
 
-        let validators = Validators::new(
-            Some(self.mnemonic.as_bytes()),
+        let num_validators = self.num_validators.unwrap_or(1);
+        let start_index = self.validator_start_index.unwrap_or(0);
+        let completed = deposits.len() as u32;
+        if completed > num_validators {
+            return Err(DepositError::InvalidInput(format!(
+                "{completed} validators were already generated, more than {num_validators} requested"
+            )));
+        }
+
+        let mnemonic = self.mnemonic.read()?;
+        let passphrase = self.passphrase.read(false)?;
+        // Resumed generation must continue the same validators,
+        // with the same withdrawal credentials, amount and chain
+        if completed > 0 {
+            let recorded_validators = Validators::stream(
+                mnemonic.as_bytes(),
+                passphrase.as_deref(),
+                None,
+                completed,
+                start_index,
+                &KeyDerivationParams {
+                    derive_withdrawal: self.withdrawal_credentials.is_none(),
+                    derivation_paths: self.derivation_paths.clone(),
+                    ..Default::default()
+                },
+            )?;
+            for (recorded, validator) in deposits.iter().zip(recorded_validators) {
+                let (index, key_material) = validator?;
+                let expected = DepositExport::from_key_material(&key_material, deposit_params)?;
+                check_recorded_deposit(index, recorded, &expected)?;
+            }
+        }
+
+        let progress_path = output_dir.join(format!("deposit_data-{timestamp}.jsonl"));
+        let mut progress = open_progress_file(&progress_path)?;
+        let validators = Validators::stream(
+            mnemonic.as_bytes(),
+            passphrase.as_deref(),
             password,
-            Some(self.num_validators),
-            self.validator_start_index,
-            self.withdrawal_credentials.is_none(),
-            self.kdf.clone(),
+            num_validators - completed,
+            start_index + completed,
+            &KeyDerivationParams {
+                derive_withdrawal: self.withdrawal_credentials.is_none(),
+                kdf: self.kdf.clone(),
+                derivation_paths: self.derivation_paths.clone(),
+            },
+        )?;
+        for validator in validators {
+            let (_, key_material) = validator?;
+            let keystore = key_material.keystore.as_ref().ok_or_else(|| {
+                DepositError::InvalidKeystore(
+                    "Keystore password must be set to export validator keys folder".to_string(),
+                )
+            })?;
+            let keystore_path = output_dir.join(keystore_file_name(keystore, timestamp)?);
+            // Keystore may be left from interrupted generation, before its deposit was recorded
+            if keystore_path.exists() {
+                std::fs::remove_file(&keystore_path).map_err(|e| {
+                    DepositError::FilesystemError(format!(
+                        "Can not remove {}: {e}",
+                        keystore_path.display()
+                    ))
+                })?;
+            }
+            write_json_file(&keystore_path, keystore)?;
+
+            let deposit = DepositExport::from_key_material(&key_material, deposit_params)?;
+            let deposit_json = serde_json::to_string(&deposit).map_err(|_| {
+                DepositError::SerializationError("could not serialize deposit data".to_string())
+            })?;
+            writeln!(progress, "{deposit_json}")
+                .and_then(|_| progress.sync_data())
+                .map_err(|e| {
+                    DepositError::FilesystemError(format!(
+                        "Can not write {}: {e}",
+                        progress_path.display()
+                    ))
+                })?;
+            deposits.push(deposit);
+            println!("{}", keystore_path.display());
+        }
+
+        let deposit_data_path = output_dir.join(format!("deposit_data-{timestamp}.json"));
+        write_json_file(&deposit_data_path, &deposits)?;
+        std::fs::remove_file(&progress_path).map_err(|e| {
+            DepositError::FilesystemError(format!(
+                "Can not remove {}: {e}",
+                progress_path.display()
+            ))
+        })?;
+        println!("{}", deposit_data_path.display());
+        Ok(())
+    }
+}
+
+/// Make sure deposit data recorded by interrupted generation matches
+/// deposit data of the validator with the same index, generated with current arguments.
+fn check_recorded_deposit(
+    index: u32,
+    recorded: &DepositExport,
+    expected: &DepositExport,
+) -> Result<(), DepositError> {
+    let mismatches: Vec<&str> = [
+        ("pubkey", recorded.pubkey == expected.pubkey),
+        (
+            "withdrawal_credentials",
+            recorded.withdrawal_credentials == expected.withdrawal_credentials,
+        ),
+        ("amount", recorded.amount == expected.amount),
+        (
+            "fork_version",
+            recorded.fork_version == expected.fork_version,
+        ),
+        (
+            "network_name",
+            recorded.network_name == expected.network_name,
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, matches)| !matches)
+    .map(|(field, _)| field)
+    .collect();
+    if !mismatches.is_empty() {
+        return Err(DepositError::InvalidInput(format!(
+            "Recorded deposit data of validator {index} does not match validators being generated, \
+            {} differ",
+            mismatches.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Find deposit data file of unfinished generation in output directory,
+/// returning its path and timestamp.
+fn find_progress_file(output_dir: &Path) -> Result<Option<(PathBuf, u64)>, DepositError> {
+    let entries = std::fs::read_dir(output_dir).map_err(|e| {
+        DepositError::FilesystemError(format!("Can not read {}: {e}", output_dir.display()))
+    })?;
+    let mut found = None;
+    for entry in entries {
+        let path = entry
+            .map_err(|e| {
+                DepositError::FilesystemError(format!("Can not read {}: {e}", output_dir.display()))
+            })?
+            .path();
+        let timestamp = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix("deposit_data-"))
+            .and_then(|name| name.strip_suffix(".jsonl"))
+            .and_then(|timestamp| timestamp.parse::<u64>().ok());
+        if let Some(timestamp) = timestamp {
+            if found.is_some() {
+                return Err(DepositError::InvalidInput(format!(
+                    "Multiple unfinished generations found in {}",
+                    output_dir.display()
+                )));
+            }
+            found = Some((path, timestamp));
+        }
+    }
+    Ok(found)
+}
+
+/// Read deposit data recorded so far, one entry per line.
+///
+/// Last line is discarded and truncated from the file if it was not
+/// written completely, e.g. when generation was interrupted.
+fn read_progress_file(path: &Path) -> Result<Vec<DepositExport>, DepositError> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        DepositError::FilesystemError(format!("Can not read {}: {e}", path.display()))
+    })?;
+    let complete_len = contents.rfind('\n').map_or(0, |pos| pos + 1);
+    if complete_len < contents.len() {
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(path)
+            .and_then(|file| file.set_len(complete_len as u64))
+            .map_err(|e| {
+                DepositError::FilesystemError(format!("Can not truncate {}: {e}", path.display()))
+            })?;
+    }
+    contents[..complete_len]
+        .lines()
+        .map(|line| {
+            serde_json::from_str(line).map_err(|e| {
+                DepositError::InvalidDepositData(format!(
+                    "Can not parse deposit data in {}: {e}",
+                    path.display()
+                ))
+            })
+        })
+        .collect()
+}
+
+fn open_progress_file(path: &Path) -> Result<std::fs::File, DepositError> {
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o640);
+    }
+    options
+        .open(path)
+        .map_err(|e| DepositError::FilesystemError(format!("Can not open {}: {e}", path.display())))
+}
+
+#[cfg(test)]
+mod test {
+    use super::read_progress_file;
+
+    #[test]
+    fn it_discards_incomplete_progress_line() {
+        let path = std::env::temp_dir().join(format!(
+            "eth-staking-smith-progress-{}.jsonl",
+            uuid::Uuid::new_v4()
+        ));
+        let deposit = r#"{"pubkey":"aa","withdrawal_credentials":"bb","amount":32000000000,"signature":"cc","deposit_message_root":"dd","deposit_data_root":"ee","fork_version":"00000000","network_name":"mainnet","deposit_cli_version":"2.7.0"}"#;
+        std::fs::write(&path, format!("{deposit}\n{{\"pubkey\":\"a")).unwrap();
+
+        let deposits = read_progress_file(&path).unwrap();
+        assert_eq!(1, deposits.len());
+        assert_eq!("aa", deposits[0].pubkey);
+        assert_eq!(
+            format!("{deposit}\n"),
+            std::fs::read_to_string(&path).unwrap()
         );
-        let export: serde_json::Value = validators
-            .export(
-                chain,
-                self.withdrawal_credentials.clone(),
-                32_000_000_000,
-                self.deposit_cli_version.clone(),
-                self.testnet_config.clone(),
-            )
-            .unwrap()
-            .try_into()
-            .expect("could not serialise validator export");
-        let export_json =
-            serde_json::to_string_pretty(&export).expect("could not parse validator export");
-        println!("{}", export_json);
+
+        std::fs::remove_file(&path).unwrap();
     }
 }
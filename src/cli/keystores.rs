@@ -0,0 +1,161 @@
+use std::path::{Path, PathBuf};
+
+use clap::{arg, Args};
+use eth2_keystore::Keystore;
+
+use crate::cli::secrets::read_secret;
+use crate::key_material::VotingKeyMaterial;
+use crate::DepositError;
+
+/// Options to sign with existing EIP-2335 keystores instead of mnemonic.
+#[derive(Args, Clone, Debug, Default)]
+pub struct KeystoreOpts {
+    /// Path to EIP-2335 keystore file, or to a directory of keystores.
+    ///
+    /// In a directory, every `keystore*.json` file is used, in the order of file names,
+    /// as written by staking-deposit-cli.
+    #[arg(
+        long,
+        requires = "keystore_password_file",
+        conflicts_with_all = ["mnemonic_source", "mnemonic_passphrase_source"]
+    )]
+    pub keystore: Option<String>,
+
+    /// Path to a file containing password of the keystore.
+    ///
+    /// For directory of keystores, it is either a single file with password
+    /// shared by all of them, or a directory with password file per keystore,
+    /// named after keystore file with `.txt` extension,
+    /// e.g. `keystore-m_12381_3600_0_0_0-1668613231.txt`.
+    /// Single trailing newline is stripped from file contents.
+    #[arg(long, visible_alias = "keystore_password_file", requires = "keystore")]
+    pub keystore_password_file: Option<String>,
+}
+
+impl KeystoreOpts {
+    /// Decrypt keystores, if keystore path is set.
+    pub fn read(&self) -> Result<Option<Vec<VotingKeyMaterial>>, DepositError> {
+        let (Some(keystore), Some(password_path)) = (&self.keystore, &self.keystore_password_file)
+        else {
+            return Ok(None);
+        };
+        let key_material = decrypt_keystores(Path::new(keystore), Path::new(password_path))?
+            .into_iter()
+            .map(|(_, key_material)| key_material)
+            .collect();
+        Ok(Some(key_material))
+    }
+}
+
+/// Decrypt keystore file or directory of keystores, with password file
+/// shared by all keystores, or directory of password files per keystore.
+/// Returns keystore file paths along with decrypted key material.
+pub(crate) fn decrypt_keystores(
+    keystore: &Path,
+    password_path: &Path,
+) -> Result<Vec<(PathBuf, VotingKeyMaterial)>, DepositError> {
+    keystore_files(keystore)?
+        .into_iter()
+        .map(|keystore_file| {
+            let password_file = if password_path.is_dir() {
+                let file_name = keystore_file
+                    .with_extension("txt")
+                    .file_name()
+                    .map(|name| name.to_os_string())
+                    .ok_or_else(|| {
+                        DepositError::InvalidInput(format!(
+                            "Invalid keystore file path {}",
+                            keystore_file.display()
+                        ))
+                    })?;
+                password_path.join(file_name)
+            } else {
+                password_path.to_path_buf()
+            };
+            let password = read_secret("keystore password", Some(&password_file), None, None)?
+                .ok_or_else(|| {
+                    DepositError::InvalidPassword(format!(
+                        "No password read for keystore {}",
+                        keystore_file.display()
+                    ))
+                })?;
+            let keystore = Keystore::from_json_file(&keystore_file).map_err(|e| {
+                DepositError::InvalidKeystore(format!(
+                    "Can not read keystore {}: {:?}",
+                    keystore_file.display(),
+                    e
+                ))
+            })?;
+            let key_material = VotingKeyMaterial::from_keystore(keystore, password.as_bytes())?;
+            Ok((keystore_file, key_material))
+        })
+        .collect()
+}
+
+/// List keystore files at the path, which is either a keystore file
+/// or a directory containing `keystore*.json` files.
+fn keystore_files(path: &Path) -> Result<Vec<PathBuf>, DepositError> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let entries = std::fs::read_dir(path).map_err(|e| {
+        DepositError::FilesystemError(format!("Can not read {}: {e}", path.display()))
+    })?;
+    let mut files = vec![];
+    for entry in entries {
+        let file = entry
+            .map_err(|e| {
+                DepositError::FilesystemError(format!("Can not read {}: {e}", path.display()))
+            })?
+            .path();
+        let is_keystore = file.is_file()
+            && file.extension().is_some_and(|ext| ext == "json")
+            && file
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("keystore"));
+        if is_keystore {
+            files.push(file);
+        }
+    }
+    if files.is_empty() {
+        return Err(DepositError::InvalidKeystore(format!(
+            "No keystore files found in {}",
+            path.display()
+        )));
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod test {
+    use super::keystore_files;
+
+    #[test]
+    fn it_lists_keystore_files_in_directory() {
+        let dir = std::env::temp_dir().join(format!(
+            "eth-staking-smith-keystores-{}",
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "keystore-m_12381_3600_1_0_0-1.json",
+            "keystore-m_12381_3600_0_0_0-1.json",
+            "deposit_data-1.json",
+            "keystore-m_12381_3600_0_0_0-1.txt",
+        ] {
+            std::fs::write(dir.join(name), "{}").unwrap();
+        }
+
+        let files = keystore_files(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            vec![
+                dir.join("keystore-m_12381_3600_0_0_0-1.json"),
+                dir.join("keystore-m_12381_3600_1_0_0-1.json"),
+            ],
+            files
+        );
+    }
+}
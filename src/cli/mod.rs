@@ -1,4 +1,14 @@
+pub mod batch;
 pub mod bls_to_execution_change;
+pub mod consolidation_request;
+pub mod deposit_transaction;
 pub mod existing_mnemonic;
+pub mod keystores;
 pub mod new_mnemonic;
 pub mod presigned_exit_message;
+pub mod recover_seed_index;
+pub mod reencrypt_keystore;
+pub mod request_fee;
+pub mod secrets;
+pub mod verify_deposit_data;
+pub mod withdrawal_request;
@@ -1,5 +1,15 @@
-use crate::{key_material::KdfVariant, networks::SupportedNetworks, Validators};
-use clap::{arg, Parser};
+use crate::{
+    cli::secrets::{KeystorePasswordOpts, PassphraseOpts},
+    key_material::KdfParams,
+    networks::SupportedNetworks,
+    seed::{create_new_seed, MnemonicLanguage},
+    utils::init_thread_pool,
+    DepositError, DepositParams, KeyDerivationParams, Validators,
+};
+use bip39::MnemonicType;
+use clap::{arg, builder::TypedValueParser, Parser};
+use std::path::Path;
+use zeroize::Zeroizing;
 
 #[derive(Parser, Clone)]
 pub struct NewMnemonicSubcommandOpts {
@@ -10,6 +20,31 @@ pub struct NewMnemonicSubcommandOpts {
     #[arg(value_enum, long)]
     pub chain: Option<SupportedNetworks>,
 
+    #[command(flatten)]
+    pub passphrase: PassphraseOpts,
+
+    /// Language of BIP-39 wordlist to generate the mnemonic with.
+    ///
+    /// Mnemonics in languages other than English may not be supported
+    /// by other wallets and tools.
+    #[arg(
+        value_enum,
+        long,
+        visible_alias = "mnemonic_language",
+        default_value = "english"
+    )]
+    pub mnemonic_language: MnemonicLanguage,
+
+    /// The number of words in the generated mnemonic.
+    #[arg(
+        long,
+        visible_alias = "mnemonic_word_count",
+        default_value_t = 24,
+        value_parser = clap::builder::PossibleValuesParser::new(["12", "15", "18", "21", "24"])
+            .map(|s| s.parse::<usize>().unwrap())
+    )]
+    pub mnemonic_word_count: usize,
+
     /// The number of new validator keys you want to
     /// generate.
     ///
@@ -17,13 +52,8 @@ pub struct NewMnemonicSubcommandOpts {
     #[arg(long, visible_alias = "num_validators")]
     pub num_validators: u32,
 
-    /// The password that will secure your keystores.
-    ///
-    /// You will need to re-enter this to
-    /// decrypt them when you setup your Ethereum
-    /// validators. If omitted, keystores will not be generated.
-    #[arg(long, visible_alias = "keystore_password")]
-    pub keystore_password: Option<String>,
+    #[command(flatten)]
+    pub keystore_password: KeystorePasswordOpts,
 
     /// The index of the first validator's keys you wish to generate the address for
     // e.g. if you generated 3 keys before (index #0, index #1, index #2)
@@ -39,18 +69,37 @@ pub struct NewMnemonicSubcommandOpts {
     /// generate withdrawal credentials with the
     /// mnemonic-derived withdrawal public key. Valid formats are
     /// ^(0x[a-fA-F0-9]{40})$ for execution addresses,
-    /// ^(0x01[0]{22}[a-fA-F0-9]{40})$ for execution withdrawal credentials
+    /// ^(0x01[0]{22}[a-fA-F0-9]{40})$ for execution withdrawal credentials,
+    /// ^(0x02[0]{22}[a-fA-F0-9]{40})$ for compounding withdrawal credentials
     /// and ^(0x00[a-fA-F0-9]{62})$ for BLS withdrawal credentials.
     #[arg(long, visible_alias = "withdrawal_credentials")]
     pub withdrawal_credentials: Option<String>,
 
-    /// Use this argument to select the key derivation function for the keystores.
+    /// Create compounding validators (EIP-7251) by turning the execution address
+    /// passed as withdrawal credentials into 0x02 credentials instead of 0x01.
+    #[arg(long, requires = "withdrawal_credentials")]
+    pub compounding: bool,
+
+    #[command(flatten)]
+    pub kdf: KdfParams,
+
+    /// Number of threads used to derive keys and encrypt keystores in parallel,
+    /// all available cores are used by default.
+    #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+    pub threads: Option<u16>,
+
+    /// Amount of each deposit in gwei, 32 ETH by default.
     ///
-    /// Depending on your use case with `scrypt` using higher security parameters
-    /// and consequently slower performance vs `pbkdf2`,
-    /// achieving better performance with lower security parameters compared to `scrypt`
-    #[arg(long)]
-    pub kdf: Option<KdfVariant>,
+    /// Must be at least the minimum deposit amount of the chain (1 ETH on mainnet),
+    /// and at most the max effective balance of the chain (32 ETH on mainnet),
+    /// or 2048 ETH for compounding validators.
+    /// Amounts below 32 ETH may be used to top up already active validators.
+    #[arg(
+        long,
+        visible_alias = "deposit_amount_gwei",
+        default_value_t = 32_000_000_000
+    )]
+    pub deposit_amount_gwei: u64,
 
     /// Path to a custom Eth PoS chain config
     #[arg(long, visible_alias = "testnet_config")]
@@ -59,45 +108,94 @@ pub struct NewMnemonicSubcommandOpts {
     /// A version of CLI to include into generated deposit data
     #[arg(long, visible_alias = "deposit_cli_version", default_value = "2.7.0")]
     pub deposit_cli_version: String,
+
+    /// Optional directory to write keystores and deposit data into, using
+    /// the `validator_keys` folder layout of staking-deposit-cli.
+    ///
+    /// If set, private keys and mnemonic are not printed on stdout,
+    /// instead the paths of written files are printed.
+    #[arg(
+        long,
+        visible_alias = "output_dir",
+        requires = "keystore_password_source"
+    )]
+    pub output_dir: Option<String>,
+
+    /// Print only keystores and deposit data, omitting private keys
+    /// and mnemonic from the output.
+    #[arg(long, visible_alias = "omit_secrets")]
+    pub omit_secrets: bool,
 }
 
 impl NewMnemonicSubcommandOpts {
-    pub fn run(&self) {
+    pub fn run(&self) -> Result<(), DepositError> {
         let chain = if self.chain.is_some() && self.testnet_config.is_some() {
-            panic!("should only pass one of testnet_config or chain")
+            return Err(DepositError::InvalidInput(
+                "should only pass one of testnet_config or chain".to_string(),
+            ));
         } else if self.testnet_config.is_some() {
             // Signalizes custom testnet config will be used
             None
         } else {
             self.chain.clone()
         };
+        let deposit_params = DepositParams {
+            network: chain,
+            withdrawal_credentials: self.withdrawal_credentials.clone(),
+            compounding: self.compounding,
+            deposit_amount_gwei: self.deposit_amount_gwei,
+            deposit_cli_version: self.deposit_cli_version.clone(),
+            chain_spec_file: self.testnet_config.clone(),
+        };
 
-        let password = self
-            .keystore_password
-            .clone()
-            .map(|p| p.as_bytes().to_owned());
+        init_thread_pool(self.threads.map(usize::from))?;
+        let password = self.keystore_password.read()?;
 
-        let validators = Validators::new(
-            None,
+        let mnemonic_type = MnemonicType::for_word_count(self.mnemonic_word_count)
+            .map_err(|e| DepositError::InvalidInput(format!("Invalid mnemonic word count: {e}")))?;
+        let mnemonic = create_new_seed(mnemonic_type, self.mnemonic_language.clone().into())?;
+
+        let passphrase = self.passphrase.read(true)?;
+        let validators = Validators::from_mnemonic(
+            &mnemonic,
+            passphrase.as_deref(),
             password,
             Some(self.num_validators),
             None,
-            self.withdrawal_credentials.is_none(),
-            self.kdf.clone(),
-        );
-        let export: serde_json::Value = validators
-            .export(
-                chain,
-                self.withdrawal_credentials.clone(),
-                32_000_000_000,
-                self.deposit_cli_version.clone(),
-                self.testnet_config.clone(),
-            )
-            .unwrap()
-            .try_into()
-            .expect("could not serialise validator export");
-        let export_json =
-            serde_json::to_string_pretty(&export).expect("could not parse validator export");
-        println!("{}", export_json);
+            &KeyDerivationParams {
+                derive_withdrawal: self.withdrawal_credentials.is_none(),
+                kdf: self.kdf.clone(),
+                ..Default::default()
+            },
+        )?;
+        if let Some(output_dir) = &self.output_dir {
+            let written = validators.export_to_dir(Path::new(output_dir), &deposit_params)?;
+            // New mnemonic is not written anywhere, so show it on the terminal
+            // without mixing it into stdout, which is often redirected.
+            eprintln!(
+                "MAKE SURE TO BACK UP YOUR MNEMONIC IN A SAFE PLACE: {}",
+                validators.mnemonic_phrase().unwrap_or_default()
+            );
+            for path in written {
+                println!("{}", path.display());
+            }
+            return Ok(());
+        }
+
+        let mut export = validators.export(&deposit_params)?;
+        if self.omit_secrets {
+            export = export.without_secrets();
+            eprintln!(
+                "MAKE SURE TO BACK UP YOUR MNEMONIC IN A SAFE PLACE: {}",
+                validators.mnemonic_phrase().unwrap_or_default()
+            );
+        }
+        // Serialize export directly, so that secrets are not copied
+        // into intermediate JSON value which is not zeroized
+        let export_json = Zeroizing::new(serde_json::to_string_pretty(&export).map_err(|_| {
+            DepositError::SerializationError("could not serialize validator export".to_string())
+        })?);
+        println!("{}", export_json.as_str());
+        Ok(())
     }
 }
@@ -1,20 +1,28 @@
 use clap::{arg, Parser};
 
-use crate::beacon_node::BeaconNodeExportable;
+use crate::beacon_node::{self, BeaconNodeExportable};
+use crate::chain_spec::{validators_root_and_spec, validators_root_and_spec_from_beacon_node};
+use crate::cli::batch;
+use crate::cli::keystores::KeystoreOpts;
+use crate::cli::secrets::{MnemonicOpts, PassphraseOpts};
+use crate::key_material::DerivationPaths;
+use crate::voluntary_exit;
 use crate::voluntary_exit::operations::SignedVoluntaryExitValidator;
-use crate::{chain_spec::validators_root_and_spec, voluntary_exit};
+use crate::DepositError;
 
 #[derive(Clone, Parser)]
 pub struct PresignedExitMessageSubcommandOpts {
-    /// The mnemonic that you used to generate your
-    /// keys.
-    ///
-    /// It is recommended not to use this
-    /// argument, and wait for the CLI to ask you
-    ///    for your mnemonic as otherwise it will
-    ///    appear in your shell history.
-    #[arg(long, required_unless_present = "private_key")]
-    pub mnemonic: Option<String>,
+    #[command(flatten)]
+    pub mnemonic: MnemonicOpts,
+
+    #[command(flatten)]
+    pub passphrase: PassphraseOpts,
+
+    #[command(flatten)]
+    pub keystore: KeystoreOpts,
+
+    #[command(flatten)]
+    pub derivation_paths: DerivationPaths,
 
     /// The name of Ethereum PoS chain you are targeting.
     ///
@@ -31,42 +39,89 @@ pub struct PresignedExitMessageSubcommandOpts {
     #[arg(
         long,
         visible_alias = "validator_seed_index",
-        required_unless_present = "private_key"
+        required_unless_present_any = ["private_key", "keystore"],
+        conflicts_with = "keystore"
     )]
     pub validator_seed_index: Option<u32>,
 
     /// Validator private key bytes in hex form
-    #[arg(long, required_unless_present_all = ["mnemonic", "validator_seed_index"])]
+    #[arg(
+        long,
+        required_unless_present_any = ["validator_seed_index", "keystore"],
+        conflicts_with_all = ["mnemonic_source", "validator_seed_index", "keystore"]
+    )]
     pub private_key: Option<String>,
 
     /// On-chain beacon index of the validator.
-    #[arg(long, visible_alias = "validator_beacon_index")]
-    pub validator_beacon_index: u32,
+    /// May be omitted if `beacon_node_uri` is set, in which case
+    /// the index is looked up at beacon node by validator public key.
+    /// For directory of keystores, `beacon_node_uri` has to be used instead.
+    #[arg(
+        long,
+        visible_alias = "validator_beacon_index",
+        required_unless_present_any = ["validator_index_file", "beacon_node_uri"]
+    )]
+    pub validator_beacon_index: Option<u64>,
+
+    /// Path to CSV or JSON file mapping validator seed indices to on-chain
+    /// beacon indices, to generate presigned exit messages in batch
+    /// for `num_validators` validators starting from `validator_seed_index`.
+    ///
+    /// CSV file has `validator_seed_index,validator_beacon_index` columns,
+    /// and JSON file is an array of objects with the same keys.
+    #[arg(
+        long,
+        visible_alias = "validator_index_file",
+        requires_all = ["validator_seed_index", "num_validators"],
+        conflicts_with = "validator_beacon_index"
+    )]
+    pub validator_index_file: Option<String>,
+
+    /// The number of validators to generate presigned exit messages for
+    /// in batch mode.
+    #[arg(
+        long,
+        visible_alias = "num_validators",
+        requires = "validator_index_file"
+    )]
+    pub num_validators: Option<u32>,
 
     /// Epoch number which must be included in the presigned exit message.
     #[arg(long, visible_alias = "execution_address")]
     pub epoch: u64,
 
-    /// Path to a custom Eth PoS chain config
+    /// Path to a custom Eth PoS chain config.
+    ///
+    /// May be omitted if `beacon_node_uri` is set, then chain config
+    /// is fetched from beacon node, and the supplied one is only checked against it.
+    /// As the message is then sent to beacon node, pass both `testnet_config`
+    /// and `genesis_validators_root` to only print it.
     #[arg(long, visible_alias = "testnet_config")]
     pub testnet_config: Option<String>,
 
     /// Custom genesis validators root for the custom testnet, passed as hex string.
     /// See https://eth2book.info/capella/part3/containers/state/ for value
-    /// description
+    /// description.
+    ///
+    /// May be omitted for custom testnet if `beacon_node_uri` is set,
+    /// then it is fetched from beacon node.
     #[arg(long, visible_alias = "genesis_validators_root")]
     pub genesis_validators_root: Option<String>,
 
     /// Optional beacon node URL. If set, the presigned-exit-message value
-    /// will not be printed on stdout, but instead sent to beacon node
+    /// will not be printed on stdout, but instead sent to beacon node.
+    /// Validator beacon index is then checked against the one known
+    /// to beacon node for the validator public key.
     #[arg(long, visible_alias = "beacon_node_uri")]
     pub beacon_node_uri: Option<url::Url>,
 }
 
 impl PresignedExitMessageSubcommandOpts {
-    pub fn run(&self) {
+    pub fn run(&self) -> Result<(), DepositError> {
         let chain = if self.chain.is_some() && self.testnet_config.is_some() {
-            panic!("should only pass one of testnet_config or chain")
+            return Err(DepositError::InvalidInput(
+                "should only pass one of testnet_config or chain".to_string(),
+            ));
         } else if self.testnet_config.is_some() {
             // Signalizes custom testnet config will be used
             None
@@ -74,59 +129,150 @@ impl PresignedExitMessageSubcommandOpts {
             self.chain.clone()
         };
 
-        let (genesis_validators_root, spec) = validators_root_and_spec(
-            chain.clone(),
-            if chain.is_some() {
-                None
-            } else {
-                Some((
-                    self.genesis_validators_root
-                        .clone()
-                        .expect("Genesis validators root parameter must be set"),
-                    self.testnet_config
-                        .clone()
-                        .expect("Testnet config must be set"),
-                ))
-            },
-        );
-
-        let (voluntary_exit, key_material) = if self.mnemonic.is_some() {
-            voluntary_exit::voluntary_exit_message_from_mnemonic(
-                self.mnemonic.clone().unwrap().as_bytes(),
-                self.validator_seed_index.unwrap() as u64,
-                self.validator_beacon_index as u64,
+        let (genesis_validators_root, spec) = match (&chain, &self.beacon_node_uri) {
+            // Custom testnet parameters are fetched from beacon node if possible
+            (None, Some(beacon_node_uri)) => validators_root_and_spec_from_beacon_node(
+                beacon_node_uri,
+                (
+                    self.genesis_validators_root.clone(),
+                    self.testnet_config.clone(),
+                ),
+            )?,
+            _ => validators_root_and_spec(
+                chain.clone(),
+                self.genesis_validators_root
+                    .clone()
+                    .zip(self.testnet_config.clone()),
+            )?,
+        };
+
+        if self.validator_seed_index.is_none() && self.derivation_paths.is_set() {
+            return Err(DepositError::InvalidInput(
+                "Derivation paths can only be set for keys derived from mnemonic".to_string(),
+            ));
+        }
+
+        let keystores = self.keystore.read()?;
+        let mut exits = if let Some(key_materials) = keystores {
+            if key_materials.len() > 1 && self.validator_beacon_index.is_some() {
+                return Err(DepositError::InvalidInput(
+                    "Beacon node URI must be used to look up beacon indices of multiple keystores"
+                        .to_string(),
+                ));
+            }
+            key_materials
+                .into_iter()
+                .map(|key_material| {
+                    voluntary_exit::voluntary_exit_message_from_key_material(
+                        key_material,
+                        self.validator_beacon_index.unwrap_or_default(),
+                        self.epoch,
+                    )
+                })
+                .collect()
+        } else if let Some(validator_index_file) = &self.validator_index_file {
+            let entries = batch::entries_for_seed_range(
+                &batch::read_batch_file(validator_index_file)?,
+                self.validator_seed_index.unwrap(),
+                self.num_validators.unwrap(),
+            )?;
+            let validator_indices: Vec<(u64, u64)> = entries
+                .iter()
+                .map(|entry| {
+                    (
+                        entry.validator_seed_index as u64,
+                        entry.validator_beacon_index,
+                    )
+                })
+                .collect();
+            voluntary_exit::voluntary_exit_messages_from_mnemonic(
+                self.mnemonic.read()?.as_bytes(),
+                self.passphrase.read(false)?.as_deref(),
+                &validator_indices,
                 self.epoch,
-            )
+                Some(&self.derivation_paths),
+            )?
+        } else if let Some(validator_seed_index) = self.validator_seed_index {
+            vec![voluntary_exit::voluntary_exit_message_from_mnemonic(
+                self.mnemonic.read()?.as_bytes(),
+                self.passphrase.read(false)?.as_deref(),
+                validator_seed_index as u64,
+                self.validator_beacon_index.unwrap_or_default(),
+                self.epoch,
+                Some(&self.derivation_paths),
+            )?]
         } else {
             let secret_key_str = self.private_key.clone().unwrap();
-            let secret_key_bytes =
-                hex::decode(secret_key_str.strip_prefix("0x").unwrap_or(&secret_key_str))
-                    .expect("Invalid private key hex input");
-            voluntary_exit::voluntary_exit_message_from_secret_key(
+            let secret_key_bytes = hex::decode(
+                secret_key_str.strip_prefix("0x").unwrap_or(&secret_key_str),
+            )
+            .map_err(|_| {
+                DepositError::InvalidPrivateKey("Invalid private key hex input".to_string())
+            })?;
+            vec![voluntary_exit::voluntary_exit_message_from_secret_key(
                 secret_key_bytes.as_slice(),
-                self.validator_beacon_index as u64,
+                self.validator_beacon_index.unwrap_or_default(),
                 self.epoch,
-            )
+            )?]
         };
 
-        let signed_voluntary_exit =
-            voluntary_exit.sign(&key_material.keypair.sk, genesis_validators_root, &spec);
+        if let Some(beacon_node_uri) = &self.beacon_node_uri {
+            for (voluntary_exit, key_material) in exits.iter_mut() {
+                let supplied_index = if self.validator_index_file.is_some() {
+                    Some(voluntary_exit.validator_index)
+                } else {
+                    self.validator_beacon_index
+                };
+                voluntary_exit.validator_index = beacon_node::resolve_validator(
+                    beacon_node_uri,
+                    &key_material.keypair.pk.clone().into(),
+                    supplied_index,
+                )?
+                .index;
+            }
+        }
+
+        let signed_voluntary_exits = exits
+            .into_iter()
+            .map(|(voluntary_exit, key_material)| {
+                let signed_voluntary_exit =
+                    voluntary_exit.sign(&key_material.keypair.sk, genesis_validators_root, &spec);
 
-        signed_voluntary_exit.clone().validate(
-            &key_material.keypair.pk,
-            &spec,
-            &genesis_validators_root,
-        );
+                signed_voluntary_exit.clone().validate(
+                    &key_material.keypair.pk,
+                    &spec,
+                    &genesis_validators_root,
+                )?;
+                Ok(signed_voluntary_exit)
+            })
+            .collect::<Result<Vec<_>, DepositError>>()?;
 
-        if self.beacon_node_uri.is_some() {
-            signed_voluntary_exit
-                .send_beacon_payload(self.beacon_node_uri.clone().unwrap())
-                .unwrap_or_else(|e| panic!("Failed sending beacon node payload: {:?}", e))
+        if let Some(beacon_node_uri) = &self.beacon_node_uri {
+            // Beacon node accepts one voluntary exit per request
+            for signed_voluntary_exit in signed_voluntary_exits.iter() {
+                signed_voluntary_exit.send_beacon_payload(beacon_node_uri.clone())?;
+            }
         } else {
-            let export = signed_voluntary_exit.export();
+            // Batch and multiple keystores are exported as array of messages
+            let export = if self.validator_index_file.is_some() || signed_voluntary_exits.len() > 1
+            {
+                serde_json::Value::Array(
+                    signed_voluntary_exits
+                        .iter()
+                        .map(|signed_voluntary_exit| signed_voluntary_exit.export())
+                        .collect(),
+                )
+            } else {
+                signed_voluntary_exits.first().unwrap().export()
+            };
             let presigned_exit_message_json =
-                serde_json::to_string_pretty(&export).expect("could not parse validator export");
+                serde_json::to_string_pretty(&export).map_err(|_| {
+                    DepositError::SerializationError(
+                        "could not serialize presigned exit message".to_string(),
+                    )
+                })?;
             println!("{}", presigned_exit_message_json);
         }
+        Ok(())
     }
 }
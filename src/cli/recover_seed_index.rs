@@ -0,0 +1,94 @@
+use clap::{arg, Parser};
+
+use crate::cli::secrets::{MnemonicOpts, PassphraseOpts};
+use crate::key_material::DerivationPaths;
+use crate::seed_index::find_seed_indices;
+use crate::utils::{init_thread_pool, parse_validator_pubkey};
+use crate::DepositError;
+
+#[derive(Clone, Parser)]
+pub struct RecoverSeedIndexSubcommandOpts {
+    #[command(flatten)]
+    pub mnemonic: MnemonicOpts,
+
+    #[command(flatten)]
+    pub passphrase: PassphraseOpts,
+
+    /// Validator public key to find seed index for, in hex form.
+    ///
+    /// May be repeated, or passed as comma separated list,
+    /// in which case all public keys are searched for in one pass.
+    #[arg(long, required = true, value_delimiter = ',')]
+    pub pubkey: Vec<String>,
+
+    /// The first validator seed index to search from, 0 by default.
+    #[arg(long, visible_alias = "validator_start_index", default_value_t = 0)]
+    pub validator_start_index: u32,
+
+    /// The number of validator seed indices to search.
+    #[arg(long, visible_alias = "num_validators", default_value_t = 1000)]
+    pub num_validators: u32,
+
+    /// Match public keys against withdrawal keys as well,
+    /// e.g. to find BLS withdrawal key of the validator.
+    #[arg(long, visible_alias = "match_withdrawal")]
+    pub match_withdrawal: bool,
+
+    #[command(flatten)]
+    pub derivation_paths: DerivationPaths,
+
+    /// Number of threads used to derive keys in parallel,
+    /// all available cores are used by default.
+    #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+    pub threads: Option<u16>,
+}
+
+impl RecoverSeedIndexSubcommandOpts {
+    pub fn run(&self) -> Result<(), DepositError> {
+        let pubkey_hex = self
+            .pubkey
+            .iter()
+            .map(|pubkey| {
+                let pubkey = pubkey.trim().to_lowercase();
+                format!("0x{}", pubkey.strip_prefix("0x").unwrap_or(&pubkey))
+            })
+            .collect::<Vec<_>>();
+        let pubkeys = pubkey_hex
+            .iter()
+            .map(String::as_str)
+            .map(parse_validator_pubkey)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        init_thread_pool(self.threads.map(usize::from))?;
+        let mnemonic = self.mnemonic.read()?;
+        let passphrase = self.passphrase.read(false)?;
+        let matches = find_seed_indices(
+            mnemonic.as_bytes(),
+            passphrase.as_deref(),
+            &pubkeys,
+            self.validator_start_index,
+            self.num_validators,
+            self.match_withdrawal,
+            Some(&self.derivation_paths),
+        )?;
+
+        let matches_json = serde_json::to_string_pretty(&matches).map_err(|_| {
+            DepositError::SerializationError("could not serialize seed indices".to_string())
+        })?;
+        println!("{}", matches_json);
+
+        if matches.len() < pubkeys.len() {
+            let not_found = pubkey_hex
+                .into_iter()
+                .filter(|pubkey| !matches.iter().any(|found| &found.pubkey == pubkey))
+                .collect::<Vec<_>>();
+            return Err(DepositError::InvalidInput(format!(
+                "No seed index found in range {}..{} for public keys: {}",
+                self.validator_start_index,
+                self.validator_start_index as u64 + self.num_validators as u64,
+                not_found.join(", ")
+            )));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use std::path::Path;
+
+use clap::{arg, Parser};
+use zeroize::Zeroizing;
+
+use crate::cli::keystores::decrypt_keystores;
+use crate::cli::secrets::KeystorePasswordOpts;
+use crate::key_material::KdfParams;
+use crate::validators::write_json_file;
+use crate::DepositError;
+
+#[derive(Clone, Parser)]
+pub struct ReencryptKeystoreSubcommandOpts {
+    /// Path to EIP-2335 keystore file, or to a directory of keystores.
+    ///
+    /// In a directory, every `keystore*.json` file is re-encrypted.
+    #[arg(long)]
+    pub keystore: String,
+
+    /// Path to a file containing current password of the keystore.
+    ///
+    /// For directory of keystores, it is either a single file with password
+    /// shared by all of them, or a directory with password file per keystore,
+    /// named after keystore file with `.txt` extension.
+    #[arg(long, visible_alias = "keystore_password_file")]
+    pub keystore_password_file: String,
+
+    #[command(flatten)]
+    pub new_password: KeystorePasswordOpts,
+
+    #[command(flatten)]
+    pub kdf: KdfParams,
+
+    /// Keep UUID of the original keystore, instead of generating new one.
+    #[arg(long, visible_alias = "keep_uuid")]
+    pub keep_uuid: bool,
+
+    /// Directory to write re-encrypted keystores into, under the same file names.
+    ///
+    /// Existing files are never overwritten, so it has to be
+    /// different from the directory of original keystores.
+    #[arg(long, visible_alias = "output_dir")]
+    pub output_dir: String,
+}
+
+impl ReencryptKeystoreSubcommandOpts {
+    pub fn run(&self) -> Result<(), DepositError> {
+        let new_password = Zeroizing::new(self.new_password.read()?.ok_or_else(|| {
+            DepositError::InvalidPassword("New keystore password must be set".to_string())
+        })?);
+        let kdf = self.kdf.to_kdf()?;
+
+        let keystores = decrypt_keystores(
+            Path::new(&self.keystore),
+            Path::new(&self.keystore_password_file),
+        )?;
+
+        let output_dir = Path::new(&self.output_dir);
+        std::fs::create_dir_all(output_dir).map_err(|e| {
+            DepositError::FilesystemError(format!("Can not create {}: {e}", output_dir.display()))
+        })?;
+
+        for (keystore_file, key_material) in keystores {
+            let keystore =
+                key_material.reencrypt_keystore(&new_password, kdf.clone(), self.keep_uuid)?;
+            let file_name = keystore_file.file_name().ok_or_else(|| {
+                DepositError::InvalidInput(format!(
+                    "Invalid keystore file path {}",
+                    keystore_file.display()
+                ))
+            })?;
+            let file_path = output_dir.join(file_name);
+            write_json_file(&file_path, &keystore)?;
+            println!("{}", file_path.display());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,14 @@
+use clap::{arg, Args};
+
+/// Fee for requests sent to execution layer predeploy contracts,
+/// such as withdrawal and consolidation requests.
+#[derive(Args, Clone, Debug)]
+pub struct RequestFeeOpts {
+    /// Fee paid to the request contract by every transaction, in wei.
+    ///
+    /// Current fee is returned by the contract for a call without calldata,
+    /// and grows with the number of requests queued. Transaction with lower fee
+    /// is reverted, and overpaid fee is not refunded, so there is no default.
+    #[arg(long, visible_alias = "fee_wei")]
+    pub fee_wei: u128,
+}
@@ -0,0 +1,301 @@
+use std::path::Path;
+
+use clap::{arg, Args};
+use zeroize::Zeroizing;
+
+use crate::DepositError;
+
+/// Options to supply the mnemonic.
+///
+/// Passing mnemonic as plain argument value makes it appear in shell history
+/// and process list, so other sources are preferred. If no source is set,
+/// mnemonic is asked for on the terminal, without echoing it.
+#[derive(Args, Clone, Debug, Default)]
+#[group(id = "mnemonic_source", multiple = false)]
+pub struct MnemonicOpts {
+    /// The mnemonic that you used to generate your
+    /// keys.
+    ///
+    /// It is recommended not to use this
+    /// argument, and wait for the CLI to ask you
+    ///    for your mnemonic as otherwise it will
+    ///    appear in your shell history.
+    #[arg(long)]
+    pub mnemonic: Option<String>,
+
+    /// Path to a file containing the mnemonic.
+    #[arg(long, visible_alias = "mnemonic_file")]
+    pub mnemonic_file: Option<String>,
+
+    /// Number of open file descriptor to read the mnemonic from,
+    /// e.g. 3 for `3< mnemonic.txt` shell redirection.
+    /// Only supported on Unix platforms.
+    #[arg(long, visible_alias = "mnemonic_fd")]
+    pub mnemonic_fd: Option<u32>,
+
+    /// Name of environment variable containing the mnemonic.
+    #[arg(long, visible_alias = "mnemonic_env")]
+    pub mnemonic_env: Option<String>,
+}
+
+impl MnemonicOpts {
+    /// Read mnemonic from the source set, or from terminal prompt if none is.
+    pub fn read(&self) -> Result<Zeroizing<String>, DepositError> {
+        let mnemonic = match &self.mnemonic {
+            Some(mnemonic) => Some(Zeroizing::new(mnemonic.clone())),
+            None => read_secret(
+                "mnemonic",
+                self.mnemonic_file.as_deref().map(Path::new),
+                self.mnemonic_fd,
+                self.mnemonic_env.as_deref(),
+            )?,
+        };
+        let mnemonic = match mnemonic {
+            Some(mnemonic) => mnemonic,
+            None => prompt_secret("mnemonic", false)?,
+        };
+        // Words are separated by any whitespace, so it is safe to trim it entirely
+        Ok(Zeroizing::new(mnemonic.trim().to_string()))
+    }
+}
+
+/// Options to supply the password that will secure your keystores.
+///
+/// You will need to re-enter this to
+/// decrypt them when you setup your Ethereum
+/// validators. If omitted, keystores will not be generated.
+#[derive(Args, Clone, Debug, Default)]
+#[group(id = "keystore_password_source", multiple = false)]
+pub struct KeystorePasswordOpts {
+    /// The password that will secure your keystores.
+    ///
+    /// You will need to re-enter this to
+    /// decrypt them when you setup your Ethereum
+    /// validators. If omitted, keystores will not be generated.
+    ///
+    /// It is recommended to use other password sources,
+    /// as otherwise it will appear in your shell history.
+    #[arg(long, visible_alias = "keystore_password")]
+    pub keystore_password: Option<String>,
+
+    /// Path to a file containing the keystore password.
+    ///
+    /// Single trailing newline is stripped from file contents,
+    /// all other whitespace is considered a part of password.
+    #[arg(long, visible_alias = "password_file")]
+    pub password_file: Option<String>,
+
+    /// Number of open file descriptor to read the keystore password from.
+    /// Only supported on Unix platforms.
+    #[arg(long, visible_alias = "password_fd")]
+    pub password_fd: Option<u32>,
+
+    /// Name of environment variable containing the keystore password.
+    #[arg(long, visible_alias = "password_env")]
+    pub password_env: Option<String>,
+
+    /// Ask for the keystore password on the terminal, without echoing it.
+    #[arg(long, visible_alias = "password_prompt")]
+    pub password_prompt: bool,
+}
+
+impl KeystorePasswordOpts {
+    /// Read keystore password from the source set, if any.
+    pub fn read(&self) -> Result<Option<Vec<u8>>, DepositError> {
+        if let Some(password) = &self.keystore_password {
+            return Ok(Some(password.as_bytes().to_vec()));
+        }
+        let password = read_secret(
+            "keystore password",
+            self.password_file.as_deref().map(Path::new),
+            self.password_fd,
+            self.password_env.as_deref(),
+        )?;
+        let password = match password {
+            Some(password) => Some(password),
+            None if self.password_prompt => Some(prompt_secret("keystore password", true)?),
+            None => None,
+        };
+        // Move password bytes out without copying them
+        Ok(password.map(|mut p| std::mem::take(&mut *p).into_bytes()))
+    }
+}
+
+/// Options to supply BIP-39 passphrase, which is mixed with the mnemonic
+/// into the seed, so the same mnemonic with different passphrase
+/// derives completely different keys.
+///
+/// Passphrase is never accepted as plain argument value,
+/// so it does not appear in shell history or process list.
+#[derive(Args, Clone, Debug, Default)]
+#[group(id = "mnemonic_passphrase_source", multiple = false)]
+pub struct PassphraseOpts {
+    /// Path to a file containing optional BIP-39 passphrase for the mnemonic.
+    ///
+    /// Single trailing newline is stripped from file contents,
+    /// all other whitespace is considered a part of passphrase.
+    #[arg(long, visible_alias = "mnemonic_passphrase_file")]
+    pub mnemonic_passphrase_file: Option<String>,
+
+    /// Number of open file descriptor to read optional BIP-39 passphrase from.
+    /// Only supported on Unix platforms.
+    #[arg(long, visible_alias = "mnemonic_passphrase_fd")]
+    pub mnemonic_passphrase_fd: Option<u32>,
+
+    /// Name of environment variable containing optional BIP-39 passphrase.
+    #[arg(long, visible_alias = "mnemonic_passphrase_env")]
+    pub mnemonic_passphrase_env: Option<String>,
+
+    /// Ask for optional BIP-39 passphrase for the mnemonic
+    /// on the terminal, without echoing it.
+    #[arg(long, visible_alias = "mnemonic_passphrase_prompt")]
+    pub mnemonic_passphrase_prompt: bool,
+}
+
+impl PassphraseOpts {
+    /// Read passphrase from the source set, if any.
+    ///
+    /// If `confirm` is set, prompted passphrase has to be typed twice,
+    /// which is used for new mnemonics where typo would make keys unrecoverable.
+    pub fn read(&self, confirm: bool) -> Result<Zeroizing<Option<String>>, DepositError> {
+        let passphrase = read_secret(
+            "mnemonic passphrase",
+            self.mnemonic_passphrase_file.as_deref().map(Path::new),
+            self.mnemonic_passphrase_fd,
+            self.mnemonic_passphrase_env.as_deref(),
+        )?;
+        let passphrase = match passphrase {
+            Some(passphrase) => Some(passphrase),
+            None if self.mnemonic_passphrase_prompt => {
+                Some(prompt_secret("BIP-39 mnemonic passphrase", confirm)?)
+            }
+            None => None,
+        };
+        Ok(Zeroizing::new(
+            passphrase.map(|mut p| std::mem::take(&mut *p)),
+        ))
+    }
+}
+
+/// Read secret from file, file descriptor or environment variable,
+/// whichever is set.
+pub(crate) fn read_secret(
+    name: &str,
+    file: Option<&Path>,
+    fd: Option<u32>,
+    env: Option<&str>,
+) -> Result<Option<Zeroizing<String>>, DepositError> {
+    if let Some(path) = file {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            DepositError::FilesystemError(format!(
+                "Can not read {} file {}: {}",
+                name,
+                path.display(),
+                e
+            ))
+        })?;
+        return Ok(Some(Zeroizing::new(strip_trailing_newline(contents))));
+    }
+    if let Some(fd) = fd {
+        return Ok(Some(read_fd(name, fd)?));
+    }
+    if let Some(var) = env {
+        let value = std::env::var(var).map_err(|e| {
+            DepositError::InvalidInput(format!(
+                "Can not read {} from environment variable {}: {}",
+                name, var, e
+            ))
+        })?;
+        return Ok(Some(Zeroizing::new(value)));
+    }
+    Ok(None)
+}
+
+/// Read secret from open file descriptor.
+#[cfg(unix)]
+fn read_fd(name: &str, fd: u32) -> Result<Zeroizing<String>, DepositError> {
+    // Open file descriptors are exposed as files on Linux and macOS,
+    // which avoids taking ownership of raw descriptor.
+    let contents = std::fs::read_to_string(format!("/dev/fd/{}", fd)).map_err(|e| {
+        DepositError::FilesystemError(format!(
+            "Can not read {} from file descriptor {}: {}",
+            name, fd, e
+        ))
+    })?;
+    Ok(Zeroizing::new(strip_trailing_newline(contents)))
+}
+
+/// Read secret from open file descriptor.
+#[cfg(not(unix))]
+fn read_fd(name: &str, fd: u32) -> Result<Zeroizing<String>, DepositError> {
+    Err(DepositError::InvalidInput(format!(
+        "Can not read {} from file descriptor {}: \
+        reading file descriptors is only supported on Unix platforms",
+        name, fd
+    )))
+}
+
+fn prompt_secret(name: &str, confirm: bool) -> Result<Zeroizing<String>, DepositError> {
+    let read = |message: String| {
+        rpassword::prompt_password(message)
+            .map(Zeroizing::new)
+            .map_err(|e| DepositError::InvalidInput(format!("Can not read {}: {}", name, e)))
+    };
+    let secret = read(format!("Enter {}: ", name))?;
+    if confirm && secret != read(format!("Repeat {}: ", name))? {
+        return Err(DepositError::InvalidInput(format!(
+            "Entered {}s do not match",
+            name
+        )));
+    }
+    Ok(secret)
+}
+
+fn strip_trailing_newline(mut contents: String) -> String {
+    if contents.ends_with('\n') {
+        contents.pop();
+        if contents.ends_with('\r') {
+            contents.pop();
+        }
+    }
+    contents
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_secret, strip_trailing_newline, Zeroizing};
+
+    #[test]
+    fn it_strips_single_trailing_newline() {
+        assert_eq!("TREZOR", strip_trailing_newline("TREZOR\n".to_string()));
+        assert_eq!("TREZOR", strip_trailing_newline("TREZOR\r\n".to_string()));
+        assert_eq!("TREZOR\n", strip_trailing_newline("TREZOR\n\n".to_string()));
+        assert_eq!(" TREZOR ", strip_trailing_newline(" TREZOR ".to_string()));
+    }
+
+    #[test]
+    fn it_reads_secret_from_environment() {
+        std::env::set_var("ETH_STAKING_SMITH_TEST_SECRET", "testtest");
+        assert_eq!(
+            Some(Zeroizing::new("testtest".to_string())),
+            read_secret(
+                "keystore password",
+                None,
+                None,
+                Some("ETH_STAKING_SMITH_TEST_SECRET")
+            )
+            .unwrap()
+        );
+        assert!(read_secret(
+            "keystore password",
+            None,
+            None,
+            Some("ETH_STAKING_SMITH_TEST_SECRET_UNSET")
+        )
+        .is_err());
+        assert_eq!(
+            None,
+            read_secret("keystore password", None, None, None).unwrap()
+        );
+    }
+}
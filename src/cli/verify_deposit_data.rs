@@ -0,0 +1,73 @@
+use clap::{arg, Parser};
+
+use crate::chain_spec::{chain_spec_for_network, chain_spec_from_file};
+use crate::{DepositError, DepositExport};
+
+#[derive(Clone, Parser)]
+pub struct VerifyDepositDataSubcommandOpts {
+    /// Path to deposit data JSON file, produced by eth-staking-smith
+    /// or staking-deposit-cli, e.g. deposit_data-1720014619.json
+    #[arg(long, visible_alias = "deposit_data_file")]
+    pub deposit_data_file: String,
+
+    /// The name of Ethereum PoS chain the deposits are targeting.
+    #[arg(value_enum, long, required_unless_present = "testnet_config")]
+    pub chain: Option<crate::networks::SupportedNetworks>,
+
+    /// Path to a custom Eth PoS chain config
+    #[arg(long, visible_alias = "testnet_config")]
+    pub testnet_config: Option<String>,
+}
+
+impl VerifyDepositDataSubcommandOpts {
+    pub fn run(&self) -> Result<(), DepositError> {
+        let spec = if self.chain.is_some() && self.testnet_config.is_some() {
+            return Err(DepositError::InvalidInput(
+                "should only pass one of testnet_config or chain".to_string(),
+            ));
+        } else if let Some(testnet_config) = &self.testnet_config {
+            chain_spec_from_file(testnet_config.clone())?
+        } else {
+            chain_spec_for_network(self.chain.as_ref().unwrap())?
+        };
+
+        let deposit_data_json = std::fs::read_to_string(&self.deposit_data_file).map_err(|e| {
+            DepositError::FilesystemError(format!("Can not read deposit data file: {}", e))
+        })?;
+        let deposits: Vec<DepositExport> =
+            serde_json::from_str(&deposit_data_json).map_err(|e| {
+                DepositError::InvalidDepositData(format!("Can not parse deposit data file: {}", e))
+            })?;
+
+        let mut all_valid = true;
+        let report: Vec<serde_json::Value> = deposits
+            .iter()
+            .map(|deposit| match deposit.verify(&spec) {
+                Ok(()) => serde_json::json!({
+                    "pubkey": deposit.pubkey,
+                    "status": "ok",
+                }),
+                Err(e) => {
+                    all_valid = false;
+                    serde_json::json!({
+                        "pubkey": deposit.pubkey,
+                        "status": "invalid",
+                        "error": e.to_string(),
+                    })
+                }
+            })
+            .collect();
+
+        let report_json = serde_json::to_string_pretty(&report).map_err(|_| {
+            DepositError::SerializationError("could not serialize report".to_string())
+        })?;
+        println!("{}", report_json);
+
+        if !all_valid {
+            return Err(DepositError::InvalidDepositData(
+                "Some of the deposits are invalid".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
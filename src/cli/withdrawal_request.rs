@@ -0,0 +1,97 @@
+use clap::{arg, Parser};
+
+use crate::cli::request_fee::RequestFeeOpts;
+use crate::cli::secrets::{MnemonicOpts, PassphraseOpts};
+use crate::key_material::DerivationPaths;
+use crate::utils::parse_validator_pubkey;
+use crate::withdrawal_request::{self, WithdrawalRequest};
+use crate::DepositError;
+
+#[derive(Clone, Parser)]
+pub struct WithdrawalRequestSubcommandOpts {
+    #[command(flatten)]
+    pub mnemonic: MnemonicOpts,
+
+    #[command(flatten)]
+    pub passphrase: PassphraseOpts,
+
+    /// The index of the first validator's keys to generate withdrawal requests for,
+    /// when validator public keys are derived from the mnemonic.
+    #[arg(
+        long,
+        visible_alias = "validator_seed_index",
+        required_unless_present = "pubkey"
+    )]
+    pub validator_seed_index: Option<u32>,
+
+    /// The number of validators derived from the mnemonic
+    /// to generate withdrawal requests for, 1 by default.
+    #[arg(
+        long,
+        visible_alias = "num_validators",
+        requires = "validator_seed_index"
+    )]
+    pub num_validators: Option<u32>,
+
+    /// Validator public key in hex form, instead of deriving it from the mnemonic.
+    ///
+    /// May be repeated, or passed as comma separated list.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        conflicts_with_all = ["mnemonic_source", "mnemonic_passphrase_source", "validator_seed_index"]
+    )]
+    pub pubkey: Vec<String>,
+
+    #[command(flatten)]
+    pub derivation_paths: DerivationPaths,
+
+    /// Amount to withdraw from every validator in gwei.
+    ///
+    /// Full exit of validators is requested by default, with zero amount.
+    /// Partial withdrawals are only processed for validators
+    /// with compounding (0x02) withdrawal credentials.
+    #[arg(long, visible_alias = "amount_gwei", default_value_t = 0)]
+    pub amount_gwei: u64,
+
+    #[command(flatten)]
+    pub fee: RequestFeeOpts,
+}
+
+impl WithdrawalRequestSubcommandOpts {
+    pub fn run(&self) -> Result<(), DepositError> {
+        let withdrawal_requests = if let Some(validator_seed_index) = self.validator_seed_index {
+            withdrawal_request::withdrawal_requests_from_mnemonic(
+                self.mnemonic.read()?.as_bytes(),
+                self.passphrase.read(false)?.as_deref(),
+                validator_seed_index,
+                self.num_validators.unwrap_or(1),
+                self.amount_gwei,
+                Some(&self.derivation_paths),
+            )?
+        } else {
+            if self.derivation_paths.is_set() {
+                return Err(DepositError::InvalidInput(
+                    "Derivation paths can only be set for keys derived from mnemonic".to_string(),
+                ));
+            }
+            self.pubkey
+                .iter()
+                .map(|pubkey| {
+                    parse_validator_pubkey(pubkey)
+                        .map(|pubkey| WithdrawalRequest::new(pubkey, self.amount_gwei))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        let export = withdrawal_requests
+            .iter()
+            .map(|withdrawal_request| withdrawal_request.export(self.fee.fee_wei))
+            .collect::<Vec<_>>();
+        let export_json = serde_json::to_string_pretty(&export).map_err(|_| {
+            DepositError::SerializationError("could not serialize withdrawal requests".to_string())
+        })?;
+        println!("{}", export_json);
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use ssz::Encode;
+use types::PublicKeyBytes;
+
+use crate::transaction::TransactionTemplate;
+use crate::utils::{BLS_CREDS_REGEX, COMPOUNDING_CREDS_REGEX, EXECUTION_CREDS_REGEX};
+use crate::DepositError;
+
+/// Address of EIP-7251 consolidation request predeploy contract,
+/// which is the same on every network.
+pub const CONSOLIDATION_REQUEST_PREDEPLOY_ADDRESS: &str =
+    "0x0000BBdDc7CE488642fb579F8B00f3a590007251";
+
+/// EIP-7251 consolidation request, sent from execution layer by the owner
+/// of withdrawal address of both source and target validators,
+/// to move balance of source validator to target validator and exit the source.
+///
+/// Request with the same source and target validator switches it
+/// from execution (0x01) to compounding (0x02) withdrawal credentials.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsolidationRequest {
+    pub source_pubkey: PublicKeyBytes,
+    pub target_pubkey: PublicKeyBytes,
+}
+
+/// Consolidation request payload along with transaction to the predeploy contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsolidationRequestExport {
+    pub source_pubkey: String,
+    pub target_pubkey: String,
+    pub transaction: TransactionTemplate,
+}
+
+impl ConsolidationRequest {
+    pub fn new(source_pubkey: PublicKeyBytes, target_pubkey: PublicKeyBytes) -> Self {
+        Self {
+            source_pubkey,
+            target_pubkey,
+        }
+    }
+
+    /// Request switching validator to compounding (0x02) withdrawal credentials.
+    pub fn switch_to_compounding(pubkey: PublicKeyBytes) -> Self {
+        Self::new(pubkey, pubkey)
+    }
+
+    pub fn is_switch_to_compounding(&self) -> bool {
+        self.source_pubkey == self.target_pubkey
+    }
+
+    /// Calldata of the request, 48 bytes of source validator public key
+    /// followed by 48 bytes of target validator public key.
+    pub fn calldata(&self) -> Vec<u8> {
+        let mut calldata = self.source_pubkey.as_ssz_bytes();
+        calldata.extend_from_slice(&self.target_pubkey.as_ssz_bytes());
+        calldata
+    }
+
+    /// Transaction to the predeploy contract, sending the request fee.
+    pub fn transaction(&self, fee_wei: u128) -> TransactionTemplate {
+        TransactionTemplate::new(
+            CONSOLIDATION_REQUEST_PREDEPLOY_ADDRESS,
+            fee_wei,
+            &self.calldata(),
+        )
+    }
+
+    pub fn export(&self, fee_wei: u128) -> ConsolidationRequestExport {
+        ConsolidationRequestExport {
+            source_pubkey: format!("0x{}", hex::encode(self.source_pubkey.as_ssz_bytes())),
+            target_pubkey: format!("0x{}", hex::encode(self.target_pubkey.as_ssz_bytes())),
+            transaction: self.transaction(fee_wei),
+        }
+    }
+}
+
+/// Create requests consolidating source validators into target validator,
+/// given current withdrawal credentials of target validator.
+///
+/// Target validator with execution (0x01) credentials is switched to compounding (0x02)
+/// ones by the first request, so that it can receive consolidations, while
+/// BLS (0x00) credentials have to be changed to execution address first.
+pub fn consolidation_requests(
+    source_pubkeys: &[PublicKeyBytes],
+    target_pubkey: PublicKeyBytes,
+    target_withdrawal_credentials: &str,
+) -> Result<Vec<ConsolidationRequest>, DepositError> {
+    if source_pubkeys.is_empty() {
+        return Err(DepositError::InvalidInput(
+            "At least one source validator must be set".to_string(),
+        ));
+    }
+    for (idx, source_pubkey) in source_pubkeys.iter().enumerate() {
+        if *source_pubkey == target_pubkey {
+            return Err(DepositError::InvalidInput(
+                "Target validator can not be consolidated into itself".to_string(),
+            ));
+        }
+        if source_pubkeys[..idx].contains(source_pubkey) {
+            return Err(DepositError::InvalidInput(format!(
+                "Source validator 0x{} is set more than once",
+                hex::encode(source_pubkey.as_ssz_bytes())
+            )));
+        }
+    }
+
+    let mut requests = vec![];
+    if EXECUTION_CREDS_REGEX.is_match(target_withdrawal_credentials) {
+        requests.push(ConsolidationRequest::switch_to_compounding(target_pubkey));
+    } else if BLS_CREDS_REGEX.is_match(target_withdrawal_credentials) {
+        return Err(DepositError::InvalidWithdrawalCredentials(
+            "Target validator has BLS (0x00) withdrawal credentials, \
+            which have to be changed to execution address first"
+                .to_string(),
+        ));
+    } else if !COMPOUNDING_CREDS_REGEX.is_match(target_withdrawal_credentials) {
+        return Err(DepositError::InvalidWithdrawalCredentials(format!(
+            "Invalid withdrawal credentials of target validator: {target_withdrawal_credentials}"
+        )));
+    }
+    requests.extend(
+        source_pubkeys
+            .iter()
+            .map(|source_pubkey| ConsolidationRequest::new(*source_pubkey, target_pubkey)),
+    );
+    Ok(requests)
+}
+
+/// Check that source validator has execution (0x01) or compounding (0x02) withdrawal
+/// credentials with the same withdrawal address as target validator,
+/// otherwise consolidation request is ignored by the beacon chain.
+pub fn check_source_withdrawal_credentials(
+    source_pubkey: &PublicKeyBytes,
+    source_withdrawal_credentials: &str,
+    target_withdrawal_credentials: &str,
+) -> Result<(), DepositError> {
+    let is_execution = |credentials: &str| {
+        EXECUTION_CREDS_REGEX.is_match(credentials) || COMPOUNDING_CREDS_REGEX.is_match(credentials)
+    };
+    // Withdrawal address is the last 20 bytes of execution credentials
+    let address = |credentials: &str| credentials[26..].to_lowercase();
+    if !is_execution(source_withdrawal_credentials)
+        || !is_execution(target_withdrawal_credentials)
+        || address(source_withdrawal_credentials) != address(target_withdrawal_credentials)
+    {
+        return Err(DepositError::InvalidWithdrawalCredentials(format!(
+            "Source validator 0x{} must have execution withdrawal credentials \
+            with the same address as target validator",
+            hex::encode(source_pubkey.as_ssz_bytes())
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test;
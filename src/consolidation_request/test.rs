@@ -0,0 +1,108 @@
+use pretty_assertions::assert_eq;
+use types::PublicKeyBytes;
+
+use super::{
+    check_source_withdrawal_credentials, consolidation_requests, ConsolidationRequest,
+    CONSOLIDATION_REQUEST_PREDEPLOY_ADDRESS,
+};
+use crate::DepositError;
+
+const PHRASE: &str = "entire habit bottom mention spoil clown finger wheat motion fox axis mechanic country make garment bar blind stadium sugar water scissors canyon often ketchup";
+
+const EXECUTION_CREDENTIALS: &str =
+    "0x0100000000000000000000000000000000000000000000000000000000000001";
+const COMPOUNDING_CREDENTIALS: &str =
+    "0x0200000000000000000000000000000000000000000000000000000000000001";
+
+fn validator_pubkeys() -> Vec<PublicKeyBytes> {
+    crate::seed_index::validator_pubkeys(PHRASE.as_bytes(), None, 0, 3, None).unwrap()
+}
+
+#[test]
+fn it_generates_consolidation_requests_into_compounding_target() {
+    let pubkeys = validator_pubkeys();
+    let requests =
+        consolidation_requests(&pubkeys[1..], pubkeys[0], COMPOUNDING_CREDENTIALS).unwrap();
+    assert_eq!(
+        vec![
+            ConsolidationRequest::new(pubkeys[1], pubkeys[0]),
+            ConsolidationRequest::new(pubkeys[2], pubkeys[0]),
+        ],
+        requests
+    );
+
+    let export = requests[0].export(1);
+    assert_eq!(
+        CONSOLIDATION_REQUEST_PREDEPLOY_ADDRESS,
+        export.transaction.to
+    );
+    assert_eq!("0x1", export.transaction.value);
+    assert_eq!(
+        "0x974ec5bce4653f1f440ad07c5b363ad3b1616520e9680ff837f9ff7a8c10e3cc67dd49aa5089f714ed45d7ad56bc758a8666389c3fe6ff0bca9adba81504f380b9e2c719419760d561836472fafe295cb50696524e19cba084e1d788d66c80d6",
+        export.transaction.data
+    );
+}
+
+#[test]
+fn it_switches_execution_target_to_compounding() {
+    let pubkeys = validator_pubkeys();
+    let requests =
+        consolidation_requests(&pubkeys[1..2], pubkeys[0], EXECUTION_CREDENTIALS).unwrap();
+    assert_eq!(2, requests.len());
+    assert!(requests[0].is_switch_to_compounding());
+    assert_eq!(pubkeys[0], requests[0].source_pubkey);
+    assert_eq!(
+        ConsolidationRequest::new(pubkeys[1], pubkeys[0]),
+        requests[1]
+    );
+}
+
+#[test]
+fn it_rejects_invalid_consolidation_requests() {
+    let pubkeys = validator_pubkeys();
+    let bls_credentials = format!("0x00{}", "11".repeat(31));
+
+    assert!(matches!(
+        consolidation_requests(&pubkeys[1..], pubkeys[0], &bls_credentials),
+        Err(DepositError::InvalidWithdrawalCredentials(_))
+    ));
+    assert!(matches!(
+        consolidation_requests(&pubkeys, pubkeys[0], COMPOUNDING_CREDENTIALS),
+        Err(DepositError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        consolidation_requests(
+            &[pubkeys[1], pubkeys[1]],
+            pubkeys[0],
+            COMPOUNDING_CREDENTIALS
+        ),
+        Err(DepositError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        consolidation_requests(&[], pubkeys[0], COMPOUNDING_CREDENTIALS),
+        Err(DepositError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn it_checks_source_withdrawal_address() {
+    let pubkeys = validator_pubkeys();
+    assert!(check_source_withdrawal_credentials(
+        &pubkeys[1],
+        EXECUTION_CREDENTIALS,
+        COMPOUNDING_CREDENTIALS
+    )
+    .is_ok());
+    assert!(check_source_withdrawal_credentials(
+        &pubkeys[1],
+        "0x0100000000000000000000000000000000000000000000000000000000000002",
+        COMPOUNDING_CREDENTIALS
+    )
+    .is_err());
+    assert!(check_source_withdrawal_credentials(
+        &pubkeys[1],
+        &format!("0x00{}", "00".repeat(30) + "01"),
+        COMPOUNDING_CREDENTIALS
+    )
+    .is_err());
+}
@@ -2,11 +2,13 @@ use eth2_keystore::keypair_from_secret;
 use types::{ChainSpec, DepositData, Hash256, Signature};
 
 use crate::{
-    chain_spec::{chain_spec_for_network, chain_spec_from_file},
+    chain_spec::{chain_spec_for_network, chain_spec_from_file, max_effective_balance_electra},
     key_material::VotingKeyMaterial,
     networks::SupportedNetworks,
 };
 
+/// Error returned by the library, every variant carries
+/// a human readable description of what went wrong.
 #[derive(Debug, Eq, PartialEq)]
 pub enum DepositError {
     InvalidWithdrawalCredentials(String),
@@ -15,6 +17,118 @@ pub enum DepositError {
     InvalidNetworkName(String),
     NoCustomConfig(String),
     SerializationError(String),
+    FilesystemError(String),
+    InvalidDepositData(String),
+    InvalidMnemonic(String),
+    InvalidPassword(String),
+    InvalidPrivateKey(String),
+    InvalidExecutionAddress(String),
+    InvalidGenesisValidatorsRoot(String),
+    InvalidSignature(String),
+    KeyDerivationError(String),
+    InvalidInput(String),
+    BeaconNodeError(String),
+}
+
+impl DepositError {
+    /// Process exit code for the error, when it is returned from CLI command.
+    ///
+    /// 1 is used for invalid input, 3 for filesystem and serialization
+    /// errors and 4 for errors communicating with beacon node.
+    /// Exit code 2 is used by argument parser for invalid usage.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            DepositError::SerializationError(_) | DepositError::FilesystemError(_) => 3,
+            DepositError::BeaconNodeError(_) => 4,
+            _ => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for DepositError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            DepositError::InvalidWithdrawalCredentials(message)
+            | DepositError::InvalidDepositAmount(message)
+            | DepositError::InvalidKeystore(message)
+            | DepositError::InvalidNetworkName(message)
+            | DepositError::NoCustomConfig(message)
+            | DepositError::SerializationError(message)
+            | DepositError::FilesystemError(message)
+            | DepositError::InvalidDepositData(message)
+            | DepositError::InvalidMnemonic(message)
+            | DepositError::InvalidPassword(message)
+            | DepositError::InvalidPrivateKey(message)
+            | DepositError::InvalidExecutionAddress(message)
+            | DepositError::InvalidGenesisValidatorsRoot(message)
+            | DepositError::InvalidSignature(message)
+            | DepositError::KeyDerivationError(message)
+            | DepositError::InvalidInput(message)
+            | DepositError::BeaconNodeError(message) => message,
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for DepositError {}
+
+impl From<crate::beacon_node::BeaconNodeError> for DepositError {
+    fn from(value: crate::beacon_node::BeaconNodeError) -> Self {
+        DepositError::BeaconNodeError(format!("Beacon node request failed: {:?}", value))
+    }
+}
+
+/// Withdrawal credentials prefix of compounding validator, introduced with EIP-7251.
+pub const COMPOUNDING_WITHDRAWAL_PREFIX_BYTE: u8 = 0x02;
+
+/// Check that deposit amount is within the bounds allowed by the network,
+/// which is at least `min_deposit_amount` of the chain spec and at most
+/// `max_effective_balance` of the chain spec, or the maximum effective balance
+/// of compounding validator for compounding (0x02) withdrawal credentials.
+///
+/// Smaller amounts are valid for top-up deposits of already active validators.
+pub(crate) fn validate_deposit_amount(
+    deposit_amount_gwei: u64,
+    withdrawal_credentials: &[u8],
+    spec: &ChainSpec,
+) -> Result<(), DepositError> {
+    if deposit_amount_gwei < spec.min_deposit_amount {
+        return Err(DepositError::InvalidDepositAmount(format!(
+            "Invalid amount of deposit data, should be at least {} gwei",
+            spec.min_deposit_amount
+        )));
+    }
+    if withdrawal_credentials.first() == Some(&COMPOUNDING_WITHDRAWAL_PREFIX_BYTE) {
+        let max_effective_balance = max_effective_balance_electra(spec);
+        if deposit_amount_gwei > max_effective_balance {
+            return Err(DepositError::InvalidDepositAmount(format!(
+                "Invalid amount of deposit data, should be at most {} gwei",
+                max_effective_balance
+            )));
+        }
+    } else if deposit_amount_gwei > spec.max_effective_balance {
+        return Err(DepositError::InvalidDepositAmount(format!(
+            "Invalid amount of deposit data, should be at most {} gwei \
+            unless withdrawal credentials are compounding (0x02)",
+            spec.max_effective_balance
+        )));
+    }
+    Ok(())
+}
+
+/// Chain spec of well known network, or of custom testnet config
+/// if no network is set.
+pub(crate) fn deposit_chain_spec(
+    network: Option<SupportedNetworks>,
+    chain_spec_file: Option<String>,
+) -> Result<ChainSpec, DepositError> {
+    match (network, chain_spec_file) {
+        (Some(chain), _) => chain_spec_for_network(&chain),
+        (None, Some(chain_spec_file)) => chain_spec_from_file(chain_spec_file),
+        (None, None) => Err(DepositError::NoCustomConfig(
+            "Either chain or custom testnet config must be set".to_string(),
+        )),
+    }
 }
 
 /// Given the network specification, validator keystore
@@ -36,17 +150,9 @@ pub(crate) fn keystore_to_deposit(
         ));
     };
 
-    // For simplicity, support only 32Eth deposits
-    if deposit_amount_gwei != 32_000_000_000 {
-        return Err(DepositError::InvalidDepositAmount(
-            "Invalid amount of deposit data, should be 32Eth".to_string(),
-        ));
-    };
+    let spec = deposit_chain_spec(network, chain_spec_file)?;
 
-    let spec = match network {
-        Some(chain) => chain_spec_for_network(&chain)?,
-        None => chain_spec_from_file(chain_spec_file.unwrap())?,
-    };
+    validate_deposit_amount(deposit_amount_gwei, withdrawal_credentials, &spec)?;
 
     let credentials_hash = Hash256::from_slice(withdrawal_credentials);
 
@@ -78,7 +184,7 @@ mod test {
     use pretty_assertions::assert_eq;
     use types::PublicKey;
 
-    use super::keystore_to_deposit;
+    use super::{keystore_to_deposit, DepositError};
     use crate::{key_material::VotingKeyMaterial, utils::get_withdrawal_credentials};
     use std::{path::PathBuf, str::FromStr};
     use test_log::test;
@@ -89,6 +195,8 @@ mod test {
         "0100000000000000000000000000000000000000000000000000000000000001".as_bytes();
     const WITHDRAWAL_CREDENTIALS_ETH2: &[u8] =
         "00e078f11bc1454244bdf9f63a3b997815f081dd6630204186d4c9627a2942f7".as_bytes();
+    const WITHDRAWAL_CREDENTIALS_COMPOUNDING: &[u8] =
+        "0200000000000000000000000000000000000000000000000000000000000001".as_bytes();
 
     #[test]
     fn test_deposit_mainnet_eth1_withdrawal() {
@@ -408,4 +516,52 @@ mod test {
             deposit_data.signature.to_string().as_str().strip_prefix("0x").unwrap()
         );
     }
+
+    #[test]
+    fn test_deposit_amount_bounds() {
+        let keystore = Keystore::from_json_str(KEYSTORE).unwrap();
+        let keypair = keystore.decrypt_keypair(PASSWORD).unwrap();
+        let key_material = VotingKeyMaterial {
+            keystore: Some(keystore.clone()),
+            keypair: keypair.clone(),
+            voting_secret: PlainText::from(keypair.sk.serialize().as_bytes().to_vec()),
+            withdrawal_keypair: None,
+        };
+        let deposit = |withdrawal_creds: &[u8], amount: u64| {
+            keystore_to_deposit(
+                &key_material,
+                hex::decode(withdrawal_creds).unwrap().as_slice(),
+                amount,
+                Some(crate::networks::SupportedNetworks::Mainnet),
+                None,
+            )
+        };
+
+        // 1 ETH top-up is the smallest allowed deposit
+        let (deposit_data, _) = deposit(WITHDRAWAL_CREDENTIALS_ETH1, 1_000_000_000).unwrap();
+        assert_eq!(1_000_000_000, deposit_data.amount);
+        let (deposit_data, _) = deposit(WITHDRAWAL_CREDENTIALS_ETH1, 32_000_000_000).unwrap();
+        assert_eq!(32_000_000_000, deposit_data.amount);
+        let (deposit_data, _) =
+            deposit(WITHDRAWAL_CREDENTIALS_COMPOUNDING, 2_048_000_000_000).unwrap();
+        assert_eq!(2_048_000_000_000, deposit_data.amount);
+
+        assert!(matches!(
+            deposit(WITHDRAWAL_CREDENTIALS_ETH1, 999_999_999),
+            Err(DepositError::InvalidDepositAmount(_))
+        ));
+        // Only compounding validators can have more than 32 ETH effective balance
+        assert!(matches!(
+            deposit(WITHDRAWAL_CREDENTIALS_ETH1, 33_000_000_000),
+            Err(DepositError::InvalidDepositAmount(_))
+        ));
+        assert!(matches!(
+            deposit(WITHDRAWAL_CREDENTIALS_ETH2, 2_048_000_000_000),
+            Err(DepositError::InvalidDepositAmount(_))
+        ));
+        assert!(matches!(
+            deposit(WITHDRAWAL_CREDENTIALS_COMPOUNDING, 2_048_000_000_001),
+            Err(DepositError::InvalidDepositAmount(_))
+        ));
+    }
 }
@@ -0,0 +1,426 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+use types::ChainSpec;
+
+use crate::chain_spec::is_gnosis_preset;
+use crate::transaction::TransactionTemplate;
+use crate::{DepositError, DepositExport};
+
+/// Selector of `deposit(bytes,bytes,bytes,bytes32)` function of the deposit contract.
+pub const DEPOSIT_FUNCTION_SELECTOR: [u8; 4] = [0x22, 0x89, 0x51, 0x18];
+
+/// Wei per gwei, deposit amounts are denominated in gwei
+/// while transaction value is in wei.
+pub(crate) const WEI_PER_GWEI: u128 = 1_000_000_000;
+
+/// Deposit data entry along with transaction to the deposit contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepositTransactionExport {
+    pub pubkey: String,
+    pub amount: u64,
+    pub deposit_data_root: String,
+    pub chain_id: u64,
+    pub transaction: TransactionTemplate,
+}
+
+/// ABI-encoded calldata of deposit contract call for the deposit data entry.
+///
+/// Deposit data is expected to be verified beforehand,
+/// otherwise deposit could be rejected or the funds lost.
+pub fn deposit_calldata(deposit: &DepositExport) -> Result<Vec<u8>, DepositError> {
+    let pubkey = decode_hex_field("pubkey", &deposit.pubkey)?;
+    let withdrawal_credentials =
+        decode_hex_field("withdrawal_credentials", &deposit.withdrawal_credentials)?;
+    let signature = decode_hex_field("signature", &deposit.signature)?;
+    let deposit_data_root = decode_hex_field("deposit_data_root", &deposit.deposit_data_root)?;
+
+    let mut calldata = DEPOSIT_FUNCTION_SELECTOR.to_vec();
+    // Head of three dynamic arguments and static deposit data root
+    let pubkey_offset = 4 * 32;
+    let withdrawal_credentials_offset = pubkey_offset + abi_bytes_len(&pubkey);
+    let signature_offset = withdrawal_credentials_offset + abi_bytes_len(&withdrawal_credentials);
+    calldata.extend(abi_uint(pubkey_offset as u128));
+    calldata.extend(abi_uint(withdrawal_credentials_offset as u128));
+    calldata.extend(abi_uint(signature_offset as u128));
+    calldata.extend(deposit_data_root);
+    calldata.extend(abi_bytes(&pubkey));
+    calldata.extend(abi_bytes(&withdrawal_credentials));
+    calldata.extend(abi_bytes(&signature));
+    Ok(calldata)
+}
+
+/// Verify deposit data entries against the chain, and create transactions
+/// to the deposit contract of the chain, sending deposit amount along.
+///
+/// No transactions are created when any of the entries is invalid.
+pub fn deposit_transactions(
+    deposits: &[DepositExport],
+    spec: &ChainSpec,
+) -> Result<Vec<DepositTransactionExport>, DepositError> {
+    check_native_deposits(spec)?;
+    verify_deposits(deposits, spec)?;
+
+    let deposit_contract_address = deposit_contract_address(spec);
+    deposits
+        .iter()
+        .map(|deposit| {
+            Ok(DepositTransactionExport {
+                pubkey: deposit.pubkey.clone(),
+                amount: deposit.amount,
+                deposit_data_root: deposit.deposit_data_root.clone(),
+                chain_id: spec.deposit_chain_id,
+                transaction: TransactionTemplate::new(
+                    &deposit_contract_address,
+                    deposit.amount as u128 * WEI_PER_GWEI,
+                    &deposit_calldata(deposit)?,
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Argument of batch deposit contract function, packing the field
+/// of every deposit data entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchDepositParam {
+    /// `bytes pubkeys`, concatenated public keys
+    Pubkeys,
+    /// `bytes withdrawal_credentials`, concatenated withdrawal credentials
+    WithdrawalCredentials,
+    /// `bytes32 withdrawal_credentials`, withdrawal credentials shared by all entries
+    SharedWithdrawalCredentials,
+    /// `bytes signatures`, concatenated signatures
+    Signatures,
+    /// `bytes32[] deposit_data_roots`, deposit data root of every entry
+    DepositDataRoots,
+}
+
+impl BatchDepositParam {
+    fn abi_type(&self) -> &'static str {
+        match self {
+            Self::Pubkeys | Self::WithdrawalCredentials | Self::Signatures => "bytes",
+            Self::SharedWithdrawalCredentials => "bytes32",
+            Self::DepositDataRoots => "bytes32[]",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Pubkeys => "pubkeys",
+            Self::WithdrawalCredentials | Self::SharedWithdrawalCredentials => {
+                "withdrawal_credentials"
+            }
+            Self::Signatures => "signatures",
+            Self::DepositDataRoots => "deposit_data_roots",
+        }
+    }
+}
+
+/// Shape of batch deposit contract function, parsed from its signature
+/// with named parameters, e.g. the default
+/// `batchDeposit(bytes pubkeys,bytes withdrawal_credentials,bytes signatures,bytes32[] deposit_data_roots)`.
+///
+/// Parameter names tell which field of deposit data entries is passed,
+/// and the order of parameters follows the signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchDepositAbi {
+    pub function_name: String,
+    pub params: Vec<BatchDepositParam>,
+}
+
+impl Default for BatchDepositAbi {
+    fn default() -> Self {
+        Self {
+            function_name: "batchDeposit".to_string(),
+            params: vec![
+                BatchDepositParam::Pubkeys,
+                BatchDepositParam::WithdrawalCredentials,
+                BatchDepositParam::Signatures,
+                BatchDepositParam::DepositDataRoots,
+            ],
+        }
+    }
+}
+
+impl BatchDepositAbi {
+    /// Canonical function signature, without parameter names.
+    pub fn signature(&self) -> String {
+        let types = self
+            .params
+            .iter()
+            .map(BatchDepositParam::abi_type)
+            .collect::<Vec<_>>();
+        format!("{}({})", self.function_name, types.join(","))
+    }
+
+    /// First 4 bytes of keccak256 hash of canonical function signature.
+    pub fn selector(&self) -> [u8; 4] {
+        let mut hash = [0u8; 32];
+        let mut keccak = Keccak::v256();
+        keccak.update(self.signature().as_bytes());
+        keccak.finalize(&mut hash);
+        [hash[0], hash[1], hash[2], hash[3]]
+    }
+
+    /// ABI-encoded calldata of batch deposit contract call for deposit data entries.
+    ///
+    /// Deposit data is expected to be verified beforehand,
+    /// otherwise deposits could be rejected or the funds lost.
+    pub fn calldata(&self, deposits: &[DepositExport]) -> Result<Vec<u8>, DepositError> {
+        let mut head = vec![];
+        let mut tail = vec![];
+        let head_len = self.params.len() * 32;
+        for param in self.params.iter() {
+            match param {
+                BatchDepositParam::Pubkeys
+                | BatchDepositParam::WithdrawalCredentials
+                | BatchDepositParam::Signatures => {
+                    let mut value = vec![];
+                    for deposit in deposits {
+                        let field = match param {
+                            BatchDepositParam::Pubkeys => &deposit.pubkey,
+                            BatchDepositParam::WithdrawalCredentials => {
+                                &deposit.withdrawal_credentials
+                            }
+                            _ => &deposit.signature,
+                        };
+                        value.extend(decode_hex_field(param.name(), field)?);
+                    }
+                    head.extend(abi_uint((head_len + tail.len()) as u128));
+                    tail.extend(abi_bytes(&value));
+                }
+                BatchDepositParam::SharedWithdrawalCredentials => {
+                    let withdrawal_credentials = shared_withdrawal_credentials(deposits)?;
+                    head.extend(decode_hex_field(param.name(), withdrawal_credentials)?);
+                }
+                BatchDepositParam::DepositDataRoots => {
+                    head.extend(abi_uint((head_len + tail.len()) as u128));
+                    tail.extend(abi_uint(deposits.len() as u128));
+                    for deposit in deposits {
+                        tail.extend(decode_hex_field(param.name(), &deposit.deposit_data_root)?);
+                    }
+                }
+            }
+        }
+
+        let mut calldata = self.selector().to_vec();
+        calldata.extend(head);
+        calldata.extend(tail);
+        Ok(calldata)
+    }
+}
+
+impl fmt::Display for BatchDepositAbi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let params = self
+            .params
+            .iter()
+            .map(|param| format!("{} {}", param.abi_type(), param.name()))
+            .collect::<Vec<_>>();
+        write!(f, "{}({})", self.function_name, params.join(","))
+    }
+}
+
+impl FromStr for BatchDepositAbi {
+    type Err = DepositError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| {
+            DepositError::InvalidInput(format!(
+                "Invalid batch deposit function signature {s}: {reason}"
+            ))
+        };
+
+        let (function_name, params) = s
+            .trim()
+            .strip_suffix(')')
+            .and_then(|s| s.split_once('('))
+            .ok_or_else(|| invalid("expected name(type name,...)"))?;
+        if function_name.is_empty()
+            || !function_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(invalid("invalid function name"));
+        }
+
+        let mut parsed: Vec<BatchDepositParam> = vec![];
+        for param in params.split(',') {
+            let param = match param.split_whitespace().collect::<Vec<_>>().as_slice() {
+                ["bytes", "pubkeys"] => BatchDepositParam::Pubkeys,
+                ["bytes", "withdrawal_credentials"] => BatchDepositParam::WithdrawalCredentials,
+                ["bytes32", "withdrawal_credentials"] => {
+                    BatchDepositParam::SharedWithdrawalCredentials
+                }
+                ["bytes", "signatures"] => BatchDepositParam::Signatures,
+                ["bytes32[]", "deposit_data_roots"] => BatchDepositParam::DepositDataRoots,
+                _ => {
+                    return Err(invalid(&format!(
+                        "unsupported parameter '{}', expected one of bytes pubkeys, \
+                        bytes withdrawal_credentials, bytes32 withdrawal_credentials, \
+                        bytes signatures or bytes32[] deposit_data_roots",
+                        param.trim()
+                    )))
+                }
+            };
+            if parsed.iter().any(|p| p.name() == param.name()) {
+                return Err(invalid(&format!("duplicate parameter {}", param.name())));
+            }
+            parsed.push(param);
+        }
+        for required in ["pubkeys", "withdrawal_credentials", "signatures"] {
+            if !parsed.iter().any(|p| p.name() == required) {
+                return Err(invalid(&format!("missing parameter {required}")));
+            }
+        }
+
+        Ok(Self {
+            function_name: function_name.to_string(),
+            params: parsed,
+        })
+    }
+}
+
+/// Deposit data entries packed into single transaction to batch deposit contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchDepositTransactionExport {
+    pub pubkeys: Vec<String>,
+    /// Total amount of all deposits in gwei
+    pub amount: u64,
+    pub function_signature: String,
+    pub chain_id: u64,
+    pub transaction: TransactionTemplate,
+}
+
+/// Verify deposit data entries against the chain, and pack them into
+/// transaction to batch deposit contract, sending total deposit amount along.
+///
+/// No transaction is created when any of the entries is invalid.
+pub fn batch_deposit_transaction(
+    deposits: &[DepositExport],
+    spec: &ChainSpec,
+    batch_contract_address: &str,
+    abi: &BatchDepositAbi,
+) -> Result<BatchDepositTransactionExport, DepositError> {
+    if !crate::utils::EXECUTION_ADDR_REGEX.is_match(batch_contract_address) {
+        return Err(DepositError::InvalidExecutionAddress(format!(
+            "Invalid batch deposit contract address {batch_contract_address}"
+        )));
+    }
+    if deposits.is_empty() {
+        return Err(DepositError::InvalidDepositData(
+            "No deposits to batch".to_string(),
+        ));
+    }
+    check_native_deposits(spec)?;
+    verify_deposits(deposits, spec)?;
+    for (idx, deposit) in deposits.iter().enumerate() {
+        if deposits[..idx].iter().any(|d| d.pubkey == deposit.pubkey) {
+            return Err(DepositError::InvalidDepositData(format!(
+                "Validator {} is deposited more than once in the batch",
+                deposit.pubkey
+            )));
+        }
+    }
+
+    let amount = deposits.iter().map(|deposit| deposit.amount).sum::<u64>();
+    Ok(BatchDepositTransactionExport {
+        pubkeys: deposits
+            .iter()
+            .map(|deposit| deposit.pubkey.clone())
+            .collect(),
+        amount,
+        function_signature: abi.signature(),
+        chain_id: spec.deposit_chain_id,
+        transaction: TransactionTemplate::new(
+            &batch_contract_address.to_lowercase(),
+            amount as u128 * WEI_PER_GWEI,
+            &abi.calldata(deposits)?,
+        ),
+    })
+}
+
+fn shared_withdrawal_credentials(deposits: &[DepositExport]) -> Result<&str, DepositError> {
+    let withdrawal_credentials = deposits
+        .first()
+        .map(|deposit| deposit.withdrawal_credentials.as_str())
+        .unwrap_or_default();
+    if deposits
+        .iter()
+        .any(|deposit| deposit.withdrawal_credentials != withdrawal_credentials)
+    {
+        return Err(DepositError::InvalidDepositData(
+            "Batch deposit contract takes single withdrawal credentials, \
+            but deposits have different ones"
+                .to_string(),
+        ));
+    }
+    Ok(withdrawal_credentials)
+}
+
+/// Deposit transactions carry ETH value, so chains on gnosis preset,
+/// where deposits are made in GNO token, are rejected whatever config name they use.
+pub(crate) fn check_native_deposits(spec: &ChainSpec) -> Result<(), DepositError> {
+    if is_gnosis_preset(spec) {
+        return Err(DepositError::InvalidNetworkName(format!(
+            "Deposit transactions are not supported for {}, where deposits are made in GNO token",
+            spec.config_name.as_deref().unwrap_or("gnosis preset chain")
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn verify_deposits(
+    deposits: &[DepositExport],
+    spec: &ChainSpec,
+) -> Result<(), DepositError> {
+    for deposit in deposits {
+        deposit.verify(spec).map_err(|e| {
+            DepositError::InvalidDepositData(format!(
+                "Deposit of validator {} is invalid: {}",
+                deposit.pubkey, e
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+pub(crate) fn deposit_contract_address(spec: &ChainSpec) -> String {
+    format!(
+        "0x{}",
+        hex::encode(spec.deposit_contract_address.as_bytes())
+    )
+}
+
+pub(crate) fn decode_hex_field(name: &str, value: &str) -> Result<Vec<u8>, DepositError> {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value)).map_err(|e| {
+        DepositError::InvalidDepositData(format!("Can not decode {name} {value}: {e}"))
+    })
+}
+
+/// ABI encoding of unsigned integer as 32 bytes big-endian word.
+pub(crate) fn abi_uint(value: u128) -> Vec<u8> {
+    let mut word = vec![0u8; 16];
+    word.extend_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// ABI encoding of dynamic `bytes` value, its length followed
+/// by the bytes right-padded to 32 bytes words.
+pub(crate) fn abi_bytes(value: &[u8]) -> Vec<u8> {
+    let mut encoded = abi_uint(value.len() as u128);
+    encoded.extend_from_slice(value);
+    encoded.resize(abi_bytes_len(value), 0);
+    encoded
+}
+
+/// Length of ABI-encoded dynamic `bytes` value.
+pub(crate) fn abi_bytes_len(value: &[u8]) -> usize {
+    32 + value.len().div_ceil(32) * 32
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,253 @@
+use pretty_assertions::assert_eq;
+
+use super::{
+    batch_deposit_transaction, deposit_calldata, deposit_transactions, BatchDepositAbi,
+    BatchDepositParam, DEPOSIT_FUNCTION_SELECTOR,
+};
+use crate::chain_spec::chain_spec_for_network;
+use crate::networks::SupportedNetworks;
+use crate::{DepositError, DepositExport};
+
+const BATCH_CONTRACT_ADDRESS: &str = "0x0102030405060708090A0B0C0D0E0F1011121314";
+
+fn goerli_deposits() -> Vec<DepositExport> {
+    // generated with staking-deposit-cli for goerli
+    serde_json::from_str(include_str!(
+        "../../tests/e2e/expected_testdata/multiple_validators/deposit_data-1670231001.json"
+    ))
+    .unwrap()
+}
+
+#[test]
+fn it_encodes_deposit_calldata() {
+    let deposit = &goerli_deposits()[0];
+    let calldata = deposit_calldata(deposit).unwrap();
+
+    // selector, 4 head words, pubkey (2 words + length),
+    // withdrawal credentials (1 word + length) and signature (3 words + length)
+    assert_eq!(4 + 32 * 4 + 32 * 3 + 32 * 2 + 32 * 4, calldata.len());
+    assert_eq!(DEPOSIT_FUNCTION_SELECTOR.as_slice(), &calldata[..4]);
+
+    let word = |i: usize| hex::encode(&calldata[4 + i * 32..4 + (i + 1) * 32]);
+    assert_eq!(format!("{:064x}", 0x80), word(0));
+    assert_eq!(format!("{:064x}", 0xe0), word(1));
+    assert_eq!(format!("{:064x}", 0x120), word(2));
+    assert_eq!(deposit.deposit_data_root, word(3));
+    assert_eq!(format!("{:064x}", 48), word(4));
+    assert_eq!(
+        format!("{}{}", deposit.pubkey, "0".repeat(32)),
+        format!("{}{}", word(5), word(6))
+    );
+    assert_eq!(format!("{:064x}", 32), word(7));
+    assert_eq!(deposit.withdrawal_credentials, word(8));
+    assert_eq!(format!("{:064x}", 96), word(9));
+    assert_eq!(
+        deposit.signature,
+        format!("{}{}{}", word(10), word(11), word(12))
+    );
+}
+
+#[test]
+fn it_generates_deposit_transactions() {
+    let deposits = goerli_deposits();
+    let spec = chain_spec_for_network(&SupportedNetworks::Goerli).unwrap();
+    let transactions = deposit_transactions(&deposits, &spec).unwrap();
+
+    assert_eq!(3, transactions.len());
+    for (deposit, export) in deposits.iter().zip(transactions.iter()) {
+        assert_eq!(deposit.pubkey, export.pubkey);
+        assert_eq!(32_000_000_000, export.amount);
+        assert_eq!(deposit.deposit_data_root, export.deposit_data_root);
+        assert_eq!(5, export.chain_id);
+        assert_eq!(
+            "0xff50ed3d0ec03ac01d4c79aad74928bff48a7b2b",
+            export.transaction.to
+        );
+        // 32 ether
+        assert_eq!("0x1bc16d674ec800000", export.transaction.value);
+        assert_eq!(
+            format!("0x{}", hex::encode(deposit_calldata(deposit).unwrap())),
+            export.transaction.data
+        );
+    }
+}
+
+#[test]
+fn it_rejects_deposits_for_another_chain() {
+    let deposits = goerli_deposits();
+    let spec = chain_spec_for_network(&SupportedNetworks::Mainnet).unwrap();
+
+    let err = deposit_transactions(&deposits, &spec).unwrap_err();
+    assert!(matches!(err, DepositError::InvalidDepositData(_)));
+    assert!(err.to_string().contains(&deposits[0].pubkey));
+}
+
+#[test]
+fn it_rejects_token_deposit_networks() {
+    let spec = chain_spec_for_network(&SupportedNetworks::Gnosis).unwrap();
+
+    let err = deposit_transactions(&goerli_deposits(), &spec).unwrap_err();
+    assert!(matches!(err, DepositError::InvalidNetworkName(_)));
+}
+
+#[test]
+fn it_rejects_token_deposit_networks_by_preset() {
+    let mut spec = chain_spec_for_network(&SupportedNetworks::Gnosis).unwrap();
+    spec.config_name = Some("private-gnosis".to_string());
+
+    let err = deposit_transactions(&goerli_deposits(), &spec).unwrap_err();
+    assert!(matches!(err, DepositError::InvalidNetworkName(_)));
+    assert!(err.to_string().contains("private-gnosis"));
+}
+
+#[test]
+fn it_parses_batch_deposit_abi() {
+    let abi = BatchDepositAbi::default();
+    assert_eq!("batchDeposit(bytes,bytes,bytes,bytes32[])", abi.signature());
+    assert_eq!([0xc8, 0x26, 0x55, 0xb7], abi.selector());
+    assert_eq!(abi, abi.to_string().parse::<BatchDepositAbi>().unwrap());
+
+    let abi: BatchDepositAbi =
+        "deposit(bytes pubkeys, bytes32 withdrawal_credentials, bytes signatures, bytes32[] deposit_data_roots)"
+            .parse()
+            .unwrap();
+    assert_eq!("deposit(bytes,bytes32,bytes,bytes32[])", abi.signature());
+    assert_eq!([0xc9, 0xf1, 0x93, 0xf9], abi.selector());
+    assert_eq!(
+        vec![
+            BatchDepositParam::Pubkeys,
+            BatchDepositParam::SharedWithdrawalCredentials,
+            BatchDepositParam::Signatures,
+            BatchDepositParam::DepositDataRoots,
+        ],
+        abi.params
+    );
+
+    for invalid in [
+        "batchDeposit",
+        "(bytes pubkeys,bytes withdrawal_credentials,bytes signatures)",
+        "batchDeposit(bytes pubkeys,bytes signatures)",
+        "batchDeposit(bytes pubkeys,bytes withdrawal_credentials,bytes signatures,uint256 amount)",
+        "batchDeposit(bytes pubkeys,bytes withdrawal_credentials,bytes signatures,bytes pubkeys)",
+    ] {
+        assert!(
+            invalid.parse::<BatchDepositAbi>().is_err(),
+            "{invalid} should not parse"
+        );
+    }
+}
+
+#[test]
+fn it_generates_batch_deposit_transaction() {
+    let deposits = goerli_deposits();
+    let spec = chain_spec_for_network(&SupportedNetworks::Goerli).unwrap();
+    let export = batch_deposit_transaction(
+        &deposits,
+        &spec,
+        BATCH_CONTRACT_ADDRESS,
+        &BatchDepositAbi::default(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        deposits
+            .iter()
+            .map(|deposit| deposit.pubkey.clone())
+            .collect::<Vec<_>>(),
+        export.pubkeys
+    );
+    assert_eq!(96_000_000_000, export.amount);
+    assert_eq!(5, export.chain_id);
+    assert_eq!(BATCH_CONTRACT_ADDRESS.to_lowercase(), export.transaction.to);
+    // 96 ether
+    assert_eq!("0x53444835ec5800000", export.transaction.value);
+
+    let calldata = hex::decode(export.transaction.data.strip_prefix("0x").unwrap()).unwrap();
+    let word = |i: usize| hex::encode(&calldata[4 + i * 32..4 + (i + 1) * 32]);
+    let concat = |field: fn(&DepositExport) -> &String| {
+        deposits.iter().map(field).cloned().collect::<String>()
+    };
+    assert_eq!("c82655b7", hex::encode(&calldata[..4]));
+    // pubkeys: 144 bytes in 5 words, withdrawal credentials: 96 bytes in 3 words,
+    // signatures: 288 bytes in 9 words, each preceded by length
+    assert_eq!(format!("{:064x}", 0x80), word(0));
+    assert_eq!(format!("{:064x}", 0x80 + 6 * 32), word(1));
+    assert_eq!(format!("{:064x}", 0x80 + 10 * 32), word(2));
+    assert_eq!(format!("{:064x}", 0x80 + 20 * 32), word(3));
+    assert_eq!(format!("{:064x}", 144), word(4));
+    assert_eq!(
+        format!("{}{}", concat(|d| &d.pubkey), "0".repeat(32)),
+        (5..10).map(word).collect::<String>()
+    );
+    assert_eq!(format!("{:064x}", 96), word(10));
+    assert_eq!(
+        concat(|d| &d.withdrawal_credentials),
+        (11..14).map(word).collect::<String>()
+    );
+    assert_eq!(format!("{:064x}", 288), word(14));
+    assert_eq!(
+        concat(|d| &d.signature),
+        (15..24).map(word).collect::<String>()
+    );
+    assert_eq!(format!("{:064x}", 3), word(24));
+    assert_eq!(
+        concat(|d| &d.deposit_data_root),
+        (25..28).map(word).collect::<String>()
+    );
+    assert_eq!(4 + 28 * 32, calldata.len());
+}
+
+#[test]
+fn it_packs_shared_withdrawal_credentials() {
+    let deposits = goerli_deposits();
+    let spec = chain_spec_for_network(&SupportedNetworks::Goerli).unwrap();
+    let abi: BatchDepositAbi =
+        "deposit(bytes pubkeys,bytes32 withdrawal_credentials,bytes signatures)"
+            .parse()
+            .unwrap();
+    let export = batch_deposit_transaction(&deposits, &spec, BATCH_CONTRACT_ADDRESS, &abi).unwrap();
+
+    let calldata = hex::decode(export.transaction.data.strip_prefix("0x").unwrap()).unwrap();
+    assert_eq!(
+        deposits[0].withdrawal_credentials,
+        hex::encode(&calldata[4 + 32..4 + 64])
+    );
+    assert_eq!(
+        format!("{:064x}", 0x60 + 6 * 32),
+        hex::encode(&calldata[4 + 64..4 + 96])
+    );
+}
+
+#[test]
+fn it_rejects_inconsistent_batch() {
+    let spec = chain_spec_for_network(&SupportedNetworks::Goerli).unwrap();
+    let abi = BatchDepositAbi::default();
+
+    let err = batch_deposit_transaction(&[], &spec, BATCH_CONTRACT_ADDRESS, &abi).unwrap_err();
+    assert_eq!(
+        DepositError::InvalidDepositData("No deposits to batch".to_string()),
+        err
+    );
+
+    let err = batch_deposit_transaction(&goerli_deposits(), &spec, "0x1234", &abi).unwrap_err();
+    assert!(matches!(err, DepositError::InvalidExecutionAddress(_)));
+
+    // Every entry is re-verified
+    let mut deposits = goerli_deposits();
+    deposits[2].amount = 31_000_000_000;
+    let err =
+        batch_deposit_transaction(&deposits, &spec, BATCH_CONTRACT_ADDRESS, &abi).unwrap_err();
+    assert!(err.to_string().contains(&deposits[2].pubkey));
+
+    let mut deposits = goerli_deposits();
+    deposits.push(deposits[0].clone());
+    let err =
+        batch_deposit_transaction(&deposits, &spec, BATCH_CONTRACT_ADDRESS, &abi).unwrap_err();
+    assert_eq!(
+        DepositError::InvalidDepositData(format!(
+            "Validator {} is deposited more than once in the batch",
+            deposits[0].pubkey
+        )),
+        err
+    );
+}
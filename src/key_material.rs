@@ -1,14 +1,24 @@
+use std::fmt;
+use std::str::FromStr;
+
 use bip39::Seed as Bip39Seed;
 use eth2_key_derivation::DerivedKey;
 use eth2_keystore::{
-    json_keystore::Kdf, keypair_from_secret, Keystore, KeystoreBuilder, PlainText,
+    json_keystore::{JsonKeystore, Kdf},
+    keypair_from_secret, Keystore, KeystoreBuilder, PlainText,
 };
-use eth2_wallet::{KeyType, ValidatorPath};
+use rayon::prelude::*;
 use types::{Keypair, SecretKey};
+use zeroize::Zeroizing;
 
-use crate::utils::{pbkdf2, scrypt};
+use crate::utils::{
+    pbkdf2, pbkdf2_with, scrypt, scrypt_with, PBKDF2_C, SCRYPT_N, SCRYPT_P, SCRYPT_R,
+};
+use crate::DepositError;
 
 /// Contains keystore encrypted with password, along with original voting secret.
+///
+/// Voting secret and secret keys of keypairs are zeroized on drop.
 #[derive(Clone)]
 pub struct VotingKeyMaterial {
     pub keystore: Option<Keystore>,
@@ -18,23 +28,97 @@ pub struct VotingKeyMaterial {
 }
 
 impl VotingKeyMaterial {
-    pub fn from_voting_secret_bytes(voting_secret_bytes: &[u8]) -> Self {
-        let sk = SecretKey::deserialize(voting_secret_bytes).expect("Invalid private key passed");
+    pub fn from_voting_secret_bytes(voting_secret_bytes: &[u8]) -> Result<Self, DepositError> {
+        let sk = SecretKey::deserialize(voting_secret_bytes).map_err(|e| {
+            DepositError::InvalidPrivateKey(format!("Invalid private key passed: {:?}", e))
+        })?;
         let pk = sk.public_key();
         let keypair = Keypair::from_components(pk, sk);
         let voting_secret = voting_secret_bytes.to_vec().into();
 
-        Self {
+        Ok(Self {
             keypair,
             voting_secret,
             keystore: None,
             withdrawal_keypair: None,
+        })
+    }
+
+    /// Decrypt existing EIP-2335 keystore with password,
+    /// keeping the keystore along with decrypted voting secret.
+    pub fn from_keystore(keystore: Keystore, password: &[u8]) -> Result<Self, DepositError> {
+        let keypair = keystore.decrypt_keypair(password).map_err(|e| {
+            DepositError::InvalidKeystore(format!(
+                "Can not decrypt keystore {}: {:?}",
+                keystore.pubkey(),
+                e
+            ))
+        })?;
+        let voting_secret = keypair.sk.serialize().as_bytes().to_vec().into();
+
+        Ok(Self {
+            keystore: Some(keystore),
+            keypair,
+            voting_secret,
+            withdrawal_keypair: None,
+        })
+    }
+
+    /// Encrypt voting keypair into new keystore with another password and kdf,
+    /// keeping derivation path of the original keystore.
+    ///
+    /// UUID of the original keystore is kept if `keep_uuid` is set,
+    /// otherwise new random UUID is generated.
+    pub fn reencrypt_keystore(
+        &self,
+        password: &[u8],
+        kdf: Kdf,
+        keep_uuid: bool,
+    ) -> Result<Keystore, DepositError> {
+        if password.len() < 8 {
+            return Err(DepositError::InvalidPassword(
+                "The password length should be at least 8".to_string(),
+            ));
+        }
+        let original = self.keystore.as_ref().ok_or_else(|| {
+            DepositError::InvalidKeystore("No keystore to re-encrypt".to_string())
+        })?;
+        let keystore =
+            KeystoreBuilder::new(&self.keypair, password, original.path().unwrap_or_default())
+                .map_err(|e| {
+                    DepositError::InvalidKeystore(format!(
+                        "Can not create KeystoreBuilder from keypair: {:?}",
+                        e
+                    ))
+                })?
+                .kdf(kdf)
+                .build()
+                .map_err(|e| {
+                    DepositError::InvalidKeystore(format!("Failed to build keystore: {:?}", e))
+                })?;
+        if !keep_uuid {
+            return Ok(keystore);
         }
+
+        // Keystore builder always generates random UUID,
+        // so original one is set through JSON representation
+        let invalid =
+            |e: String| DepositError::InvalidKeystore(format!("Can not keep keystore UUID: {}", e));
+        let mut keystore_json: JsonKeystore = serde_json::from_str(
+            &keystore
+                .to_json_string()
+                .map_err(|e| invalid(format!("{:?}", e)))?,
+        )
+        .map_err(|e| invalid(e.to_string()))?;
+        keystore_json.uuid = *original.uuid();
+        let keystore_json =
+            serde_json::to_string(&keystore_json).map_err(|e| invalid(e.to_string()))?;
+        Keystore::from_json_str(&keystore_json).map_err(|e| invalid(format!("{:?}", e)))
     }
 }
 
 /// Key derivation function for the keystore
-#[derive(clap::ValueEnum, Clone)]
+#[derive(clap::ValueEnum, Clone, Debug)]
 pub enum KdfVariant {
     Scrypt,
     Pbkdf2,
@@ -49,8 +133,289 @@ impl From<KdfVariant> for Kdf {
     }
 }
 
+/// Key derivation function for the keystore, along with its parameters.
+///
+/// Parameters default to the ones recommended by EIP-2335, which are also
+/// their lower bounds, unless insecure mode is enabled for test fixtures.
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct KdfParams {
+    /// Use this argument to select the key derivation function for the keystores.
+    ///
+    /// Depending on your use case with `scrypt` using higher security parameters
+    /// and consequently slower performance vs `pbkdf2`,
+    /// achieving better performance with lower security parameters compared to `scrypt`
+    #[arg(long)]
+    pub kdf: Option<KdfVariant>,
+
+    /// PBKDF2 iteration count, 262144 by default.
+    #[arg(long, visible_alias = "pbkdf2_c")]
+    pub pbkdf2_c: Option<u32>,
+
+    /// Scrypt CPU and memory cost parameter, power of two, 262144 by default.
+    #[arg(long, visible_alias = "scrypt_n")]
+    pub scrypt_n: Option<u32>,
+
+    /// Scrypt block size parameter, 8 by default.
+    #[arg(long, visible_alias = "scrypt_r")]
+    pub scrypt_r: Option<u32>,
+
+    /// Scrypt parallelization parameter, 1 by default.
+    #[arg(long, visible_alias = "scrypt_p")]
+    pub scrypt_p: Option<u32>,
+
+    /// INSECURE: allow KDF parameters below the defaults recommended by EIP-2335.
+    ///
+    /// Keystores encrypted with weak parameters are easy to brute force,
+    /// so this is only meant for generating cheap test fixtures.
+    #[arg(long, visible_alias = "insecure_kdf")]
+    pub insecure_kdf: bool,
+}
+
+impl From<KdfVariant> for KdfParams {
+    fn from(value: KdfVariant) -> Self {
+        Self {
+            kdf: Some(value),
+            ..Default::default()
+        }
+    }
+}
+
+impl KdfParams {
+    /// Whether any of key derivation function or its parameters is set.
+    pub fn is_set(&self) -> bool {
+        self.kdf.is_some()
+            || self.pbkdf2_c.is_some()
+            || self.scrypt_n.is_some()
+            || self.scrypt_r.is_some()
+            || self.scrypt_p.is_some()
+    }
+
+    /// Build key derivation function with fresh salt, checking parameters
+    /// against their lower bounds, `pbkdf2` being used by default.
+    pub fn to_kdf(&self) -> Result<Kdf, DepositError> {
+        let check = |name: &str, value: u32, lower_bound: u32| {
+            if value == 0 {
+                return Err(DepositError::InvalidInput(format!(
+                    "KDF parameter {name} must be positive"
+                )));
+            }
+            if value < lower_bound && !self.insecure_kdf {
+                return Err(DepositError::InvalidInput(format!(
+                    "KDF parameter {name} must be at least {lower_bound}, \
+                    lower values are only allowed in insecure mode"
+                )));
+            }
+            Ok(value)
+        };
+        let kdf = match self.kdf.clone().unwrap_or(KdfVariant::Pbkdf2) {
+            KdfVariant::Pbkdf2 => {
+                if self.scrypt_n.is_some() || self.scrypt_r.is_some() || self.scrypt_p.is_some() {
+                    return Err(DepositError::InvalidInput(
+                        "Scrypt parameters can only be set for scrypt KDF".to_string(),
+                    ));
+                }
+                let c = check("pbkdf2_c", self.pbkdf2_c.unwrap_or(PBKDF2_C), PBKDF2_C)?;
+                pbkdf2_with(c)
+            }
+            KdfVariant::Scrypt => {
+                if self.pbkdf2_c.is_some() {
+                    return Err(DepositError::InvalidInput(
+                        "PBKDF2 parameters can only be set for pbkdf2 KDF".to_string(),
+                    ));
+                }
+                let n = check("scrypt_n", self.scrypt_n.unwrap_or(SCRYPT_N), SCRYPT_N)?;
+                if n < 2 || !n.is_power_of_two() {
+                    return Err(DepositError::InvalidInput(
+                        "KDF parameter scrypt_n must be a power of two, greater than 1".to_string(),
+                    ));
+                }
+                let r = check("scrypt_r", self.scrypt_r.unwrap_or(SCRYPT_R), SCRYPT_R)?;
+                let p = check("scrypt_p", self.scrypt_p.unwrap_or(SCRYPT_P), SCRYPT_P)?;
+                scrypt_with(n, r, p)
+            }
+        };
+        if self.insecure_kdf {
+            // Printed regardless of log level, as keystores may end up unsafe
+            eprintln!("WARNING: Insecure KDF mode is enabled, keystores may use weak parameters");
+        }
+        Ok(kdf)
+    }
+}
+
+/// Node of derivation path template, either fixed index or validator index placeholder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PathNode {
+    Fixed(u32),
+    ValidatorIndex,
+}
+
+/// Key derivation path template, e.g. `m/12381/3600/{i}/0/0`,
+/// where `{i}` is replaced with validator index.
+///
+/// Raw path without `{i}` placeholder derives the same key for any validator index,
+/// so it can only be used for a single validator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathTemplate(Vec<PathNode>);
+
+impl PathTemplate {
+    /// EIP-2334 voting key path, `m/12381/3600/{i}/0/0`.
+    pub fn voting() -> Self {
+        Self(vec![
+            PathNode::Fixed(12381),
+            PathNode::Fixed(3600),
+            PathNode::ValidatorIndex,
+            PathNode::Fixed(0),
+            PathNode::Fixed(0),
+        ])
+    }
+
+    /// EIP-2334 withdrawal key path, `m/12381/3600/{i}/0`.
+    pub fn withdrawal() -> Self {
+        Self(vec![
+            PathNode::Fixed(12381),
+            PathNode::Fixed(3600),
+            PathNode::ValidatorIndex,
+            PathNode::Fixed(0),
+        ])
+    }
+
+    /// Whether path contains validator index placeholder.
+    pub fn has_validator_index(&self) -> bool {
+        self.0.contains(&PathNode::ValidatorIndex)
+    }
+
+    /// Derivation path nodes for validator index.
+    fn nodes(&self, idx: u32) -> impl Iterator<Item = u32> + '_ {
+        self.0.iter().map(move |node| match node {
+            PathNode::Fixed(i) => *i,
+            PathNode::ValidatorIndex => idx,
+        })
+    }
+
+    /// Derivation path for validator index, as recorded in keystore.
+    pub fn path(&self, idx: u32) -> String {
+        self.nodes(idx)
+            .fold("m".to_string(), |path, i| format!("{path}/{i}"))
+    }
+}
+
+impl fmt::Display for PathTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for node in self.0.iter() {
+            match node {
+                PathNode::Fixed(i) => write!(f, "/{i}")?,
+                PathNode::ValidatorIndex => write!(f, "/{{i}}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PathTemplate {
+    type Err = DepositError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| {
+            DepositError::InvalidInput(format!("Invalid derivation path {s}: {reason}"))
+        };
+        let mut parts = s.trim().split('/');
+        if parts.next() != Some("m") {
+            return Err(invalid("must start with m/"));
+        }
+        let nodes = parts
+            .map(|part| match part {
+                "{i}" => Ok(PathNode::ValidatorIndex),
+                _ => part
+                    .parse()
+                    .map(PathNode::Fixed)
+                    .map_err(|_| invalid("nodes must be either {i} or unsigned 32-bit integers")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if nodes.is_empty() {
+            return Err(invalid("must have at least one node"));
+        }
+        Ok(Self(nodes))
+    }
+}
+
+/// Derivation paths of voting and withdrawal keys, EIP-2334 ones by default.
+///
+/// Custom paths are meant for recovering keys created by other tools
+/// with non-standard paths, new keys should always follow EIP-2334.
+/// Flattened into every subcommand which derives keys from the mnemonic.
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct DerivationPaths {
+    /// Custom derivation path of voting keys, `m/12381/3600/{i}/0/0` by default.
+    ///
+    /// `{i}` is replaced with validator index, raw path without it
+    /// can only be used for a single validator.
+    /// Derived path is recorded in the `path` field of keystores.
+    #[arg(long, visible_alias = "voting_path")]
+    pub voting_path: Option<PathTemplate>,
+
+    /// Custom derivation path of withdrawal keys, `m/12381/3600/{i}/0` by default.
+    ///
+    /// `{i}` is replaced with validator index, raw path without it
+    /// can only be used for a single validator.
+    #[arg(long, visible_alias = "withdrawal_path")]
+    pub withdrawal_path: Option<PathTemplate>,
+}
+
+impl DerivationPaths {
+    /// Whether any of custom derivation paths is set.
+    pub fn is_set(&self) -> bool {
+        self.voting_path.is_some() || self.withdrawal_path.is_some()
+    }
+
+    /// Voting key path template, EIP-2334 one unless set.
+    pub fn voting(&self) -> PathTemplate {
+        self.voting_path
+            .clone()
+            .unwrap_or_else(PathTemplate::voting)
+    }
+
+    /// Withdrawal key path template, EIP-2334 one unless set.
+    pub fn withdrawal(&self) -> PathTemplate {
+        self.withdrawal_path
+            .clone()
+            .unwrap_or_else(PathTemplate::withdrawal)
+    }
+
+    /// Check path templates can be used to derive given number of validators,
+    /// and warn about paths deviating from EIP-2334.
+    pub(crate) fn check(
+        &self,
+        num_validators: usize,
+        derive_withdrawal: bool,
+    ) -> Result<(), DepositError> {
+        let mut paths = vec![("Voting", self.voting(), PathTemplate::voting())];
+        if derive_withdrawal {
+            paths.push(("Withdrawal", self.withdrawal(), PathTemplate::withdrawal()));
+        }
+        for (key_type, path, standard_path) in paths {
+            if num_validators > 1 && !path.has_validator_index() {
+                return Err(DepositError::InvalidInput(format!(
+                    "{key_type} key derivation path {path} has no {{i}} placeholder, \
+                    so it can only be used for a single validator"
+                )));
+            }
+            if path != standard_path {
+                eprintln!(
+                    "WARNING: {key_type} key derivation path {path} deviates \
+                    from EIP-2334 path {standard_path}"
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Given eth2 wallet seed, create N key material wrappers,
 /// with voting secret and keystore encrypted with password.
+///
+/// Keys are derived and encrypted in parallel on the current rayon thread pool,
+/// while the order of returned key material always follows validator indices.
 pub(crate) fn seed_to_key_material(
     seed: &Bip39Seed,
     n: u32,
@@ -58,53 +423,86 @@ pub(crate) fn seed_to_key_material(
     password: Option<Vec<u8>>,
     derive_withdrawal: bool,
     kdf: Option<Kdf>,
-) -> Vec<VotingKeyMaterial> {
+    derivation_paths: &DerivationPaths,
+) -> Result<Vec<VotingKeyMaterial>, DepositError> {
+    if password.as_ref().is_some_and(|pass| pass.len() < 8) {
+        return Err(DepositError::InvalidPassword(
+            "The password length should be at least 8".to_string(),
+        ));
+    }
+    let password = password.map(Zeroizing::new);
     let kdf = kdf.unwrap_or(pbkdf2());
-    (start_index..start_index + n)
+    let voting_path = derivation_paths.voting();
+    let withdrawal_path = derivation_paths.withdrawal();
+    let end_index = start_index.checked_add(n).ok_or_else(|| {
+        DepositError::InvalidInput("Validator index range is too large".to_string())
+    })?;
+    let master = master_key(seed)?;
+    (start_index..end_index)
+        .into_par_iter()
         .map(|idx| {
-            let master = DerivedKey::from_seed(seed.as_bytes()).expect("Invalid seed is provided");
-            let (voting_path, voting_secret, keypair) =
-                derive_keypair(master, idx, KeyType::Voting);
-            let keystore = password.clone().map(|pass| {
-                if pass.len() < 8 {
-                    panic!("The password length should be at least 8");
-                }
-                KeystoreBuilder::new(&keypair, pass.as_slice(), format!("{}", voting_path))
-                    .expect("Can not create KeystoreBuilder from provided seed")
-                    .kdf(kdf.clone())
-                    .build()
-                    .expect("Failed to build keystore")
-            });
+            let (voting_secret, keypair) = derive_keypair(&master, &voting_path, idx)?;
+            let keystore = password
+                .as_ref()
+                .map(|pass| {
+                    KeystoreBuilder::new(&keypair, pass.as_slice(), voting_path.path(idx))
+                        .map_err(|e| {
+                            DepositError::InvalidKeystore(format!(
+                                "Can not create KeystoreBuilder from provided seed: {:?}",
+                                e
+                            ))
+                        })?
+                        .kdf(kdf.clone())
+                        .build()
+                        .map_err(|e| {
+                            DepositError::InvalidKeystore(format!(
+                                "Failed to build keystore: {:?}",
+                                e
+                            ))
+                        })
+                })
+                .transpose()?;
             let withdrawal_keypair = if derive_withdrawal {
-                let master =
-                    DerivedKey::from_seed(seed.as_bytes()).expect("Invalid seed is provided");
-                let (_, _, withdrawal_keypair) = derive_keypair(master, idx, KeyType::Withdrawal);
+                let (_, withdrawal_keypair) = derive_keypair(&master, &withdrawal_path, idx)?;
                 Some(withdrawal_keypair)
             } else {
                 None
             };
 
-            VotingKeyMaterial {
+            Ok(VotingKeyMaterial {
                 keystore,
                 keypair,
                 voting_secret,
                 withdrawal_keypair,
-            }
+            })
         })
         .collect()
 }
 
-fn derive_keypair(
-    master: DerivedKey,
+pub(crate) fn master_key(seed: &Bip39Seed) -> Result<DerivedKey, DepositError> {
+    DerivedKey::from_seed(seed.as_bytes())
+        .map_err(|e| DepositError::KeyDerivationError(format!("Invalid seed is provided: {:?}", e)))
+}
+
+pub(crate) fn derive_keypair(
+    master: &DerivedKey,
+    path: &PathTemplate,
     idx: u32,
-    key_type: KeyType,
-) -> (ValidatorPath, PlainText, Keypair) {
-    let voting_path = ValidatorPath::new(idx, key_type);
-    let voting_destination = voting_path.iter_nodes().fold(master, |dk, i| dk.child(*i));
+) -> Result<(PlainText, Keypair), DepositError> {
+    let voting_destination = path
+        .nodes(idx)
+        .fold(None, |dk: Option<DerivedKey>, i| {
+            Some(dk.as_ref().unwrap_or(master).child(i))
+        })
+        .ok_or_else(|| DepositError::KeyDerivationError("Empty derivation path".to_string()))?;
     let voting_secret: PlainText = voting_destination.secret().to_vec().into();
-    let keypair = keypair_from_secret(voting_secret.as_bytes())
-        .expect("Can not initialize keypair from provided seed");
-    (voting_path, voting_secret, keypair)
+    let keypair = keypair_from_secret(voting_secret.as_bytes()).map_err(|e| {
+        DepositError::KeyDerivationError(format!(
+            "Can not initialize keypair from provided seed: {:?}",
+            e
+        ))
+    })?;
+    Ok((voting_secret, keypair))
 }
 
 #[cfg(test)]
@@ -112,9 +510,16 @@ mod test {
 
     use crate::utils::{self, pbkdf2, scrypt};
 
-    use super::seed_to_key_material;
+    use super::{
+        seed_to_key_material, DerivationPaths, KdfParams, KdfVariant, PathTemplate,
+        VotingKeyMaterial,
+    };
+    use crate::DepositError;
     use ::bip39::{Language, Mnemonic, Seed};
-    use eth2_keystore::{json_keystore::JsonKeystore, Keystore};
+    use eth2_keystore::{
+        json_keystore::{JsonKeystore, Kdf},
+        Keystore,
+    };
     use pretty_assertions::assert_eq;
     use test_log::test;
     use types::{Hash256, PublicKey};
@@ -144,6 +549,236 @@ mod test {
         kdf_function
     }
 
+    #[test]
+    fn test_key_material_from_keystore() {
+        let seed = seed_from_mnemonic();
+        let key_material = seed_to_key_material(
+            &seed,
+            1,
+            0,
+            Some(VOTING_KEYSTORE_PASSWORD.to_vec()),
+            false,
+            Some(pbkdf2()),
+            &DerivationPaths::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let keystore = key_material.keystore.clone().unwrap();
+
+        let decrypted =
+            VotingKeyMaterial::from_keystore(keystore.clone(), VOTING_KEYSTORE_PASSWORD).unwrap();
+        assert_eq!(key_material.keypair.pk, decrypted.keypair.pk);
+        assert_eq!(
+            key_material.voting_secret.as_bytes(),
+            decrypted.voting_secret.as_bytes()
+        );
+        assert_eq!(
+            Some(keystore.uuid()),
+            decrypted.keystore.as_ref().map(|k| k.uuid())
+        );
+
+        assert!(matches!(
+            VotingKeyMaterial::from_keystore(keystore, b"wrongpassword"),
+            Err(DepositError::InvalidKeystore(_))
+        ));
+    }
+
+    #[test]
+    fn test_reencrypt_keystore() {
+        let seed = seed_from_mnemonic();
+        let key_material = seed_to_key_material(
+            &seed,
+            1,
+            0,
+            Some(VOTING_KEYSTORE_PASSWORD.to_vec()),
+            false,
+            Some(pbkdf2()),
+            &DerivationPaths::default(),
+        )
+        .unwrap()
+        .remove(0);
+        let original = key_material.keystore.as_ref().unwrap();
+        let new_password = b"newpassword";
+
+        let keystore = key_material
+            .reencrypt_keystore(new_password, scrypt(), true)
+            .unwrap();
+        assert_eq!("scrypt", parse_kdf_function(&keystore));
+        assert_eq!(original.pubkey(), keystore.pubkey());
+        assert_eq!(original.path(), keystore.path());
+        assert_eq!(original.uuid(), keystore.uuid());
+        assert_eq!(
+            key_material.keypair.pk,
+            keystore.decrypt_keypair(new_password).unwrap().pk
+        );
+        assert!(keystore.decrypt_keypair(VOTING_KEYSTORE_PASSWORD).is_err());
+
+        let keystore = key_material
+            .reencrypt_keystore(new_password, pbkdf2(), false)
+            .unwrap();
+        assert_eq!("pbkdf2", parse_kdf_function(&keystore));
+        assert_ne!(original.uuid(), keystore.uuid());
+
+        assert!(matches!(
+            key_material.reencrypt_keystore(b"short", pbkdf2(), false),
+            Err(DepositError::InvalidPassword(_))
+        ));
+    }
+
+    #[test]
+    fn test_kdf_params() {
+        let kdf = KdfParams::default().to_kdf().unwrap();
+        assert!(matches!(kdf, Kdf::Pbkdf2(params) if params.c == utils::PBKDF2_C));
+
+        let kdf = KdfParams {
+            kdf: Some(KdfVariant::Scrypt),
+            scrypt_n: Some(1 << 20),
+            ..Default::default()
+        }
+        .to_kdf()
+        .unwrap();
+        assert!(
+            matches!(kdf, Kdf::Scrypt(params) if params.n == 1 << 20 && params.r == 8 && params.p == 1)
+        );
+
+        let weak_params = KdfParams {
+            kdf: Some(KdfVariant::Scrypt),
+            scrypt_n: Some(1024),
+            scrypt_r: Some(1),
+            ..Default::default()
+        };
+        assert!(matches!(
+            weak_params.to_kdf(),
+            Err(DepositError::InvalidInput(message)) if message.contains("insecure mode")
+        ));
+        let kdf = KdfParams {
+            insecure_kdf: true,
+            ..weak_params
+        }
+        .to_kdf()
+        .unwrap();
+        assert!(matches!(kdf, Kdf::Scrypt(params) if params.n == 1024 && params.r == 1));
+
+        for invalid_params in [
+            KdfParams {
+                kdf: Some(KdfVariant::Scrypt),
+                scrypt_n: Some(300_000),
+                ..Default::default()
+            },
+            KdfParams {
+                kdf: Some(KdfVariant::Scrypt),
+                pbkdf2_c: Some(1 << 20),
+                ..Default::default()
+            },
+            KdfParams {
+                scrypt_n: Some(1 << 20),
+                ..Default::default()
+            },
+            KdfParams {
+                pbkdf2_c: Some(0),
+                insecure_kdf: true,
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                invalid_params.to_kdf(),
+                Err(DepositError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_seed_to_key_material_order_is_deterministic() {
+        let seed = seed_from_mnemonic();
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        let key_materials = pool
+            .install(|| {
+                seed_to_key_material(&seed, 16, 3, None, true, None, &DerivationPaths::default())
+            })
+            .unwrap();
+
+        assert_eq!(16, key_materials.len());
+        for (idx, key_material) in (3..19).zip(key_materials.iter()) {
+            let expected =
+                seed_to_key_material(&seed, 1, idx, None, true, None, &DerivationPaths::default())
+                    .unwrap()
+                    .remove(0);
+            assert_eq!(expected.keypair.pk, key_material.keypair.pk);
+            assert_eq!(
+                expected.withdrawal_keypair.unwrap().pk,
+                key_material.withdrawal_keypair.as_ref().unwrap().pk
+            );
+        }
+    }
+
+    #[test]
+    fn test_path_template() {
+        let template: PathTemplate = "m/12381/3600/{i}/0/0".parse().unwrap();
+        assert_eq!(PathTemplate::voting(), template);
+        assert_eq!("m/12381/3600/{i}/0/0", template.to_string());
+        assert_eq!("m/12381/3600/7/0/0", template.path(7));
+
+        let raw: PathTemplate = "m/12381/60/0/0".parse().unwrap();
+        assert!(!raw.has_validator_index());
+        assert_eq!("m/12381/60/0/0", raw.path(7));
+
+        for invalid in [
+            "",
+            "m",
+            "12381/3600/{i}",
+            "m/12381//0",
+            "m/12381'/0",
+            "m/{j}",
+        ] {
+            assert!(invalid.parse::<PathTemplate>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_seed_to_key_material_custom_path() {
+        let seed = seed_from_mnemonic();
+        let standard =
+            seed_to_key_material(&seed, 1, 3, None, true, None, &DerivationPaths::default())
+                .unwrap()
+                .remove(0);
+
+        // Voting key at the withdrawal path of EIP-2334 is the standard withdrawal key
+        let derivation_paths = DerivationPaths {
+            voting_path: Some(PathTemplate::withdrawal()),
+            withdrawal_path: None,
+        };
+        let custom = seed_to_key_material(
+            &seed,
+            1,
+            3,
+            Some(VOTING_KEYSTORE_PASSWORD.to_vec()),
+            false,
+            Some(pbkdf2()),
+            &derivation_paths,
+        )
+        .unwrap()
+        .remove(0);
+        assert_eq!(standard.withdrawal_keypair.unwrap().pk, custom.keypair.pk);
+        assert_eq!(
+            Some("m/12381/3600/3/0".to_string()),
+            custom.keystore.unwrap().path()
+        );
+    }
+
+    #[test]
+    fn test_derivation_paths_check() {
+        let raw_path = DerivationPaths {
+            voting_path: Some("m/12381/3600/0/0/0".parse().unwrap()),
+            withdrawal_path: None,
+        };
+        assert!(raw_path.check(1, true).is_ok());
+        assert!(raw_path.check(2, false).is_err());
+        assert!(DerivationPaths::default().check(2, true).is_ok());
+    }
+
     #[test]
     fn test_seed_to_keystore() {
         let seed = seed_from_mnemonic();
@@ -154,7 +789,9 @@ mod test {
             Some(VOTING_KEYSTORE_PASSWORD.to_vec()),
             false,
             None,
-        );
+            &DerivationPaths::default(),
+        )
+        .unwrap();
 
         assert_eq!(keystores.len(), 3);
 
@@ -207,7 +844,9 @@ mod test {
             Some(VOTING_KEYSTORE_PASSWORD.to_vec()),
             true,
             None,
-        );
+            &DerivationPaths::default(),
+        )
+        .unwrap();
 
         assert_eq!(keystores.len(), 3);
 
@@ -246,10 +885,32 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_seed_to_keystore_short_password() {
+        let seed = seed_from_mnemonic();
+        assert_eq!(
+            Some(DepositError::InvalidPassword(
+                "The password length should be at least 8".to_string()
+            )),
+            seed_to_key_material(
+                &seed,
+                1,
+                0,
+                Some(b"short".to_vec()),
+                false,
+                None,
+                &DerivationPaths::default()
+            )
+            .err()
+        );
+    }
+
     #[test]
     fn test_key_material_no_keystore() {
         let seed = seed_from_mnemonic();
-        let keystores = seed_to_key_material(&seed, 2, 0, None, true, None);
+        let keystores =
+            seed_to_key_material(&seed, 2, 0, None, true, None, &DerivationPaths::default())
+                .unwrap();
 
         assert_eq!(keystores.len(), 2);
 
@@ -275,7 +936,9 @@ mod test {
             Some(VOTING_KEYSTORE_PASSWORD.to_vec()),
             true,
             None,
-        );
+            &DerivationPaths::default(),
+        )
+        .unwrap();
 
         assert_eq!(keystores.len(), 2);
 
@@ -314,6 +977,23 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_seed_to_keystore_start_index_overflow() {
+        let seed = seed_from_mnemonic();
+
+        let result = seed_to_key_material(
+            &seed,
+            2,
+            u32::MAX,
+            None,
+            false,
+            None,
+            &DerivationPaths::default(),
+        );
+
+        assert!(matches!(result, Err(DepositError::InvalidInput(_))));
+    }
+
     #[test]
     fn test_seed_to_keystore_scrypt() {
         let seed = seed_from_mnemonic();
@@ -324,7 +1004,9 @@ mod test {
             Some(VOTING_KEYSTORE_PASSWORD.to_vec()),
             false,
             Some(scrypt()),
-        );
+            &DerivationPaths::default(),
+        )
+        .unwrap();
 
         assert_eq!(keystores.len(), 3);
 
@@ -377,7 +1059,9 @@ mod test {
             Some(VOTING_KEYSTORE_PASSWORD.to_vec()),
             false,
             Some(pbkdf2()),
-        );
+            &DerivationPaths::default(),
+        )
+        .unwrap();
 
         assert_eq!(keystores.len(), 3);
 
@@ -3,13 +3,19 @@ pub(crate) mod beacon_node;
 pub mod bls_to_execution_change;
 pub mod chain_spec;
 pub mod cli;
+pub mod consolidation_request;
 pub(crate) mod deposit;
+pub mod deposit_transaction;
 pub(crate) mod key_material;
 pub mod networks;
 pub(crate) mod seed;
+pub mod seed_index;
+pub mod transaction;
 pub mod utils;
 pub mod validators;
 pub mod voluntary_exit;
+pub mod withdrawal_request;
 
 pub use deposit::DepositError;
+pub use key_material::{DerivationPaths, KdfParams, KdfVariant, PathTemplate};
 pub use validators::*;
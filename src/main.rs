@@ -1,8 +1,11 @@
 #![forbid(unsafe_code)]
 use clap::{Parser, Subcommand};
 use eth_staking_smith::cli::{
-    bls_to_execution_change, existing_mnemonic, new_mnemonic, presigned_exit_message,
+    bls_to_execution_change, consolidation_request, deposit_transaction, existing_mnemonic,
+    new_mnemonic, presigned_exit_message, recover_seed_index, reencrypt_keystore,
+    verify_deposit_data, withdrawal_request,
 };
+use eth_staking_smith::DepositError;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -16,6 +19,12 @@ enum SubCommands {
     /// Generates a SignedBLSToExecutionChange object which can be sent
     /// to the Beacon Node to change the withdrawal address from BLS to an execution address
     BlsToExecutionChange(bls_to_execution_change::BlsToExecutionChangeSubcommandOpts),
+    /// Generate EIP-7251 consolidation requests, along with transactions which
+    /// the withdrawal address owner sends to consolidate validators into compounding one
+    ConsolidationRequest(consolidation_request::ConsolidationRequestSubcommandOpts),
+    /// Verify deposit data file and generate transactions to the deposit contract
+    /// for every entry, to be proposed e.g. through multisig wallet
+    DepositTransaction(deposit_transaction::DepositTransactionSubcommandOpts),
     /// Generate (or recover) keys from an existing mnemonic.
     ExistingMnemonic(existing_mnemonic::ExistingMnemonicSubcommandOpts),
     /// Generate new keys with new mnemonic.
@@ -23,21 +32,43 @@ enum SubCommands {
     /// Generate presigned exit message which can be sent
     /// to the Beacon Node to start voluntary exit process for the validator
     PresignedExitMessage(presigned_exit_message::PresignedExitMessageSubcommandOpts),
+    /// Find validator seed indices of known public keys,
+    /// by deriving keys from the mnemonic over a range of indices
+    RecoverSeedIndex(recover_seed_index::RecoverSeedIndexSubcommandOpts),
+    /// Re-encrypt existing keystores with new password and key derivation function,
+    /// e.g. to rotate keystore passwords or migrate from pbkdf2 to scrypt
+    ReencryptKeystore(reencrypt_keystore::ReencryptKeystoreSubcommandOpts),
+    /// Verify every entry of existing deposit data file
+    /// against the given chain, and print report per entry
+    VerifyDepositData(verify_deposit_data::VerifyDepositDataSubcommandOpts),
+    /// Generate EIP-7002 withdrawal requests, along with transactions which
+    /// the withdrawal address owner sends to trigger exits or partial withdrawals
+    WithdrawalRequest(withdrawal_request::WithdrawalRequestSubcommandOpts),
 }
 
 impl SubCommands {
-    pub fn run(&self) {
+    pub fn run(&self) -> Result<(), DepositError> {
         match self {
             Self::BlsToExecutionChange(sub) => sub.run(),
+            Self::ConsolidationRequest(sub) => sub.run(),
+            Self::DepositTransaction(sub) => sub.run(),
             Self::ExistingMnemonic(sub) => sub.run(),
             Self::NewMnemonic(sub) => sub.run(),
             Self::PresignedExitMessage(sub) => sub.run(),
+            Self::RecoverSeedIndex(sub) => sub.run(),
+            Self::ReencryptKeystore(sub) => sub.run(),
+            Self::VerifyDepositData(sub) => sub.run(),
+            Self::WithdrawalRequest(sub) => sub.run(),
         }
     }
 }
 
 fn main() {
-    env_logger::init();
+    // Warnings, e.g. about chain config mismatches, are shown unless overridden by RUST_LOG
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
     let opts = Opts::parse();
-    opts.subcommand.run()
+    if let Err(e) = opts.subcommand.run() {
+        eprintln!("Error: {}", e);
+        std::process::exit(e.exit_code());
+    }
 }
@@ -7,7 +7,10 @@ use types::Hash256;
 pub enum SupportedNetworks {
     Mainnet,
     Holesky,
+    Hoodi,
+    Sepolia,
     Gnosis,
+    Chiado,
     // These are legacy networks they are supported on best effort basis
     Prater,
     Goerli,
@@ -18,9 +21,12 @@ impl std::fmt::Display for SupportedNetworks {
         let s = match self {
             SupportedNetworks::Mainnet => "mainnet",
             SupportedNetworks::Holesky => "holesky",
+            SupportedNetworks::Hoodi => "hoodi",
+            SupportedNetworks::Sepolia => "sepolia",
             SupportedNetworks::Prater => "goerli",
             SupportedNetworks::Goerli => "goerli",
             SupportedNetworks::Gnosis => "gnosis",
+            SupportedNetworks::Chiado => "chiado",
         };
         write!(f, "{}", s)
     }
@@ -40,12 +46,21 @@ lazy_static! {
     pub static ref GENESIS_VALIDATORS_ROOT_HOLESKY: Hash256 = decode_genesis_validators_root(
         "9143aa7c615a7f7115e2b6aac319c03529df8242ae705fba9df39b79c59fa8b1"
     );
+    pub static ref GENESIS_VALIDATORS_ROOT_HOODI: Hash256 = decode_genesis_validators_root(
+        "212f13fc4df078b6cb7db228f1c8307566dcecf900867401a92023d7ba99cb5f"
+    );
+    pub static ref GENESIS_VALIDATORS_ROOT_SEPOLIA: Hash256 = decode_genesis_validators_root(
+        "d8ea171f3c94aea21ebc42a1ed61052acf3f9209c00e4efbaaddac09ed9b8078"
+    );
     pub static ref GENESIS_VALIDATORS_ROOT_GOERLI: Hash256 = decode_genesis_validators_root(
         "043db0d9a83813551ee2f33450d23797757d430911a9320530ad8a0eabc43efb"
     );
     pub static ref GENESIS_VALIDATORS_ROOT_GNOSIS: Hash256 = decode_genesis_validators_root(
         "f5dcb5564e829aab27264b9becd5dfaa017085611224cb3036f573368dbb9d47"
     );
+    pub static ref GENESIS_VALIDATORS_ROOT_CHIADO: Hash256 = decode_genesis_validators_root(
+        "9d642dac73058fbf39c0ae41ab1e34e4d889043cb199851ded7095bc99eb4c1e"
+    );
     pub static ref GENESIS_VALIDATOR_ROOT: HashMap<SupportedNetworks, Hash256> = HashMap::from([
         (
             SupportedNetworks::Mainnet,
@@ -63,9 +78,21 @@ lazy_static! {
             SupportedNetworks::Holesky,
             GENESIS_VALIDATORS_ROOT_HOLESKY.to_owned()
         ),
+        (
+            SupportedNetworks::Hoodi,
+            GENESIS_VALIDATORS_ROOT_HOODI.to_owned()
+        ),
+        (
+            SupportedNetworks::Sepolia,
+            GENESIS_VALIDATORS_ROOT_SEPOLIA.to_owned()
+        ),
         (
             SupportedNetworks::Gnosis,
             GENESIS_VALIDATORS_ROOT_GNOSIS.to_owned()
+        ),
+        (
+            SupportedNetworks::Chiado,
+            GENESIS_VALIDATORS_ROOT_CHIADO.to_owned()
         )
     ]);
 }
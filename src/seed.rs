@@ -1,4 +1,55 @@
-use bip39::{Language, Mnemonic, MnemonicType, Seed as Bip39Seed};
+use bip39::{ErrorKind, Language, Mnemonic, MnemonicType, Seed as Bip39Seed};
+
+use zeroize::Zeroizing;
+
+use crate::DepositError;
+
+/// Language of BIP-39 mnemonic wordlist.
+///
+/// Czech and Portuguese wordlists are not available in the BIP-39 library we use.
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum MnemonicLanguage {
+    English,
+    ChineseSimplified,
+    ChineseTraditional,
+    French,
+    Italian,
+    Japanese,
+    Korean,
+    Spanish,
+}
+
+impl From<MnemonicLanguage> for Language {
+    fn from(value: MnemonicLanguage) -> Self {
+        match value {
+            MnemonicLanguage::English => Language::English,
+            MnemonicLanguage::ChineseSimplified => Language::ChineseSimplified,
+            MnemonicLanguage::ChineseTraditional => Language::ChineseTraditional,
+            MnemonicLanguage::French => Language::French,
+            MnemonicLanguage::Italian => Language::Italian,
+            MnemonicLanguage::Japanese => Language::Japanese,
+            MnemonicLanguage::Korean => Language::Korean,
+            MnemonicLanguage::Spanish => Language::Spanish,
+        }
+    }
+}
+
+/// Wordlists tried in order when parsing existing mnemonic.
+///
+/// Some words are shared between wordlists (e.g. English and French,
+/// or both Chinese wordlists), but seed is derived from the phrase itself
+/// rather than from word indices, so the same keys are derived
+/// whichever of matching languages is picked.
+const MNEMONIC_LANGUAGES: [Language; 8] = [
+    Language::English,
+    Language::ChineseSimplified,
+    Language::ChineseTraditional,
+    Language::French,
+    Language::Italian,
+    Language::Japanese,
+    Language::Korean,
+    Language::Spanish,
+];
 
 /// This creates new seed using platform dependent getrandom(2) system call.
 
@@ -8,25 +59,66 @@ use bip39::{Language, Mnemonic, MnemonicType, Seed as Bip39Seed};
 
 /// For non-Linux platforms, look up target platform implementation in
 /// https://github.com/rust-random/getrandom for details.
-fn create_new_seed() -> Mnemonic {
-    let mut bytes = vec![0u8; MnemonicType::Words24.entropy_bits() / 8];
+pub(crate) fn create_new_seed(
+    mnemonic_type: MnemonicType,
+    language: Language,
+) -> Result<Mnemonic, DepositError> {
+    let mut bytes = vec![0u8; mnemonic_type.entropy_bits() / 8];
 
-    getrandom::getrandom(&mut bytes).expect("Failed to generate seed using getrandom(2)");
+    getrandom::getrandom(&mut bytes).map_err(|e| {
+        DepositError::KeyDerivationError(format!("Failed to generate seed using getrandom(2): {e}"))
+    })?;
 
-    Mnemonic::from_entropy(bytes.as_slice(), Language::English)
-        .expect("Failed to generate mnemonic")
+    Mnemonic::from_entropy(bytes.as_slice(), language)
+        .map_err(|e| DepositError::KeyDerivationError(format!("Failed to generate mnemonic: {e}")))
 }
 
-pub(crate) fn get_eth2_seed(existing_mnemonic: Option<&[u8]>) -> (Bip39Seed, String) {
+/// Derive BIP-39 seed from existing or newly generated mnemonic,
+/// protected by optional BIP-39 passphrase (the "25th word").
+///
+/// Empty passphrase is the same as no passphrase at all, per BIP-39.
+pub(crate) fn get_eth2_seed(
+    existing_mnemonic: Option<&[u8]>,
+    passphrase: Option<&str>,
+) -> Result<(Bip39Seed, Zeroizing<String>), DepositError> {
     let mnemonic = match existing_mnemonic {
         Some(found_mnemonic) => {
-            let phrase = std::str::from_utf8(found_mnemonic).unwrap().to_string();
-            Mnemonic::from_phrase(phrase.as_str(), Language::English)
-                .expect("Invalid phrase passed")
+            let phrase = std::str::from_utf8(found_mnemonic).map_err(|_| {
+                DepositError::InvalidMnemonic("Invalid phrase passed: not UTF-8".to_string())
+            })?;
+            mnemonic_from_phrase(phrase)?
         }
-        None => create_new_seed(),
+        None => create_new_seed(MnemonicType::Words24, Language::English)?,
     };
-    (Bip39Seed::new(&mnemonic, ""), mnemonic.into_phrase())
+    Ok((
+        Bip39Seed::new(&mnemonic, passphrase.unwrap_or("")),
+        Zeroizing::new(mnemonic.into_phrase()),
+    ))
+}
+
+/// Parse mnemonic phrase of any length allowed by BIP-39,
+/// detecting wordlist language from the words used
+/// among the languages of [`MnemonicLanguage`].
+pub(crate) fn mnemonic_from_phrase(phrase: &str) -> Result<Mnemonic, DepositError> {
+    let mut error = None;
+    for language in MNEMONIC_LANGUAGES {
+        match Mnemonic::from_phrase(phrase, language) {
+            Ok(mnemonic) => return Ok(mnemonic),
+            // Words are not found in wordlists of other languages,
+            // so keep more specific error like invalid checksum if any
+            Err(e) => {
+                if error.is_none()
+                    || !matches!(e.downcast_ref::<ErrorKind>(), Some(ErrorKind::InvalidWord))
+                {
+                    error = Some(e);
+                }
+            }
+        }
+    }
+    Err(DepositError::InvalidMnemonic(format!(
+        "Invalid phrase passed: {}",
+        error.map(|e| e.to_string()).unwrap_or_default()
+    )))
 }
 
 #[cfg(test)]
@@ -41,13 +133,99 @@ mod tests {
 
     #[test]
     fn it_creates_seed_with_new_mnemonic() {
-        let (seed, _) = get_eth2_seed(None);
+        let (seed, _) = get_eth2_seed(None, None).unwrap();
         assert_ne!(PHRASE_SEED, hex::encode(seed.as_bytes()));
     }
 
+    #[test]
+    fn it_rejects_invalid_mnemonic() {
+        assert!(matches!(
+            get_eth2_seed(Some("entire habit bottom".as_bytes()), None),
+            Err(DepositError::InvalidMnemonic(_))
+        ));
+    }
+
     #[test]
     fn it_creates_seed_with_existing_mnemonic() {
-        let (seed, _) = get_eth2_seed(Some(PHRASE.as_bytes()));
+        let (seed, _) = get_eth2_seed(Some(PHRASE.as_bytes()), None).unwrap();
         assert_eq!(PHRASE_SEED, hex::encode(seed.as_bytes()));
     }
+
+    #[test]
+    fn it_creates_seed_with_passphrase() {
+        // Test vector from https://github.com/trezor/python-mnemonic/blob/master/vectors.json
+        let (seed, _) = get_eth2_seed(
+            Some("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".as_bytes()),
+            Some("TREZOR"),
+        )
+        .unwrap();
+        assert_eq!(
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+            hex::encode(seed.as_bytes())
+        );
+
+        // Empty passphrase is the same as no passphrase
+        let (seed, _) = get_eth2_seed(Some(PHRASE.as_bytes()), Some("")).unwrap();
+        assert_eq!(PHRASE_SEED, hex::encode(seed.as_bytes()));
+    }
+
+    #[test]
+    fn it_detects_mnemonic_language() {
+        // Phrase typed in composed form, while wordlist is in decomposed one
+        let (seed, _) = get_eth2_seed(
+            Some(
+                "ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco abierto"
+                    .as_bytes(),
+            ),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            "fdfe9b7c7a5e5079bb36d6381838867a34358db0a0307d060adbaf5edadb08b0a87c06ede1a96afd8566ef499792ffcbd37f43f6f554fa344138660eacdefcf8",
+            hex::encode(seed.as_bytes())
+        );
+
+        let (seed, _) = get_eth2_seed(
+            Some("壤 对 据 人 三 谈 我 表 壤 对 据 人 三 谈 我 表 壤 民".as_bytes()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            "eecd9563cf655e50ed42f188fabcde60647301f7d8558d0adc381934e31f3f18b4b6461f8200a7cee8d03b0546b498329845ad936be7b0f8d66089401990a7ea",
+            hex::encode(seed.as_bytes())
+        );
+
+        let (seed, _) = get_eth2_seed(
+            Some("われる われる われる われる われる われる われる われる われる われる われる ろんぶん".as_bytes()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            "c7b7621030ba6a424b2f53bbeda9415d1194d4d8beb073d09a5ec5fdbd77626e2a0e25bc82d5f2c20072e70000bb085c666255836ceecb92499ce4630b0a258b",
+            hex::encode(seed.as_bytes())
+        );
+    }
+
+    #[test]
+    fn it_reports_invalid_checksum_for_non_english_mnemonic() {
+        let result = mnemonic_from_phrase(
+            "ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco",
+        );
+        assert_eq!(
+            Some(DepositError::InvalidMnemonic(
+                "Invalid phrase passed: invalid checksum".to_string()
+            )),
+            result.err()
+        );
+    }
+
+    #[test]
+    fn it_creates_new_mnemonic_with_language_and_word_count() {
+        let mnemonic = create_new_seed(MnemonicType::Words12, Language::Spanish).unwrap();
+        assert_eq!(12, mnemonic.phrase().split_whitespace().count());
+
+        let parsed = mnemonic_from_phrase(mnemonic.phrase()).unwrap();
+        assert_eq!(Language::Spanish, parsed.language());
+        assert_eq!(mnemonic.entropy(), parsed.entropy());
+    }
 }
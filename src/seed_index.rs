@@ -0,0 +1,229 @@
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use types::{Keypair, PublicKeyBytes};
+
+use crate::key_material::{derive_keypair, master_key, seed_to_key_material, DerivationPaths};
+use crate::seed::get_eth2_seed;
+use crate::utils::withdrawal_creds_from_pk;
+use crate::DepositError;
+
+/// Type of the key matching public key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeedKeyType {
+    Voting,
+    Withdrawal,
+}
+
+/// Key derived from the seed, matching one of public keys searched for.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeedIndexMatch {
+    pub pubkey: String,
+    pub key_type: SeedKeyType,
+    pub validator_seed_index: u32,
+    pub path: String,
+    /// BLS (0x00) withdrawal credentials of withdrawal key at the same seed index
+    pub withdrawal_credentials: String,
+}
+
+/// Derive public keys of `num_validators` validators from the mnemonic,
+/// starting from `validator_start_index`.
+pub fn validator_pubkeys(
+    mnemonic_phrase: &[u8],
+    passphrase: Option<&str>,
+    validator_start_index: u32,
+    num_validators: u32,
+    derivation_paths: Option<&DerivationPaths>,
+) -> Result<Vec<PublicKeyBytes>, DepositError> {
+    let (seed, _) = get_eth2_seed(Some(mnemonic_phrase), passphrase)?;
+    let derivation_paths = derivation_paths.cloned().unwrap_or_default();
+    derivation_paths.check(num_validators as usize, false)?;
+
+    Ok(seed_to_key_material(
+        &seed,
+        num_validators,
+        validator_start_index,
+        None,
+        false,
+        None,
+        &derivation_paths,
+    )?
+    .into_iter()
+    .map(|key_material| key_material.keypair.pk.into())
+    .collect())
+}
+
+/// Search `num_indices` validator seed indices, starting from `validator_start_index`,
+/// for voting keys matching any of given public keys, and for withdrawal keys
+/// as well if `match_withdrawal` is set.
+///
+/// All public keys are matched in one pass over the seed, which stops as soon
+/// as every public key is found. Returned matches follow the order of public keys,
+/// and public keys not found in the range are left out.
+pub fn find_seed_indices(
+    mnemonic_phrase: &[u8],
+    passphrase: Option<&str>,
+    pubkeys: &[PublicKeyBytes],
+    validator_start_index: u32,
+    num_indices: u32,
+    match_withdrawal: bool,
+    derivation_paths: Option<&DerivationPaths>,
+) -> Result<Vec<SeedIndexMatch>, DepositError> {
+    let end_index = validator_start_index
+        .checked_add(num_indices)
+        .ok_or_else(|| DepositError::InvalidInput("Seed index range is too large".to_string()))?;
+    let (seed, _) = get_eth2_seed(Some(mnemonic_phrase), passphrase)?;
+    let derivation_paths = derivation_paths.cloned().unwrap_or_default();
+    derivation_paths.check(num_indices as usize, match_withdrawal)?;
+    let voting_path = derivation_paths.voting();
+    let withdrawal_path = derivation_paths.withdrawal();
+    let master = master_key(&seed)?;
+
+    let mut matches: Vec<Option<SeedIndexMatch>> = vec![None; pubkeys.len()];
+    let mut next_index = validator_start_index;
+    while next_index < end_index && matches.iter().any(Option::is_none) {
+        // Derive a chunk of indices in parallel, so that search stops soon after match
+        let n = (rayon::current_num_threads() as u32).min(end_index - next_index);
+        let keypairs = (next_index..next_index + n)
+            .into_par_iter()
+            .map(|idx| {
+                let (_, voting_keypair) = derive_keypair(&master, &voting_path, idx)?;
+                let withdrawal_keypair = if match_withdrawal {
+                    Some(derive_keypair(&master, &withdrawal_path, idx)?.1)
+                } else {
+                    None
+                };
+                Ok((idx, voting_keypair, withdrawal_keypair))
+            })
+            .collect::<Result<Vec<_>, DepositError>>()?;
+
+        for (idx, voting_keypair, withdrawal_keypair) in keypairs {
+            let voting_pubkey = PublicKeyBytes::from(voting_keypair.pk.clone());
+            let withdrawal_pubkey = withdrawal_keypair
+                .as_ref()
+                .map(|keypair| PublicKeyBytes::from(keypair.pk.clone()));
+            for (pubkey, found) in pubkeys.iter().zip(matches.iter_mut()) {
+                if found.is_some() {
+                    continue;
+                }
+                let (key_type, keypair, path) = if *pubkey == voting_pubkey {
+                    (SeedKeyType::Voting, &voting_keypair, voting_path.path(idx))
+                } else if withdrawal_pubkey.as_ref() == Some(pubkey) {
+                    (
+                        SeedKeyType::Withdrawal,
+                        withdrawal_keypair.as_ref().unwrap(),
+                        withdrawal_path.path(idx),
+                    )
+                } else {
+                    continue;
+                };
+                let withdrawal_credentials = match &withdrawal_keypair {
+                    Some(withdrawal_keypair) => withdrawal_credentials(withdrawal_keypair),
+                    None => {
+                        withdrawal_credentials(&derive_keypair(&master, &withdrawal_path, idx)?.1)
+                    }
+                };
+                *found = Some(SeedIndexMatch {
+                    pubkey: keypair.pk.as_hex_string(),
+                    key_type,
+                    validator_seed_index: idx,
+                    path,
+                    withdrawal_credentials,
+                });
+            }
+        }
+        next_index += n;
+    }
+    Ok(matches.into_iter().flatten().collect())
+}
+
+fn withdrawal_credentials(withdrawal_keypair: &Keypair) -> String {
+    format!(
+        "0x{}",
+        withdrawal_creds_from_pk(&withdrawal_keypair.pk.clone().into())
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+    use types::PublicKeyBytes;
+
+    use super::{find_seed_indices, SeedKeyType};
+    use crate::key_material::{seed_to_key_material, DerivationPaths};
+    use crate::seed::get_eth2_seed;
+
+    const PHRASE: &str = "entire habit bottom mention spoil clown finger wheat motion fox axis mechanic country make garment bar blind stadium sugar water scissors canyon often ketchup";
+
+    #[test]
+    fn test_find_seed_indices() {
+        let (seed, _) = get_eth2_seed(Some(PHRASE.as_bytes()), None).unwrap();
+        let key_material =
+            seed_to_key_material(&seed, 8, 0, None, true, None, &DerivationPaths::default())
+                .unwrap();
+        let voting_pubkey = |idx: usize| PublicKeyBytes::from(key_material[idx].keypair.pk.clone());
+        let withdrawal_pubkey = |idx: usize| {
+            PublicKeyBytes::from(
+                key_material[idx]
+                    .withdrawal_keypair
+                    .as_ref()
+                    .unwrap()
+                    .pk
+                    .clone(),
+            )
+        };
+        let unknown = PublicKeyBytes::from(
+            seed_to_key_material(
+                &seed,
+                1,
+                100,
+                None,
+                false,
+                None,
+                &DerivationPaths::default(),
+            )
+            .unwrap()[0]
+                .keypair
+                .pk
+                .clone(),
+        );
+        let pubkeys = vec![
+            voting_pubkey(6),
+            unknown,
+            withdrawal_pubkey(2),
+            voting_pubkey(1),
+        ];
+
+        // Withdrawal keys are only matched when asked for
+        let matches =
+            find_seed_indices(PHRASE.as_bytes(), None, &pubkeys, 0, 8, false, None).unwrap();
+        assert_eq!(
+            vec![(6, SeedKeyType::Voting), (1, SeedKeyType::Voting)],
+            matches
+                .iter()
+                .map(|m| (m.validator_seed_index, m.key_type.clone()))
+                .collect::<Vec<_>>()
+        );
+
+        let matches =
+            find_seed_indices(PHRASE.as_bytes(), None, &pubkeys, 1, 7, true, None).unwrap();
+        assert_eq!(3, matches.len());
+        assert_eq!(6, matches[0].validator_seed_index);
+        assert_eq!("m/12381/3600/6/0/0", matches[0].path);
+        assert_eq!(2, matches[1].validator_seed_index);
+        assert_eq!(SeedKeyType::Withdrawal, matches[1].key_type);
+        assert_eq!("m/12381/3600/2/0", matches[1].path);
+        assert_eq!(1, matches[2].validator_seed_index);
+        assert_eq!(
+            key_material[1].keypair.pk.as_hex_string(),
+            matches[2].pubkey
+        );
+        assert_eq!(
+            format!(
+                "0x{}",
+                crate::utils::withdrawal_creds_from_pk(&withdrawal_pubkey(1))
+            ),
+            matches[2].withdrawal_credentials
+        );
+    }
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// Unsigned execution layer transaction calling a contract, in the form
+/// of JSON-RPC `eth_sendTransaction` parameters, to be signed and sent
+/// by the owner of the sender address, e.g. of the withdrawal address.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionTemplate {
+    pub to: String,
+    /// Value in wei, as hex quantity
+    pub value: String,
+    pub data: String,
+}
+
+impl TransactionTemplate {
+    pub fn new(to: &str, value_wei: u128, data: &[u8]) -> Self {
+        Self {
+            to: to.to_string(),
+            value: format!("0x{:x}", value_wei),
+            data: format!("0x{}", hex::encode(data)),
+        }
+    }
+}
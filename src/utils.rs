@@ -2,31 +2,65 @@ use eth2_keystore::json_keystore::{HexBytes, Kdf, Pbkdf2, Prf, Scrypt};
 use eth2_keystore::{DKLEN, SALT_SIZE};
 use regex::Regex;
 use ssz::Encode;
-use types::{Hash256, PublicKeyBytes};
+use std::str::FromStr;
+use types::{Hash256, PublicKey, PublicKeyBytes};
+
+/// PBKDF2 iteration count recommended by EIP-2335,
+/// used by default and as a lower bound for custom parameters.
+pub const PBKDF2_C: u32 = 262_144;
+/// Scrypt parameters recommended by EIP-2335,
+/// used by default and as a lower bound for custom parameters.
+pub const SCRYPT_N: u32 = 262_144;
+pub const SCRYPT_R: u32 = 8;
+pub const SCRYPT_P: u32 = 1;
 
 pub(crate) fn pbkdf2() -> Kdf {
+    pbkdf2_with(PBKDF2_C)
+}
+
+pub(crate) fn pbkdf2_with(c: u32) -> Kdf {
     let mut salt = vec![0u8; SALT_SIZE];
     getrandom::getrandom(&mut salt).expect("Failed to generate pbkdf salt using getrandom(2)");
     Kdf::Pbkdf2(Pbkdf2 {
         dklen: DKLEN,
-        c: 262_144,
+        c,
         prf: Prf::HmacSha256,
         salt: HexBytes::from(salt),
     })
 }
 
 pub(crate) fn scrypt() -> Kdf {
+    scrypt_with(SCRYPT_N, SCRYPT_R, SCRYPT_P)
+}
+
+pub(crate) fn scrypt_with(n: u32, r: u32, p: u32) -> Kdf {
     let mut salt = vec![0u8; SALT_SIZE];
     getrandom::getrandom(&mut salt).expect("Failed to generate scrypt salt using getrandom(2)");
     Kdf::Scrypt(Scrypt {
         dklen: DKLEN,
-        n: 262144,
-        p: 1,
-        r: 8,
+        n,
+        p,
+        r,
         salt: salt.into(),
     })
 }
 
+/// Set number of threads used to derive keys and encrypt keystores in parallel.
+///
+/// All available cores are used by default. Thread pool can only
+/// be configured once per process, before keys are generated.
+pub fn init_thread_pool(threads: Option<usize>) -> Result<(), crate::DepositError> {
+    if let Some(threads) = threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .map_err(|e| {
+                crate::DepositError::InvalidInput(format!("Can not set number of threads: {}", e))
+            })?;
+    }
+    Ok(())
+}
+
 /// Returns the withdrawal credentials for a given BLS public key.
 ///
 /// Used for submitting deposits to the Eth1 deposit contract.
@@ -47,11 +81,29 @@ pub fn withdrawal_creds_from_pk(withdrawal_pk: &PublicKeyBytes) -> String {
     hex::encode(credentials_hash.as_bytes())
 }
 
+/// Parse validator public key in hex form, with or without 0x prefix,
+/// rejecting keys which are not valid BLS points
+pub(crate) fn parse_validator_pubkey(pubkey: &str) -> Result<PublicKeyBytes, crate::DepositError> {
+    let pubkey = pubkey.trim();
+    let pubkey_bytes = PublicKeyBytes::from_str(&format!(
+        "0x{}",
+        pubkey.strip_prefix("0x").unwrap_or(pubkey)
+    ))
+    .map_err(|e| crate::DepositError::InvalidInput(format!("Invalid public key {pubkey}: {e}")))?;
+    PublicKey::deserialize(pubkey_bytes.as_serialized()).map_err(|e| {
+        crate::DepositError::InvalidInput(format!("Invalid public key {pubkey}: {e:?}"))
+    })?;
+    Ok(pubkey_bytes)
+}
+
 // Various regexes used for input validation
 lazy_static::lazy_static! {
     /// see format of execution address: https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/validator.md#eth1_address_withdrawal_prefix
     pub static ref EXECUTION_ADDR_REGEX: Regex = Regex::new(r"^(0x[a-fA-F0-9]{40})$").unwrap();
     pub static ref EXECUTION_CREDS_REGEX: Regex =
         Regex::new(r"^(0x01[0]{22}[a-fA-F0-9]{40})$").unwrap();
+    /// see format of compounding credentials: https://eips.ethereum.org/EIPS/eip-7251
+    pub static ref COMPOUNDING_CREDS_REGEX: Regex =
+        Regex::new(r"^(0x02[0]{22}[a-fA-F0-9]{40})$").unwrap();
     pub static ref BLS_CREDS_REGEX: Regex = Regex::new(r"^(0x00[a-fA-F0-9]{62})$").unwrap();
 }
@@ -1,7 +1,11 @@
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-use crate::deposit::{keystore_to_deposit, DepositError};
-use crate::key_material::{seed_to_key_material, KdfVariant, VotingKeyMaterial};
+use crate::deposit::{
+    deposit_chain_spec, keystore_to_deposit, validate_deposit_amount, DepositError,
+};
+use crate::key_material::{seed_to_key_material, DerivationPaths, KdfParams, VotingKeyMaterial};
 use crate::networks::SupportedNetworks;
 use crate::seed::get_eth2_seed;
 use crate::utils::get_withdrawal_credentials;
@@ -14,14 +18,21 @@ use types::{
     ChainSpec, DepositData, Hash256, Keypair, PublicKey, PublicKeyBytes, Signature, SignatureBytes,
     SignedRoot,
 };
+use zeroize::{Zeroize, Zeroizing};
 
 const ETH1_CREDENTIALS_PREFIX: &[u8] = &[
     48, 49, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48,
 ];
 const ETH2_CREDENTIALS_PREFIX: &[u8] = &[48, 48];
+const COMPOUNDING_CREDENTIALS_PREFIX: &[u8] = &[
+    48, 50, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48,
+];
 
+/// Validator keys derived from the mnemonic, or decrypted from existing keystores.
+///
+/// Mnemonic phrase and voting secrets are zeroized on drop.
 pub struct Validators {
-    mnemonic_phrase: String,
+    mnemonic_phrase: Option<Zeroizing<String>>,
     key_material: Vec<VotingKeyMaterial>,
 }
 
@@ -30,6 +41,53 @@ struct MnemonicExport {
     seed: String,
 }
 
+/// How validator keys are derived from the seed and encrypted into keystores.
+#[derive(Clone, Debug, Default)]
+pub struct KeyDerivationParams {
+    /// Derive withdrawal keys as well, for BLS (0x00) withdrawal credentials
+    pub derive_withdrawal: bool,
+    pub kdf: KdfParams,
+    pub derivation_paths: DerivationPaths,
+}
+
+/// Parameters of deposit data signed for every validator.
+#[derive(Clone, Debug)]
+pub struct DepositParams {
+    /// Well known network the deposits are targeting, `chain_spec_file` is used if not set
+    pub network: Option<SupportedNetworks>,
+    /// Execution address, execution, compounding or BLS withdrawal credentials,
+    /// credentials of the derived withdrawal key are used if not set
+    pub withdrawal_credentials: Option<String>,
+    /// Turn execution address passed as withdrawal credentials into
+    /// 0x02 compounding credentials (EIP-7251) instead of 0x01
+    pub compounding: bool,
+    pub deposit_amount_gwei: u64,
+    pub deposit_cli_version: String,
+    /// Path to custom chain config, when network is not set
+    pub chain_spec_file: Option<String>,
+}
+
+impl DepositParams {
+    /// Check chain, withdrawal credentials and deposit amount once,
+    /// before signing deposit data of any validator, returning the chain spec.
+    ///
+    /// Credentials of derived withdrawal keys are BLS (0x00) ones,
+    /// so only deposit amount limits are checked for them.
+    pub fn validate(&self) -> Result<ChainSpec, DepositError> {
+        let spec = deposit_chain_spec(self.network.clone(), self.chain_spec_file.clone())?;
+        let withdrawal_credentials = match &self.withdrawal_credentials {
+            Some(_) => set_withdrawal_credentials(
+                self.withdrawal_credentials.clone(),
+                None,
+                self.compounding,
+            )?,
+            None => vec![0; 32],
+        };
+        validate_deposit_amount(self.deposit_amount_gwei, &withdrawal_credentials, &spec)?;
+        Ok(spec)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
 pub struct DepositExport {
     pub pubkey: String,
@@ -44,16 +102,73 @@ pub struct DepositExport {
 }
 
 impl DepositExport {
+    /// Sign deposit data of a single validator with its voting key.
+    ///
+    /// Withdrawal credentials are set the same way as in `Validators::export`.
+    pub fn from_key_material(
+        key_material: &VotingKeyMaterial,
+        params: &DepositParams,
+    ) -> Result<Self, DepositError> {
+        let network_name = params
+            .network
+            .clone()
+            .map_or("privatenet".to_string(), |n| n.to_string());
+        let withdrawal_credentials = set_withdrawal_credentials(
+            params.withdrawal_credentials.clone(),
+            key_material.withdrawal_keypair.clone(),
+            params.compounding,
+        )?;
+
+        let public_key = key_material.keypair.pk.as_hex_string().replace("0x", "");
+        let (deposit, chain_spec) = keystore_to_deposit(
+            key_material,
+            withdrawal_credentials.as_ref(),
+            params.deposit_amount_gwei,
+            params.network.clone(),
+            params.chain_spec_file.clone(),
+        )?;
+
+        Ok(DepositExport {
+            pubkey: public_key,
+            withdrawal_credentials: hex::encode(deposit.withdrawal_credentials),
+            amount: deposit.amount,
+            signature: deposit
+                .signature
+                .to_string()
+                .as_str()
+                .strip_prefix("0x")
+                .unwrap()
+                .to_string(),
+            deposit_message_root: hex::encode(deposit.as_deposit_message().tree_hash_root()),
+            deposit_data_root: hex::encode(deposit.tree_hash_root()),
+            fork_version: hex::encode(chain_spec.genesis_fork_version),
+            network_name,
+            deposit_cli_version: params.deposit_cli_version.clone(),
+        })
+    }
+
     /*
         Checks whether a deposit is valid based on the staking deposit rules.
         https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/beacon-chain.md#deposits
     */
-    pub fn validate(self, spec: ChainSpec) {
+    pub fn validate(self, spec: ChainSpec) -> Result<(), DepositError> {
+        self.verify(&spec)
+    }
+
+    /// Same checks as `validate`, returning the first failed check
+    /// as an error, without consuming the deposit.
+    pub fn verify(&self, spec: &ChainSpec) -> Result<(), DepositError> {
+        let invalid = |reason: &str| DepositError::InvalidDepositData(reason.to_string());
+
         let pub_key = &self.pubkey;
-        assert_eq!(96, pub_key.len());
+        if pub_key.len() != 96 {
+            return Err(invalid("invalid public key length"));
+        }
 
         let withdrawal_credentials = &self.withdrawal_credentials;
-        assert_eq!(64, withdrawal_credentials.len());
+        if withdrawal_credentials.len() != 64 {
+            return Err(invalid("invalid withdrawal credentials length"));
+        }
 
         if !withdrawal_credentials
             .as_bytes()
@@ -61,24 +176,32 @@ impl DepositExport {
             && !withdrawal_credentials
                 .as_bytes()
                 .starts_with(ETH2_CREDENTIALS_PREFIX)
+            && !withdrawal_credentials
+                .as_bytes()
+                .starts_with(COMPOUNDING_CREDENTIALS_PREFIX)
         {
-            panic!("withdrawal address has unexpected prefix");
+            return Err(invalid("withdrawal credentials have unexpected prefix"));
         }
 
-        assert_eq!(32000000000, self.amount);
+        let withdrawal_credentials_bytes = hex::decode(withdrawal_credentials)
+            .map_err(|_| invalid("could not parse withdrawal credentials"))?;
+        validate_deposit_amount(self.amount, &withdrawal_credentials_bytes, spec)
+            .map_err(|e| invalid(&format!("invalid deposit amount: {e}")))?;
 
-        let pubkey =
-            PublicKey::from_str(&format!("0x{}", pub_key)).expect("could not parse public key");
+        let pubkey = PublicKey::from_str(&format!("0x{}", pub_key))
+            .map_err(|_| invalid("could not parse public key"))?;
         let pubkey_bytes = PublicKeyBytes::from_str(&format!("0x{}", pub_key))
-            .expect("could not parse public key");
+            .map_err(|_| invalid("could not parse public key"))?;
         let withdrawal_credentials = Hash256::from_str(withdrawal_credentials)
-            .expect("could not parse withdrawal credentials");
+            .map_err(|_| invalid("could not parse withdrawal credentials"))?;
         let signature = Signature::from_str(&format!("0x{}", self.signature))
-            .expect("could not parse signature");
+            .map_err(|_| invalid("could not parse signature"))?;
         let signature_bytes = SignatureBytes::from_str(&format!("0x{}", self.signature))
-            .expect("could not parse signature");
+            .map_err(|_| invalid("could not parse signature"))?;
+        let deposit_message_root = Hash256::from_str(&self.deposit_message_root)
+            .map_err(|_| invalid("could not parse deposit message root"))?;
         let deposit_data_root = Hash256::from_str(&self.deposit_data_root)
-            .expect("could not parse deposit message root");
+            .map_err(|_| invalid("could not parse deposit data root"))?;
 
         let deposit_data = DepositData {
             pubkey: pubkey_bytes,
@@ -87,29 +210,69 @@ impl DepositExport {
             signature: signature_bytes,
         };
 
-        let fork_version = hex::decode(self.fork_version).expect("could not wrap fork version");
-        let fork_version_from_chain = spec.genesis_fork_version.to_vec();
-        assert_eq!(fork_version, fork_version_from_chain); // should match the spec
-        assert_eq!(fork_version.len(), 4);
+        let fork_version =
+            hex::decode(&self.fork_version).map_err(|_| invalid("could not parse fork version"))?;
+        // should match the spec
+        if fork_version != spec.genesis_fork_version {
+            return Err(invalid("fork version does not match the chain"));
+        }
+
+        if deposit_message_root != deposit_data.as_deposit_message().tree_hash_root() {
+            return Err(invalid("deposit message root does not match"));
+        }
 
         let domain = spec.get_deposit_domain();
         let signing_root = deposit_data.as_deposit_message().signing_root(domain);
 
-        let is_valid = signature.verify(&pubkey, signing_root);
-        assert!(is_valid);
+        if !signature.verify(&pubkey, signing_root) {
+            return Err(invalid("invalid signature"));
+        }
 
-        assert_eq!(deposit_data_root, deposit_data.tree_hash_root());
+        if deposit_data_root != deposit_data.tree_hash_root() {
+            return Err(invalid("deposit data root does not match"));
+        }
+
+        Ok(())
     }
 }
 
+/// Exported keystores, deposit data and optionally secrets.
+///
+/// Private keys and mnemonic are zeroized on drop,
+/// and are omitted from serialized output if removed with `without_secrets`.
 #[derive(Serialize, Deserialize)]
 pub struct ValidatorExports {
     pub keystores: Vec<Keystore>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub private_keys: Vec<String>,
-    mnemonic: MnemonicExport,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mnemonic: Option<MnemonicExport>,
     pub deposit_data: Vec<DepositExport>,
 }
 
+impl ValidatorExports {
+    /// Remove private keys and mnemonic from the export,
+    /// leaving only keystores and deposit data.
+    pub fn without_secrets(mut self) -> Self {
+        self.private_keys.zeroize();
+        if let Some(mnemonic) = self.mnemonic.as_mut() {
+            mnemonic.seed.zeroize();
+        }
+        self.private_keys = vec![];
+        self.mnemonic = None;
+        self
+    }
+}
+
+impl Drop for ValidatorExports {
+    fn drop(&mut self) {
+        self.private_keys.zeroize();
+        if let Some(mnemonic) = self.mnemonic.as_mut() {
+            mnemonic.seed.zeroize();
+        }
+    }
+}
+
 impl TryInto<serde_json::Value> for ValidatorExports {
     type Error = DepositError;
 
@@ -128,71 +291,113 @@ impl Validators {
         password: Option<Vec<u8>>,
         num_validators: Option<u32>,
         validator_start_index: Option<u32>,
-        derive_withdrawal: bool,
-        kdf: Option<Kdf>,
-    ) -> Vec<VotingKeyMaterial> {
-        let mut key_material = vec![];
-        for voting_keystore in seed_to_key_material(
+        params: &KeyDerivationParams,
+    ) -> Result<Vec<VotingKeyMaterial>, DepositError> {
+        let num_validators = num_validators.unwrap_or(1);
+        params
+            .derivation_paths
+            .check(num_validators as usize, params.derive_withdrawal)?;
+        seed_to_key_material(
             seed,
-            num_validators.unwrap_or(1),
+            num_validators,
             validator_start_index.unwrap_or(0),
             password,
-            derive_withdrawal,
-            kdf,
-        ) {
-            key_material.push(voting_keystore);
-        }
-        key_material
+            params.derive_withdrawal,
+            Some(params.kdf.to_kdf()?),
+            &params.derivation_paths,
+        )
     }
 
     /// Initialize seed from mnemonic bytes
     pub fn new(
         mnemonic_phrase: Option<&[u8]>,
+        passphrase: Option<&str>,
         password: Option<Vec<u8>>,
         num_validators: Option<u32>,
         validator_start_index: Option<u32>,
-        derive_withdrawal: bool,
-        kdf: Option<KdfVariant>,
-    ) -> Self {
-        let (seed, phrase_string) = get_eth2_seed(mnemonic_phrase);
+        params: &KeyDerivationParams,
+    ) -> Result<Self, DepositError> {
+        let (seed, phrase_string) = get_eth2_seed(mnemonic_phrase, passphrase)?;
 
-        Self {
-            mnemonic_phrase: phrase_string,
+        Ok(Self {
+            mnemonic_phrase: Some(phrase_string),
             key_material: Validators::key_material_from_seed(
                 &seed,
                 password,
                 num_validators,
                 validator_start_index,
-                derive_withdrawal,
-                kdf.map(|k| k.into()),
-            ),
-        }
+                params,
+            )?,
+        })
     }
 
     /// Initialize seed from mnemonic object
     pub fn from_mnemonic(
         mnemonic: &Mnemonic,
+        passphrase: Option<&str>,
         password: Option<Vec<u8>>,
         num_validators: Option<u32>,
         validator_start_index: Option<u32>,
-        derive_withdrawal: bool,
-        kdf: Option<KdfVariant>,
-    ) -> Self {
-        let mnemonic_phrase = mnemonic.clone().into_phrase();
-        let (seed, _) = get_eth2_seed(Some(mnemonic.clone().into_phrase().as_bytes()));
-        Self {
-            mnemonic_phrase,
+        params: &KeyDerivationParams,
+    ) -> Result<Self, DepositError> {
+        let (seed, mnemonic_phrase) =
+            get_eth2_seed(Some(mnemonic.phrase().as_bytes()), passphrase)?;
+        Ok(Self {
+            mnemonic_phrase: Some(mnemonic_phrase),
             key_material: Validators::key_material_from_seed(
                 &seed,
                 password,
                 num_validators,
                 validator_start_index,
-                derive_withdrawal,
-                kdf.map(|k| k.into()),
-            ),
+                params,
+            )?,
+        })
+    }
+
+    /// Initialize from key material of existing keystores, e.g. to top up
+    /// already deposited validators. There is no mnemonic to export then,
+    /// private keys are not exported either, as keystores already hold them,
+    /// and withdrawal credentials have to be set explicitly.
+    pub fn from_key_material(key_material: Vec<VotingKeyMaterial>) -> Self {
+        Self {
+            mnemonic_phrase: None,
+            key_material,
         }
     }
 
+    /// Derive validators from mnemonic bytes lazily, for large number of validators
+    /// which should not be held in memory at once.
+    ///
+    /// Returned stream yields key material of every validator along with its index.
+    pub fn stream(
+        mnemonic_phrase: &[u8],
+        passphrase: Option<&str>,
+        password: Option<Vec<u8>>,
+        num_validators: u32,
+        validator_start_index: u32,
+        params: &KeyDerivationParams,
+    ) -> Result<ValidatorStream, DepositError> {
+        let end_index = validator_start_index
+            .checked_add(num_validators)
+            .ok_or_else(|| {
+                DepositError::InvalidInput("Validator index range is too large".to_string())
+            })?;
+        let (seed, _) = get_eth2_seed(Some(mnemonic_phrase), passphrase)?;
+        params
+            .derivation_paths
+            .check(num_validators as usize, params.derive_withdrawal)?;
+        Ok(ValidatorStream {
+            seed,
+            password: password.map(Zeroizing::new),
+            kdf: Some(params.kdf.to_kdf()?),
+            derive_withdrawal: params.derive_withdrawal,
+            derivation_paths: params.derivation_paths.clone(),
+            next_index: validator_start_index,
+            end_index,
+            chunk: VecDeque::new(),
+        })
+    }
+
     /// Export keystores and deposit data in JSON format,
     /// compatible with eth2.0-deposit-cli.
     ///
@@ -225,85 +430,221 @@ impl Validators {
     ///         { ....
     ///     ]
     /// }
-    pub fn export(
-        &self,
-        network: Option<SupportedNetworks>,
-        withdrawal_credentials: Option<String>,
-        deposit_amount_gwei: u64,
-        deposit_cli_version: String,
-        chain_spec_file: Option<String>,
-    ) -> Result<ValidatorExports, DepositError> {
+    pub fn export(&self, params: &DepositParams) -> Result<ValidatorExports, DepositError> {
         let mut keystores: Vec<Keystore> = vec![];
         let mut private_keys: Vec<String> = vec![];
         let mut deposit_data: Vec<DepositExport> = vec![];
-        let network_name = network
-            .clone()
-            .map_or("privatenet".to_string(), |n| n.to_string());
 
         for key_with_store in self.key_material.iter() {
             if let Some(ks) = key_with_store.keystore.clone() {
                 keystores.push(ks);
             };
 
-            private_keys.push(hex::encode(key_with_store.voting_secret.as_bytes()));
-
-            let withdrawal_credentials = set_withdrawal_credentials(
-                withdrawal_credentials.clone(),
-                key_with_store.withdrawal_keypair.clone(),
-            )?;
-
-            let public_key = key_with_store.keypair.pk.as_hex_string().replace("0x", "");
-            let (deposit, chain_spec) = keystore_to_deposit(
-                &(*key_with_store).clone(),
-                withdrawal_credentials.as_ref(),
-                deposit_amount_gwei,
-                network.clone(),
-                chain_spec_file.clone(),
-            )?;
-
-            deposit_data.push(DepositExport {
-                pubkey: public_key,
-                withdrawal_credentials: hex::encode(deposit.withdrawal_credentials),
-                amount: deposit.amount,
-                signature: deposit
-                    .signature
-                    .to_string()
-                    .as_str()
-                    .strip_prefix("0x")
-                    .unwrap()
-                    .to_string(),
-                deposit_message_root: hex::encode(deposit.as_deposit_message().tree_hash_root()),
-                deposit_data_root: hex::encode(deposit.tree_hash_root()),
-                fork_version: hex::encode(chain_spec.genesis_fork_version),
-                network_name: network_name.clone(),
-                deposit_cli_version: deposit_cli_version.clone(),
-            })
+            // Keys of existing keystores are only exported encrypted
+            if self.mnemonic_phrase.is_some() {
+                private_keys.push(hex::encode(key_with_store.voting_secret.as_bytes()));
+            }
+
+            deposit_data.push(DepositExport::from_key_material(key_with_store, params)?);
         }
         let exports = ValidatorExports {
             keystores,
             private_keys,
-            mnemonic: MnemonicExport {
-                seed: self.mnemonic_phrase.clone(),
-            },
+            mnemonic: self.mnemonic_phrase.as_ref().map(|phrase| MnemonicExport {
+                seed: phrase.to_string(),
+            }),
             deposit_data,
         };
         Ok(exports)
     }
+
+    /// Export keystores and deposit data into the given directory,
+    /// using the `validator_keys` folder layout of staking-deposit-cli.
+    ///
+    /// Sample folder contents:
+    ///     deposit_data-1720014619.json
+    ///     keystore-m_12381_3600_0_0_0-1720014619.json
+    ///     keystore-m_12381_3600_1_0_0-1720014619.json
+    ///
+    /// Existing keystores, which may come from other tools without derivation path,
+    /// are named after their public key instead, e.g. `keystore-8844cebb...-1720014619.json`.
+    ///
+    /// Private keys and mnemonic are never written, so keystore password
+    /// must be set to produce the keystore files. Returns paths of the
+    /// files written.
+    pub fn export_to_dir(
+        &self,
+        output_dir: &Path,
+        params: &DepositParams,
+    ) -> Result<Vec<PathBuf>, DepositError> {
+        if self.key_material.iter().any(|km| km.keystore.is_none()) {
+            return Err(DepositError::InvalidKeystore(
+                "Keystore password must be set to export validator keys folder".to_string(),
+            ));
+        }
+
+        let exports = self.export(params)?.without_secrets();
+
+        let timestamp = unix_timestamp()?;
+        let file_names = exports
+            .keystores
+            .iter()
+            .map(|keystore| match self.mnemonic_phrase {
+                Some(_) => keystore_file_name(keystore, timestamp),
+                None => Ok(format!("keystore-{}-{timestamp}.json", keystore.pubkey())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // Check names upfront, so that no files are left behind on collision
+        if file_names.iter().collect::<HashSet<_>>().len() != file_names.len() {
+            return Err(DepositError::InvalidKeystore(
+                "Keystores to export have the same file name, \
+                the same keystore may be passed more than once"
+                    .to_string(),
+            ));
+        }
+
+        std::fs::create_dir_all(output_dir).map_err(|e| {
+            DepositError::FilesystemError(format!("Can not create {}: {e}", output_dir.display()))
+        })?;
+
+        let mut written = vec![];
+        for (keystore, file_name) in exports.keystores.iter().zip(file_names) {
+            let file_path = output_dir.join(file_name);
+            write_json_file(&file_path, keystore)?;
+            written.push(file_path);
+        }
+
+        let file_path = output_dir.join(format!("deposit_data-{timestamp}.json"));
+        write_json_file(&file_path, &exports.deposit_data)?;
+        written.push(file_path);
+
+        Ok(written)
+    }
+
+    /// Mnemonic phrase the validators were generated from, if any
+    pub fn mnemonic_phrase(&self) -> Option<&str> {
+        self.mnemonic_phrase.as_deref().map(String::as_str)
+    }
+}
+
+/// Validator keys derived from the mnemonic one chunk at a time.
+///
+/// Every chunk is derived and encrypted in parallel, and has as many validators
+/// as there are threads in the current rayon thread pool, so that only a few
+/// validators are held in memory at once. Validators are yielded in the order
+/// of their indices, and the stream ends after the first error.
+pub struct ValidatorStream {
+    seed: Bip39Seed,
+    password: Option<Zeroizing<Vec<u8>>>,
+    kdf: Option<Kdf>,
+    derive_withdrawal: bool,
+    derivation_paths: DerivationPaths,
+    next_index: u32,
+    end_index: u32,
+    chunk: VecDeque<(u32, VotingKeyMaterial)>,
+}
+
+impl Iterator for ValidatorStream {
+    type Item = Result<(u32, VotingKeyMaterial), DepositError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.chunk.is_empty() && self.next_index < self.end_index {
+            let n = (rayon::current_num_threads() as u32).min(self.end_index - self.next_index);
+            let key_material = seed_to_key_material(
+                &self.seed,
+                n,
+                self.next_index,
+                self.password.as_ref().map(|p| p.to_vec()),
+                self.derive_withdrawal,
+                self.kdf.clone(),
+                &self.derivation_paths,
+            );
+            match key_material {
+                Ok(key_material) => {
+                    self.chunk
+                        .extend((self.next_index..self.next_index + n).zip(key_material));
+                    self.next_index += n;
+                }
+                Err(e) => {
+                    self.next_index = self.end_index;
+                    return Some(Err(e));
+                }
+            }
+        }
+        self.chunk.pop_front().map(Ok)
+    }
+}
+
+/// Keystore file name in the `validator_keys` folder layout of staking-deposit-cli,
+/// e.g. `keystore-m_12381_3600_0_0_0-1720014619.json` for EIP-2334 path m/12381/3600/0/0/0.
+pub(crate) fn keystore_file_name(
+    keystore: &Keystore,
+    timestamp: u64,
+) -> Result<String, DepositError> {
+    let keystore_path = keystore.path().ok_or_else(|| {
+        DepositError::InvalidKeystore("Keystore has no derivation path".to_string())
+    })?;
+    Ok(format!(
+        "keystore-{}-{timestamp}.json",
+        keystore_path.replace('/', "_")
+    ))
+}
+
+/// Current time as UNIX timestamp, used in names of written files.
+pub(crate) fn unix_timestamp() -> Result<u64, DepositError> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| DepositError::FilesystemError(format!("Invalid system time: {e}")))?
+        .as_secs())
+}
+
+/// Write JSON value into a new file, readable only for owner and group,
+/// the same way staking-deposit-cli does.
+pub(crate) fn write_json_file<T: Serialize>(path: &Path, value: &T) -> Result<(), DepositError> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o440);
+    }
+    let file = options.open(path).map_err(|e| {
+        DepositError::FilesystemError(format!("Can not create {}: {e}", path.display()))
+    })?;
+    serde_json::to_writer(file, value).map_err(|_| {
+        DepositError::SerializationError(format!("Failed to serialize {}", path.display()))
+    })
 }
 
 fn set_withdrawal_credentials(
     existing_withdrawal_credentials: Option<String>,
     derived_withdrawal_credentials: Option<Keypair>,
+    compounding: bool,
 ) -> Result<Vec<u8>, DepositError> {
+    if compounding
+        && !existing_withdrawal_credentials
+            .as_ref()
+            .is_some_and(|creds| crate::utils::EXECUTION_ADDR_REGEX.is_match(creds.as_str()))
+    {
+        return Err(DepositError::InvalidWithdrawalCredentials(
+            "Compounding withdrawal credentials require an execution address".to_string(),
+        ));
+    }
+
     let withdrawal_credentials = match existing_withdrawal_credentials {
         Some(creds) => {
             let withdrawal_credentials = if crate::utils::EXECUTION_ADDR_REGEX
                 .is_match(creds.as_str())
             {
-                let mut formatted_creds = ETH1_CREDENTIALS_PREFIX.to_vec();
+                let mut formatted_creds = if compounding {
+                    COMPOUNDING_CREDENTIALS_PREFIX.to_vec()
+                } else {
+                    ETH1_CREDENTIALS_PREFIX.to_vec()
+                };
                 formatted_creds.extend_from_slice(&creds.as_bytes()[2..]);
                 formatted_creds
             } else if crate::utils::EXECUTION_CREDS_REGEX.is_match(creds.as_str())
+                || crate::utils::COMPOUNDING_CREDS_REGEX.is_match(creds.as_str())
                 || crate::utils::BLS_CREDS_REGEX.is_match(creds.as_str())
             {
                 // see format of execution & bls credentials https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/validator.md#bls_withdrawal_prefix
@@ -311,11 +652,15 @@ fn set_withdrawal_credentials(
                 formatted_creds
             } else {
                 return Err(DepositError::InvalidWithdrawalCredentials(
-                    "Invalid withdrawal address: Please pass in a valid execution address, execution or BLS credentials with the correct format".to_string(),
+                    "Invalid withdrawal address: Please pass in a valid execution address, execution, compounding or BLS credentials with the correct format".to_string(),
                 ));
             };
 
-            hex::decode(withdrawal_credentials).expect("could not decode hex address ")
+            hex::decode(withdrawal_credentials).map_err(|_| {
+                DepositError::InvalidWithdrawalCredentials(
+                    "could not decode hex address".to_string(),
+                )
+            })?
         }
         None => {
             let withdrawal_pk = match derived_withdrawal_credentials {
@@ -341,46 +686,80 @@ mod test {
         key_material::KdfVariant,
         networks::SupportedNetworks,
         validators::{set_withdrawal_credentials, ValidatorExports},
-        DepositExport,
+        DepositError, DepositExport,
     };
 
-    use super::Validators;
+    use super::{DepositParams, KeyDerivationParams, Validators};
     use test_log::test;
     use types::Keypair;
 
     const PHRASE: &str = "entire habit bottom mention spoil clown finger wheat motion fox axis mechanic country make garment bar blind stadium sugar water scissors canyon often ketchup";
 
+    fn mainnet_params(withdrawal_credentials: Option<&str>) -> DepositParams {
+        DepositParams {
+            network: Some(SupportedNetworks::Mainnet),
+            withdrawal_credentials: withdrawal_credentials.map(str::to_string),
+            compounding: false,
+            deposit_amount_gwei: 32_000_000_000,
+            deposit_cli_version: "2.7.0".to_string(),
+            chain_spec_file: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_deposit_params() {
+        assert!(mainnet_params(None).validate().is_ok());
+        assert!(
+            mainnet_params(Some("0xD4BB555d3B0D7fF17c606161B44E372689C14F4B"))
+                .validate()
+                .is_ok()
+        );
+        assert!(matches!(
+            mainnet_params(Some("0x01D4BB555d3B0D7fF17c606161B44E372689C14F4B")).validate(),
+            Err(DepositError::InvalidWithdrawalCredentials(_))
+        ));
+
+        let mut params = mainnet_params(Some("0xD4BB555d3B0D7fF17c606161B44E372689C14F4B"));
+        params.deposit_amount_gwei = 33_000_000_000;
+        assert!(matches!(
+            params.validate(),
+            Err(DepositError::InvalidDepositAmount(_))
+        ));
+        params.compounding = true;
+        assert!(params.validate().is_ok());
+
+        let mut params = mainnet_params(None);
+        params.network = None;
+        assert!(matches!(
+            params.validate(),
+            Err(DepositError::NoCustomConfig(_))
+        ));
+    }
+
     #[test]
     fn test_export_validators_existing_mnemonic() {
         fn validators_with_mnemonic() -> Validators {
             Validators::new(
                 Some(PHRASE.as_bytes()),
+                None,
                 Some("testtest".as_bytes().to_vec()),
                 Some(1),
                 Some(0),
-                false,
-                None,
+                &KeyDerivationParams::default(),
             )
+            .unwrap()
         }
 
         let exports = vec![
             validators_with_mnemonic()
-                .export(
-                    Some(SupportedNetworks::Mainnet),
-                    Some("0x0000000000000000000000000000000000000001".to_string()),
-                    32_000_000_000,
-                    "2.7.0".to_string(),
-                    None,
-                )
+                .export(&mainnet_params(Some(
+                    "0x0000000000000000000000000000000000000001",
+                )))
                 .unwrap(),
             validators_with_mnemonic()
-                .export(
-                    Some(SupportedNetworks::Mainnet),
-                    Some("0x0000000000000000000000000000000000000001".to_string()),
-                    32_000_000_000,
-                    "2.7.0".to_string(),
-                    None,
-                )
+                .export(&mainnet_params(Some(
+                    "0x0000000000000000000000000000000000000001",
+                )))
                 .unwrap(),
         ];
 
@@ -404,7 +783,7 @@ mod test {
                 "3f3e0a69a6a66aeaec606a2ccb47c703afb2e8ae64f70a1650c03343b06e8f0c",
                 export.private_keys[0]
             );
-            assert_eq!("entire habit bottom mention spoil clown finger wheat motion fox axis mechanic country make garment bar blind stadium sugar water scissors canyon often ketchup", export.mnemonic.seed);
+            assert_eq!("entire habit bottom mention spoil clown finger wheat motion fox axis mechanic country make garment bar blind stadium sugar water scissors canyon often ketchup", export.mnemonic.as_ref().unwrap().seed);
             assert_eq!(exp_deposit_data, export.deposit_data);
         }
     }
@@ -414,32 +793,28 @@ mod test {
         fn validators_with_mnemonic() -> Validators {
             Validators::new(
                 Some(PHRASE.as_bytes()),
+                None,
                 Some("testtest".as_bytes().to_vec()),
                 Some(1),
                 Some(0),
-                false,
-                Some(KdfVariant::Scrypt),
+                &KeyDerivationParams {
+                    kdf: KdfVariant::Scrypt.into(),
+                    ..Default::default()
+                },
             )
+            .unwrap()
         }
 
         let exports = vec![
             validators_with_mnemonic()
-                .export(
-                    Some(SupportedNetworks::Mainnet),
-                    Some("0x0000000000000000000000000000000000000001".to_string()),
-                    32_000_000_000,
-                    "2.7.0".to_string(),
-                    None,
-                )
+                .export(&mainnet_params(Some(
+                    "0x0000000000000000000000000000000000000001",
+                )))
                 .unwrap(),
             validators_with_mnemonic()
-                .export(
-                    Some(SupportedNetworks::Mainnet),
-                    Some("0x0000000000000000000000000000000000000001".to_string()),
-                    32_000_000_000,
-                    "2.7.0".to_string(),
-                    None,
-                )
+                .export(&mainnet_params(Some(
+                    "0x0000000000000000000000000000000000000001",
+                )))
                 .unwrap(),
         ];
 
@@ -463,7 +838,7 @@ mod test {
                 "3f3e0a69a6a66aeaec606a2ccb47c703afb2e8ae64f70a1650c03343b06e8f0c",
                 export.private_keys[0]
             );
-            assert_eq!("entire habit bottom mention spoil clown finger wheat motion fox axis mechanic country make garment bar blind stadium sugar water scissors canyon often ketchup", export.mnemonic.seed);
+            assert_eq!("entire habit bottom mention spoil clown finger wheat motion fox axis mechanic country make garment bar blind stadium sugar water scissors canyon often ketchup", export.mnemonic.as_ref().unwrap().seed);
             assert_eq!(exp_deposit_data, export.deposit_data);
         }
     }
@@ -472,33 +847,26 @@ mod test {
     fn test_export_validators_new_mnemonic() {
         fn validators_new_mnemonic() -> Validators {
             Validators::new(
+                None,
                 None,
                 Some("testtest".as_bytes().to_vec()),
                 Some(1),
                 Some(0),
-                false,
-                None,
+                &KeyDerivationParams::default(),
             )
+            .unwrap()
         }
 
         let exports: Vec<ValidatorExports> = vec![
             validators_new_mnemonic()
-                .export(
-                    Some(SupportedNetworks::Mainnet),
-                    Some("0x0000000000000000000000000000000000000001".to_string()),
-                    32_000_000_000,
-                    "2.7.0".to_string(),
-                    None,
-                )
+                .export(&mainnet_params(Some(
+                    "0x0000000000000000000000000000000000000001",
+                )))
                 .unwrap(),
             validators_new_mnemonic()
-                .export(
-                    Some(SupportedNetworks::Mainnet),
-                    Some("0x0000000000000000000000000000000000000001".to_string()),
-                    32_000_000_000,
-                    "2.7.0".to_string(),
-                    None,
-                )
+                .export(&mainnet_params(Some(
+                    "0x0000000000000000000000000000000000000001",
+                )))
                 .unwrap(),
         ];
 
@@ -523,33 +891,29 @@ mod test {
     fn test_export_validators_new_mnemonic_scrypt() {
         fn validators_new_mnemonic() -> Validators {
             Validators::new(
+                None,
                 None,
                 Some("testtest".as_bytes().to_vec()),
                 Some(1),
                 Some(0),
-                false,
-                Some(KdfVariant::Scrypt),
+                &KeyDerivationParams {
+                    kdf: KdfVariant::Scrypt.into(),
+                    ..Default::default()
+                },
             )
+            .unwrap()
         }
 
         let exports: Vec<ValidatorExports> = vec![
             validators_new_mnemonic()
-                .export(
-                    Some(SupportedNetworks::Mainnet),
-                    Some("0x0000000000000000000000000000000000000001".to_string()),
-                    32_000_000_000,
-                    "2.7.0".to_string(),
-                    None,
-                )
+                .export(&mainnet_params(Some(
+                    "0x0000000000000000000000000000000000000001",
+                )))
                 .unwrap(),
             validators_new_mnemonic()
-                .export(
-                    Some(SupportedNetworks::Mainnet),
-                    Some("0x0000000000000000000000000000000000000001".to_string()),
-                    32_000_000_000,
-                    "2.7.0".to_string(),
-                    None,
-                )
+                .export(&mainnet_params(Some(
+                    "0x0000000000000000000000000000000000000001",
+                )))
                 .unwrap(),
         ];
 
@@ -574,22 +938,18 @@ mod test {
     fn test_export_validators_no_withdrawal_credentials() {
         let validators = Validators::new(
             Some(PHRASE.as_bytes()),
+            None,
             Some("testtest".as_bytes().to_vec()),
             Some(1),
             Some(0),
-            true,
-            None,
-        );
+            &KeyDerivationParams {
+                derive_withdrawal: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
 
-        let export = validators
-            .export(
-                Some(SupportedNetworks::Mainnet),
-                None,
-                32_000_000_000,
-                "2.7.0".to_string(),
-                None,
-            )
-            .unwrap();
+        let export = validators.export(&mainnet_params(None)).unwrap();
 
         let exp_deposit_data: Vec<DepositExport> = serde_json::from_str(r#"[
     {
@@ -608,12 +968,191 @@ mod test {
         assert_eq!(exp_deposit_data, export.deposit_data);
     }
 
+    #[test]
+    fn test_export_validators_without_secrets() {
+        let validators = Validators::new(
+            Some(PHRASE.as_bytes()),
+            None,
+            Some("testtest".as_bytes().to_vec()),
+            Some(1),
+            Some(0),
+            &KeyDerivationParams::default(),
+        )
+        .unwrap();
+
+        let export: serde_json::Value = validators
+            .export(&mainnet_params(Some(
+                "0x0000000000000000000000000000000000000001",
+            )))
+            .unwrap()
+            .without_secrets()
+            .try_into()
+            .unwrap();
+
+        assert!(export.get("private_keys").is_none());
+        assert!(export.get("mnemonic").is_none());
+        assert_eq!(1, export["keystores"].as_array().unwrap().len());
+        assert_eq!(1, export["deposit_data"].as_array().unwrap().len());
+    }
+
+    #[test]
+    fn test_validator_stream() {
+        let validators = Validators::new(
+            Some(PHRASE.as_bytes()),
+            None,
+            None,
+            Some(5),
+            Some(2),
+            &KeyDerivationParams {
+                derive_withdrawal: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let stream = Validators::stream(
+            PHRASE.as_bytes(),
+            None,
+            None,
+            5,
+            2,
+            &KeyDerivationParams {
+                derive_withdrawal: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let streamed: Vec<(u32, String)> = stream
+            .map(|item| {
+                let (idx, key_material) = item.unwrap();
+                (idx, key_material.keypair.pk.as_hex_string())
+            })
+            .collect();
+        let expected: Vec<(u32, String)> = (2..7)
+            .zip(
+                validators
+                    .key_material
+                    .iter()
+                    .map(|key_material| key_material.keypair.pk.as_hex_string()),
+            )
+            .collect();
+        assert_eq!(expected, streamed);
+
+        let mut stream = Validators::stream(
+            PHRASE.as_bytes(),
+            None,
+            Some(b"short".to_vec()),
+            5,
+            0,
+            &KeyDerivationParams {
+                derive_withdrawal: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(matches!(
+            stream.next(),
+            Some(Err(DepositError::InvalidPassword(_)))
+        ));
+        assert!(stream.next().is_none());
+
+        assert!(matches!(
+            Validators::stream(
+                PHRASE.as_bytes(),
+                None,
+                None,
+                2,
+                u32::MAX,
+                &KeyDerivationParams::default()
+            ),
+            Err(DepositError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_validators_with_passphrase() {
+        let validators = Validators::new(
+            Some(PHRASE.as_bytes()),
+            Some("TREZOR"),
+            None,
+            Some(2),
+            Some(0),
+            &KeyDerivationParams::default(),
+        )
+        .unwrap();
+
+        let public_keys: Vec<String> = validators
+            .key_material
+            .iter()
+            .map(|key_material| key_material.keypair.pk.as_hex_string())
+            .collect();
+        assert_eq!(
+            vec![
+                "0xa2f9fe8766d5fca6dd04c117f0063b244eed951885e13bef16dcd6958478de9aa55e0694573950192cc793f83d361a96",
+                "0x88ebbac790e3d588fc0f91a75af3da69694a97ec300944d48bb6537430a4a3b3d7f7ba1e74656c2e2ba216b5b80e6002",
+            ],
+            public_keys
+        );
+    }
+
+    #[test]
+    fn test_verify_deposit_data() {
+        let validators = Validators::new(
+            Some(PHRASE.as_bytes()),
+            None,
+            None,
+            Some(1),
+            Some(0),
+            &KeyDerivationParams::default(),
+        )
+        .unwrap();
+        let spec = crate::chain_spec::chain_spec_for_network(&SupportedNetworks::Mainnet).unwrap();
+        let deposit = validators
+            .export(&mainnet_params(Some(
+                "0x0000000000000000000000000000000000000001",
+            )))
+            .unwrap()
+            .deposit_data
+            .remove(0);
+        assert!(deposit.verify(&spec).is_ok());
+
+        let mut wrong_amount = deposit.clone();
+        wrong_amount.amount = 31_000_000_000;
+        assert_eq!(
+            Err(DepositError::InvalidDepositData(
+                "deposit message root does not match".to_string()
+            )),
+            wrong_amount.verify(&spec)
+        );
+
+        let mut wrong_fork = deposit.clone();
+        wrong_fork.fork_version = "00001020".to_string();
+        assert_eq!(
+            Err(DepositError::InvalidDepositData(
+                "fork version does not match the chain".to_string()
+            )),
+            wrong_fork.verify(&spec)
+        );
+
+        let mut wrong_prefix = deposit;
+        wrong_prefix
+            .withdrawal_credentials
+            .replace_range(0..2, "03");
+        assert_eq!(
+            Err(DepositError::InvalidDepositData(
+                "withdrawal credentials have unexpected prefix".to_string()
+            )),
+            wrong_prefix.verify(&spec)
+        );
+    }
+
     #[test]
     fn set_withdrawal_credentials_wrong_execution_format() {
         // should be 0xD4BB555d3B0D7fF17c606161B44E372689C14F4B
         let response = set_withdrawal_credentials(
             Some("0x01D4BB555d3B0D7fF17c606161B44E372689C14F4B".to_string()),
             None,
+            false,
         );
         assert!(response.is_err());
     }
@@ -623,6 +1162,7 @@ mod test {
         let response = set_withdrawal_credentials(
             Some("0xD4BB555d3B0D7fF17c606161B44E372689C14F4B".to_string()),
             None,
+            false,
         );
         assert!(response.is_ok());
     }
@@ -632,17 +1172,57 @@ mod test {
         let response = set_withdrawal_credentials(
             Some("0x0100000000000000000000000000000000000000000000000000000000000001".to_string()),
             None,
+            false,
+        );
+
+        assert!(response.is_ok());
+    }
+
+    #[test]
+    fn set_withdrawal_credentials_compounding_execution_address() {
+        let response = set_withdrawal_credentials(
+            Some("0xD4BB555d3B0D7fF17c606161B44E372689C14F4B".to_string()),
+            None,
+            true,
+        );
+        assert_eq!(
+            "020000000000000000000000d4bb555d3b0d7ff17c606161b44e372689c14f4b",
+            hex::encode(response.unwrap())
+        );
+    }
+
+    #[test]
+    fn set_withdrawal_credentials_valid_compounding_credentials() {
+        let response = set_withdrawal_credentials(
+            Some("0x0200000000000000000000000000000000000000000000000000000000000001".to_string()),
+            None,
+            false,
         );
 
         assert!(response.is_ok());
     }
 
+    #[test]
+    fn set_withdrawal_credentials_compounding_requires_execution_address() {
+        let response = set_withdrawal_credentials(
+            Some("0x0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d".to_string()),
+            None,
+            true,
+        );
+        assert!(response.is_err());
+
+        let keypair = Keypair::random();
+        let response = set_withdrawal_credentials(None, Some(keypair), true);
+        assert!(response.is_err());
+    }
+
     #[test]
     fn set_withdrawal_credentials_wrong_bls_format() {
         // should be 0x0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d
         let response = set_withdrawal_credentials(
             Some("0x45b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d".to_string()),
             None,
+            false,
         );
         assert!(response.is_err());
     }
@@ -652,6 +1232,7 @@ mod test {
         let response = set_withdrawal_credentials(
             Some("0x0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d".to_string()),
             None,
+            false,
         );
         assert!(response.is_ok());
     }
@@ -659,14 +1240,14 @@ mod test {
     #[test]
     fn set_withdrawal_credentials_error_no_key() {
         // either withdrawal public key or withdrawal credentials must be provided
-        let response = set_withdrawal_credentials(None, None);
+        let response = set_withdrawal_credentials(None, None, false);
         assert!(response.is_err());
     }
 
     #[test]
     fn set_withdrawal_credentials_from_public_key() {
         let keypair = Keypair::random();
-        let response = set_withdrawal_credentials(None, Some(keypair));
+        let response = set_withdrawal_credentials(None, Some(keypair), false);
         assert!(response.is_ok());
     }
 }
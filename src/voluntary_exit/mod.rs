@@ -2,44 +2,89 @@ pub(crate) mod operations;
 
 use types::{Epoch, VoluntaryExit};
 
-use crate::key_material::VotingKeyMaterial;
+use crate::key_material::{DerivationPaths, VotingKeyMaterial};
+use crate::DepositError;
 
 pub fn voluntary_exit_message_from_mnemonic(
     mnemonic_phrase: &[u8],
+    passphrase: Option<&str>,
     validator_seed_index: u64,
     validator_beacon_index: u64,
     epoch: u64,
-) -> (VoluntaryExit, VotingKeyMaterial) {
-    let (seed, _) = crate::seed::get_eth2_seed(Some(mnemonic_phrase));
+    derivation_paths: Option<&DerivationPaths>,
+) -> Result<(VoluntaryExit, VotingKeyMaterial), DepositError> {
+    Ok(voluntary_exit_messages_from_mnemonic(
+        mnemonic_phrase,
+        passphrase,
+        &[(validator_seed_index, validator_beacon_index)],
+        epoch,
+        derivation_paths,
+    )?
+    .remove(0))
+}
 
-    let key_materials = crate::key_material::seed_to_key_material(
-        &seed,
-        1,
-        validator_seed_index as u32,
-        None,
-        false,
-        None,
-    );
+/// Create voluntary exit messages for a batch of validators derived from the same mnemonic,
+/// given pairs of validator seed index and validator beacon index.
+pub fn voluntary_exit_messages_from_mnemonic(
+    mnemonic_phrase: &[u8],
+    passphrase: Option<&str>,
+    validator_indices: &[(u64, u64)],
+    epoch: u64,
+    derivation_paths: Option<&DerivationPaths>,
+) -> Result<Vec<(VoluntaryExit, VotingKeyMaterial)>, DepositError> {
+    let (seed, _) = crate::seed::get_eth2_seed(Some(mnemonic_phrase), passphrase)?;
+    let derivation_paths = derivation_paths.cloned().unwrap_or_default();
+    derivation_paths.check(validator_indices.len(), false)?;
 
-    let key_material = key_materials
-        .first()
-        .expect("Error deriving key material from mnemonic");
+    validator_indices
+        .iter()
+        .map(|(validator_seed_index, validator_beacon_index)| {
+            let key_material = crate::key_material::seed_to_key_material(
+                &seed,
+                1,
+                *validator_seed_index as u32,
+                None,
+                false,
+                None,
+                &derivation_paths,
+            )?
+            .pop()
+            .ok_or_else(|| {
+                DepositError::KeyDerivationError(
+                    "Error deriving key material from mnemonic".to_string(),
+                )
+            })?;
 
-    let voluntary_exit = VoluntaryExit {
-        epoch: Epoch::from(epoch),
-        validator_index: validator_beacon_index,
-    };
-
-    (voluntary_exit, key_material.clone())
+            Ok(voluntary_exit_message_from_key_material(
+                key_material,
+                *validator_beacon_index,
+                epoch,
+            ))
+        })
+        .collect()
 }
 
 pub fn voluntary_exit_message_from_secret_key(
     secret_key_bytes: &[u8],
     validator_beacon_index: u64,
     epoch: u64,
-) -> (VoluntaryExit, VotingKeyMaterial) {
-    let key_material = VotingKeyMaterial::from_voting_secret_bytes(secret_key_bytes);
+) -> Result<(VoluntaryExit, VotingKeyMaterial), DepositError> {
+    let key_material = VotingKeyMaterial::from_voting_secret_bytes(secret_key_bytes)?;
+
+    Ok(voluntary_exit_message_from_key_material(
+        key_material,
+        validator_beacon_index,
+        epoch,
+    ))
+}
 
+/// Create voluntary exit message for already loaded key material,
+/// e.g. decrypted from existing keystore.
+pub fn voluntary_exit_message_from_key_material(
+    key_material: VotingKeyMaterial,
+    validator_beacon_index: u64,
+    epoch: u64,
+) -> (VoluntaryExit, VotingKeyMaterial) {
     let voluntary_exit = VoluntaryExit {
         epoch: Epoch::from(epoch),
         validator_index: validator_beacon_index,
@@ -4,9 +4,15 @@ use types::{
 };
 
 use crate::beacon_node::BeaconNodeExportable;
+use crate::DepositError;
 
 pub(crate) trait SignedVoluntaryExitValidator {
-    fn validate(self, pubkey: &PublicKey, spec: &ChainSpec, genesis_validators_root: &Hash256);
+    fn validate(
+        self,
+        pubkey: &PublicKey,
+        spec: &ChainSpec,
+        genesis_validators_root: &Hash256,
+    ) -> Result<(), DepositError>;
 }
 
 impl BeaconNodeExportable for SignedVoluntaryExit {
@@ -26,7 +32,12 @@ impl BeaconNodeExportable for SignedVoluntaryExit {
 }
 
 impl SignedVoluntaryExitValidator for SignedVoluntaryExit {
-    fn validate(self, pubkey: &PublicKey, spec: &ChainSpec, genesis_validators_root: &Hash256) {
+    fn validate(
+        self,
+        pubkey: &PublicKey,
+        spec: &ChainSpec,
+        genesis_validators_root: &Hash256,
+    ) -> Result<(), DepositError> {
         let fork_name = spec.fork_name_at_epoch(self.message.epoch);
         let fork_version = match fork_name {
             ForkName::Base | ForkName::Altair | ForkName::Merge | ForkName::Capella => {
@@ -47,7 +58,10 @@ impl SignedVoluntaryExitValidator for SignedVoluntaryExit {
         };
         let signing_root = voluntary_exit.signing_root(domain);
         if !self.signature.verify(pubkey, signing_root) {
-            panic!("Invalid voluntary exit signature")
+            return Err(DepositError::InvalidSignature(
+                "Invalid voluntary exit signature".to_string(),
+            ));
         }
+        Ok(())
     }
 }
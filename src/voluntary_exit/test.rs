@@ -5,15 +5,18 @@ const PHRASE: &str = "entire habit bottom mention spoil clown finger wheat motio
 #[test]
 fn it_generates_presigned_exit_message() {
     let (genesis_validators_root, spec) =
-        validators_root_and_spec(Some(SupportedNetworks::Holesky), None);
+        validators_root_and_spec(Some(SupportedNetworks::Holesky), None).unwrap();
 
     let (voluntary_exit, key_material) =
         crate::voluntary_exit::voluntary_exit_message_from_mnemonic(
             PHRASE.as_bytes(),
+            None,
             0,
             100,
             73682,
-        );
+            None,
+        )
+        .unwrap();
 
     let signed_voluntary_exit =
         voluntary_exit.sign(&key_material.keypair.sk, genesis_validators_root, &spec);
@@ -26,15 +29,18 @@ fn it_generates_presigned_exit_message() {
 #[test]
 fn it_generates_presigned_exit_message_gnosis() {
     let (genesis_validators_root, spec) =
-        validators_root_and_spec(Some(SupportedNetworks::Gnosis), None);
+        validators_root_and_spec(Some(SupportedNetworks::Gnosis), None).unwrap();
 
     let (voluntary_exit, key_material) =
         crate::voluntary_exit::voluntary_exit_message_from_mnemonic(
             PHRASE.as_bytes(),
+            None,
             0,
             100,
             73682,
-        );
+            None,
+        )
+        .unwrap();
 
     let signed_voluntary_exit =
         voluntary_exit.sign(&key_material.keypair.sk, genesis_validators_root, &spec);
@@ -43,3 +49,112 @@ fn it_generates_presigned_exit_message_gnosis() {
     assert_eq!(73682, signed_voluntary_exit.message.epoch.as_u64());
     assert_eq!("0x8a237dd1b6127ebd9d7103e2117b8b093a049aa5a42f45c55bb17d3b689b9aa0fe5730954d9d322b6cf0eecc7205da03153cf7f155a46f582b704a511a5306267bbe6b78a71ef55a72709350770e05578253f54f7088514dcdf5f5c73db53c45", signed_voluntary_exit.signature.to_string());
 }
+
+#[test]
+fn it_generates_presigned_exit_message_sepolia() {
+    let (genesis_validators_root, spec) =
+        validators_root_and_spec(Some(SupportedNetworks::Sepolia), None).unwrap();
+
+    let (voluntary_exit, key_material) =
+        crate::voluntary_exit::voluntary_exit_message_from_mnemonic(
+            PHRASE.as_bytes(),
+            None,
+            0,
+            100,
+            73682,
+            None,
+        )
+        .unwrap();
+
+    let signed_voluntary_exit =
+        voluntary_exit.sign(&key_material.keypair.sk, genesis_validators_root, &spec);
+
+    assert_eq!(100, signed_voluntary_exit.message.validator_index);
+    assert_eq!(73682, signed_voluntary_exit.message.epoch.as_u64());
+    assert_eq!("0x8281a4d51aa2d7e219873e2c134a20a27076a9945b19e15d501e8a20b7e40945e0480efc56f6ee74093c2a927d79627517bde014746944c0a1e13e5dad1cd9fbdfc0b79878a3fcdbc49d4af915d6a6b7b765666d73d66842e9fa29201fdb008f", signed_voluntary_exit.signature.to_string());
+}
+
+#[test]
+fn it_generates_presigned_exit_message_hoodi() {
+    let (genesis_validators_root, spec) =
+        validators_root_and_spec(Some(SupportedNetworks::Hoodi), None).unwrap();
+
+    let (voluntary_exit, key_material) =
+        crate::voluntary_exit::voluntary_exit_message_from_mnemonic(
+            PHRASE.as_bytes(),
+            None,
+            0,
+            100,
+            73682,
+            None,
+        )
+        .unwrap();
+
+    let signed_voluntary_exit =
+        voluntary_exit.sign(&key_material.keypair.sk, genesis_validators_root, &spec);
+
+    assert_eq!(100, signed_voluntary_exit.message.validator_index);
+    assert_eq!(73682, signed_voluntary_exit.message.epoch.as_u64());
+    assert_eq!("0x984b610ccc64cb77a0a44e98474660123f2cd9e3aafdea23c7a7a92324b4b4c94c2deb90d16e6ebe7cf231c7549915650f5fa3140678220447c8f4e4403b617cab9b8b6819229a4cfadf5c5e61ea895f06a93d8e55ceda3a88455f69fde4f1b0", signed_voluntary_exit.signature.to_string());
+}
+
+#[test]
+fn it_generates_presigned_exit_message_chiado() {
+    let (genesis_validators_root, spec) =
+        validators_root_and_spec(Some(SupportedNetworks::Chiado), None).unwrap();
+
+    let (voluntary_exit, key_material) =
+        crate::voluntary_exit::voluntary_exit_message_from_mnemonic(
+            PHRASE.as_bytes(),
+            None,
+            0,
+            100,
+            73682,
+            None,
+        )
+        .unwrap();
+
+    let signed_voluntary_exit =
+        voluntary_exit.sign(&key_material.keypair.sk, genesis_validators_root, &spec);
+
+    assert_eq!(100, signed_voluntary_exit.message.validator_index);
+    assert_eq!(73682, signed_voluntary_exit.message.epoch.as_u64());
+    assert_eq!("0xaa37936b0db14369784ae5ec66d318df2289ea865e1ecfbe3a7b6360c95862d709638ef678ea107477ee523ed5f6fa7306947a9773dfca9b8abd353858ae8afd34be88053d8b326b086d9401c595dd65e5d4cc9d444ba9c2a3fec34ed98aefcf", signed_voluntary_exit.signature.to_string());
+}
+
+#[test]
+fn it_generates_presigned_exit_messages_batch() {
+    let (genesis_validators_root, spec) =
+        validators_root_and_spec(Some(SupportedNetworks::Holesky), None).unwrap();
+
+    let exits = crate::voluntary_exit::voluntary_exit_messages_from_mnemonic(
+        PHRASE.as_bytes(),
+        None,
+        &[(0, 100), (1, 101)],
+        73682,
+        None,
+    )
+    .unwrap();
+    assert_eq!(2, exits.len());
+
+    // first exit in the batch is the same as the one generated alone
+    let (voluntary_exit, key_material) = exits.first().unwrap().clone();
+    let signed_voluntary_exit =
+        voluntary_exit.sign(&key_material.keypair.sk, genesis_validators_root, &spec);
+    assert_eq!(100, signed_voluntary_exit.message.validator_index);
+    assert_eq!("0xa418543c8bdc266e00a45d7409386fffe02ad9ce3c1e707d562b38f7160966567602c518f6615edc8ecd964b978837f30a742c535dafc33137833b3aa6c30f4fecf37449de405ed50d8e436f21c6f58c0a48407037b1985507c3221ce53ba213", signed_voluntary_exit.signature.to_string());
+
+    let (voluntary_exit, key_material) = exits.get(1).unwrap().clone();
+    assert_eq!(101, voluntary_exit.validator_index);
+    assert_ne!(exits[0].1.keypair.pk, key_material.keypair.pk);
+}
+
+#[test]
+fn it_rejects_invalid_secret_key() {
+    let result =
+        crate::voluntary_exit::voluntary_exit_message_from_secret_key(&[0u8; 31], 100, 73682);
+    assert!(matches!(
+        result,
+        Err(crate::DepositError::InvalidPrivateKey(_))
+    ));
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use ssz::Encode;
+use types::PublicKeyBytes;
+
+use crate::key_material::DerivationPaths;
+use crate::transaction::TransactionTemplate;
+use crate::DepositError;
+
+/// Address of EIP-7002 withdrawal request predeploy contract,
+/// which is the same on every network.
+pub const WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS: &str = "0x00000961Ef480Eb55e80D19ad83579A64c007002";
+
+/// Withdrawal request amount which triggers full exit of the validator.
+pub const FULL_EXIT_REQUEST_AMOUNT: u64 = 0;
+
+/// EIP-7002 withdrawal request, triggered from execution layer by the owner
+/// of withdrawal address of the validator, without voting key.
+///
+/// Zero amount requests full exit of the validator, and any other amount
+/// in gwei requests partial withdrawal, which is only processed
+/// for validators with compounding (0x02) withdrawal credentials.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WithdrawalRequest {
+    pub validator_pubkey: PublicKeyBytes,
+    pub amount: u64,
+}
+
+/// Withdrawal request payload along with transaction to the predeploy contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawalRequestExport {
+    pub validator_pubkey: String,
+    pub amount: u64,
+    pub transaction: TransactionTemplate,
+}
+
+impl WithdrawalRequest {
+    pub fn new(validator_pubkey: PublicKeyBytes, amount: u64) -> Self {
+        Self {
+            validator_pubkey,
+            amount,
+        }
+    }
+
+    /// Calldata of the request, 48 bytes of validator public key
+    /// followed by 8 bytes of big-endian amount in gwei.
+    pub fn calldata(&self) -> Vec<u8> {
+        let mut calldata = self.validator_pubkey.as_ssz_bytes();
+        calldata.extend_from_slice(&self.amount.to_be_bytes());
+        calldata
+    }
+
+    /// Transaction to the predeploy contract, sending the request fee.
+    pub fn transaction(&self, fee_wei: u128) -> TransactionTemplate {
+        TransactionTemplate::new(
+            WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS,
+            fee_wei,
+            &self.calldata(),
+        )
+    }
+
+    pub fn export(&self, fee_wei: u128) -> WithdrawalRequestExport {
+        WithdrawalRequestExport {
+            validator_pubkey: format!("0x{}", hex::encode(self.validator_pubkey.as_ssz_bytes())),
+            amount: self.amount,
+            transaction: self.transaction(fee_wei),
+        }
+    }
+}
+
+/// Create withdrawal requests for `num_validators` validators derived from the mnemonic,
+/// starting from `validator_start_index`, requesting the same amount for every validator.
+pub fn withdrawal_requests_from_mnemonic(
+    mnemonic_phrase: &[u8],
+    passphrase: Option<&str>,
+    validator_start_index: u32,
+    num_validators: u32,
+    amount: u64,
+    derivation_paths: Option<&DerivationPaths>,
+) -> Result<Vec<WithdrawalRequest>, DepositError> {
+    let validator_pubkeys = crate::seed_index::validator_pubkeys(
+        mnemonic_phrase,
+        passphrase,
+        validator_start_index,
+        num_validators,
+        derivation_paths,
+    )?;
+
+    Ok(validator_pubkeys
+        .into_iter()
+        .map(|validator_pubkey| WithdrawalRequest::new(validator_pubkey, amount))
+        .collect())
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,57 @@
+use pretty_assertions::assert_eq;
+
+use super::{
+    withdrawal_requests_from_mnemonic, FULL_EXIT_REQUEST_AMOUNT,
+    WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS,
+};
+
+const PHRASE: &str = "entire habit bottom mention spoil clown finger wheat motion fox axis mechanic country make garment bar blind stadium sugar water scissors canyon often ketchup";
+
+#[test]
+fn it_generates_full_exit_request() {
+    let withdrawal_requests = withdrawal_requests_from_mnemonic(
+        PHRASE.as_bytes(),
+        None,
+        1,
+        1,
+        FULL_EXIT_REQUEST_AMOUNT,
+        None,
+    )
+    .unwrap();
+    assert_eq!(1, withdrawal_requests.len());
+
+    let export = withdrawal_requests[0].export(1);
+    assert_eq!(
+        "0x974ec5bce4653f1f440ad07c5b363ad3b1616520e9680ff837f9ff7a8c10e3cc67dd49aa5089f714ed45d7ad56bc758a",
+        export.validator_pubkey
+    );
+    assert_eq!(0, export.amount);
+    assert_eq!(WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS, export.transaction.to);
+    assert_eq!("0x1", export.transaction.value);
+    assert_eq!(
+        "0x974ec5bce4653f1f440ad07c5b363ad3b1616520e9680ff837f9ff7a8c10e3cc67dd49aa5089f714ed45d7ad56bc758a0000000000000000",
+        export.transaction.data
+    );
+}
+
+#[test]
+fn it_generates_partial_withdrawal_requests() {
+    let withdrawal_requests =
+        withdrawal_requests_from_mnemonic(PHRASE.as_bytes(), None, 0, 3, 1_000_000_000, None)
+            .unwrap();
+    assert_eq!(3, withdrawal_requests.len());
+
+    let calldata = withdrawal_requests[2].calldata();
+    assert_eq!(56, calldata.len());
+    assert_eq!(
+        "8bf0a669a51d0cb6ff745e4b0aa7c41e8de8d179ff9267977e76c7188aaa2fb1b8b1bdfefcc545d9efdac0b4bc2239e6000000003b9aca00",
+        hex::encode(calldata)
+    );
+    assert_eq!(
+        "0x2386f26fc10000",
+        withdrawal_requests[2]
+            .export(10_000_000_000_000_000)
+            .transaction
+            .value
+    );
+}
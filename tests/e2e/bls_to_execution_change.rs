@@ -1,5 +1,6 @@
 use assert_cmd::prelude::*;
 use eth_staking_smith::utils::withdrawal_creds_from_pk;
+use predicates::prelude::*;
 use ssz::Encode;
 use std::process::Command;
 use types::SignedBlsToExecutionChange;
@@ -92,6 +93,23 @@ fn test_bls_to_execution_change() -> Result<(), Box<dyn std::error::Error>> {
 fn test_bls_to_execution_change_send_beacon_node() -> Result<(), Box<dyn std::error::Error>> {
     let server = httpmock::MockServer::start();
 
+    let validator_mock = server.mock(|when, then| {
+        when.method(httpmock::Method::GET)
+            .path("/eth/v1/beacon/states/head/validators/0x8844cebb34d10e0e57f3c29ada375dafe14762ab85b2e408c3d6d55ce6d03317660bca9f2c2d17d8fbe14a2529ada1ea");
+        then.status(200).json_body(serde_json::json!({
+            "execution_optimistic": false,
+            "finalized": false,
+            "data": {
+                "index": "100",
+                "status": "active_ongoing",
+                "validator": {
+                    "pubkey": "0x8844cebb34d10e0e57f3c29ada375dafe14762ab85b2e408c3d6d55ce6d03317660bca9f2c2d17d8fbe14a2529ada1ea",
+                    "withdrawal_credentials": "0x0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d",
+                }
+            }
+        }));
+    });
+
     let beacon_node_mock = server.mock(|when, then| {
         when.method(httpmock::Method::POST)
             .path("/eth/v1/beacon/pool/bls_to_execution_changes")
@@ -138,6 +156,221 @@ fn test_bls_to_execution_change_send_beacon_node() -> Result<(), Box<dyn std::er
 
     // verify request path and body
 
+    validator_mock.assert();
+    beacon_node_mock.assert();
+
+    Ok(())
+}
+
+#[test]
+fn test_bls_to_execution_change_batch() -> Result<(), Box<dyn std::error::Error>> {
+    let chain = "holesky";
+    let expected_mnemonic = "ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say";
+    let validator_start_index = "0";
+    let num_validators = "2";
+    let execution_address = "0x71C7656EC7ab88b098defB751B7401B5f6d8976F";
+    let other_execution_address = "0x0000000000000000000000000000000000000001";
+
+    let validator_index_file = std::env::temp_dir().join(format!(
+        "eth-staking-smith-bls-change-batch-{}.csv",
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::write(
+        &validator_index_file,
+        format!(
+            "0,100,,0x0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d\n\
+            1,101,{other_execution_address},\
+            0x006ab1394ad6a99cd25e2f1f15da057cfde5025b066bcecc1afedc2a4cb36314\n"
+        ),
+    )?;
+
+    // run eth-staking-smith
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("bls-to-execution-change");
+    cmd.arg("--chain");
+    cmd.arg(chain);
+    cmd.arg("--validator_seed_index");
+    cmd.arg(validator_start_index);
+    cmd.arg("--num_validators");
+    cmd.arg(num_validators);
+    cmd.arg("--validator_index_file");
+    cmd.arg(&validator_index_file);
+    cmd.arg("--mnemonic");
+    cmd.arg(expected_mnemonic);
+    cmd.arg("--execution_address");
+    cmd.arg(execution_address);
+
+    cmd.assert().success();
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    std::fs::remove_file(&validator_index_file)?;
+
+    let signed_bls_to_execution_changes: Vec<SignedBlsToExecutionChange> =
+        serde_json::from_str(command_output)?;
+    assert_eq!(2, signed_bls_to_execution_changes.len());
+
+    // first change is the same as generated for the single validator
+    let first_change = &signed_bls_to_execution_changes[0];
+    assert_eq!(100, first_change.message.validator_index);
+    assert_eq!(
+        "0xb9e6fcdf66962fbaeec762908e7c986c154ba2274fdfe307603d71c465acda49af98a75aa62743fc59a71e678fccd433164247130c1cede0832a17cc61fc21204ec83c7f8fd76848d6520805939547b4c677fca85f98d1f749c428814fd6a6c5",
+        format!("0x{}", hex::encode(first_change.signature.as_ssz_bytes()))
+    );
+
+    let second_change = &signed_bls_to_execution_changes[1];
+    assert_eq!(101, second_change.message.validator_index);
+    assert_eq!(
+        other_execution_address,
+        format!(
+            "0x{}",
+            hex::encode(second_change.message.to_execution_address)
+        )
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_bls_to_execution_change_batch_wrong_credentials() -> Result<(), Box<dyn std::error::Error>>
+{
+    let validator_index_file = std::env::temp_dir().join(format!(
+        "eth-staking-smith-bls-change-batch-{}.csv",
+        uuid::Uuid::new_v4()
+    ));
+    // withdrawal credentials of validator with seed index 1 are set for seed index 0
+    std::fs::write(
+        &validator_index_file,
+        "0,100,,0x006ab1394ad6a99cd25e2f1f15da057cfde5025b066bcecc1afedc2a4cb36314\n",
+    )?;
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("bls-to-execution-change");
+    cmd.arg("--chain");
+    cmd.arg("holesky");
+    cmd.arg("--validator_seed_index");
+    cmd.arg("0");
+    cmd.arg("--num_validators");
+    cmd.arg("1");
+    cmd.arg("--validator_index_file");
+    cmd.arg(&validator_index_file);
+    cmd.arg("--mnemonic");
+    cmd.arg("ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say");
+    cmd.arg("--execution_address");
+    cmd.arg("0x71C7656EC7ab88b098defB751B7401B5f6d8976F");
+
+    let assert = cmd.assert();
+    std::fs::remove_file(&validator_index_file)?;
+    assert.failure().stderr(predicate::str::contains(
+        "invalid bls withdrawal credentials",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_bls_to_execution_change_batch_missing_credentials() -> Result<(), Box<dyn std::error::Error>>
+{
+    let validator_index_file = std::env::temp_dir().join(format!(
+        "eth-staking-smith-bls-change-batch-{}.csv",
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::write(&validator_index_file, "0,100\n")?;
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("bls-to-execution-change");
+    cmd.arg("--chain");
+    cmd.arg("holesky");
+    cmd.arg("--validator_seed_index");
+    cmd.arg("0");
+    cmd.arg("--num_validators");
+    cmd.arg("1");
+    cmd.arg("--validator_index_file");
+    cmd.arg(&validator_index_file);
+    cmd.arg("--mnemonic");
+    cmd.arg("ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say");
+    cmd.arg("--execution_address");
+    cmd.arg("0x71C7656EC7ab88b098defB751B7401B5f6d8976F");
+
+    let assert = cmd.assert();
+    std::fs::remove_file(&validator_index_file)?;
+    assert.failure().stderr(predicate::str::contains(
+        "No BLS withdrawal credentials for validator seed index 0",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_bls_to_execution_change_batch_credentials_from_beacon_node(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let server = httpmock::MockServer::start();
+
+    let validator_mock = server.mock(|when, then| {
+        when.method(httpmock::Method::GET)
+            .path("/eth/v1/beacon/states/head/validators/0x8844cebb34d10e0e57f3c29ada375dafe14762ab85b2e408c3d6d55ce6d03317660bca9f2c2d17d8fbe14a2529ada1ea");
+        then.status(200).json_body(serde_json::json!({
+            "execution_optimistic": false,
+            "finalized": false,
+            "data": {
+                "index": "100",
+                "status": "active_ongoing",
+                "validator": {
+                    "pubkey": "0x8844cebb34d10e0e57f3c29ada375dafe14762ab85b2e408c3d6d55ce6d03317660bca9f2c2d17d8fbe14a2529ada1ea",
+                    "withdrawal_credentials": "0x0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d",
+                }
+            }
+        }));
+    });
+
+    let beacon_node_mock = server.mock(|when, then| {
+        when.method(httpmock::Method::POST)
+            .path("/eth/v1/beacon/pool/bls_to_execution_changes")
+            .json_body(serde_json::json!([
+                {
+                    "message": {
+                        "from_bls_pubkey": "0x958823db41e63bdb54b8445e454f24a592a44faef7bf1161c482c254d36cd2ffb027af3cc87817064c6a09f54acec5a0",
+                        "to_execution_address": "0x71C7656EC7ab88b098defB751B7401B5f6d8976F".to_lowercase(),
+                        "validator_index": "100",
+                    },
+                    "signature": "0xb9e6fcdf66962fbaeec762908e7c986c154ba2274fdfe307603d71c465acda49af98a75aa62743fc59a71e678fccd433164247130c1cede0832a17cc61fc21204ec83c7f8fd76848d6520805939547b4c677fca85f98d1f749c428814fd6a6c5"
+                }
+            ]));
+        then.status(200);
+    });
+
+    let validator_index_file = std::env::temp_dir().join(format!(
+        "eth-staking-smith-bls-change-batch-{}.csv",
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::write(&validator_index_file, "0,100\n")?;
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("bls-to-execution-change");
+    cmd.arg("--chain");
+    cmd.arg("holesky");
+    cmd.arg("--validator_seed_index");
+    cmd.arg("0");
+    cmd.arg("--num_validators");
+    cmd.arg("1");
+    cmd.arg("--validator_index_file");
+    cmd.arg(&validator_index_file);
+    cmd.arg("--mnemonic");
+    cmd.arg("ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say");
+    cmd.arg("--execution_address");
+    cmd.arg("0x71C7656EC7ab88b098defB751B7401B5f6d8976F");
+    cmd.arg("--beacon-node-uri");
+    cmd.arg(server.base_url());
+
+    let assert = cmd.assert();
+    std::fs::remove_file(&validator_index_file)?;
+    assert.success();
+
+    validator_mock.assert();
     beacon_node_mock.assert();
 
     Ok(())
@@ -0,0 +1,160 @@
+use assert_cmd::prelude::*;
+use eth_staking_smith::consolidation_request::ConsolidationRequestExport;
+use predicates::prelude::*;
+use std::process::Command;
+
+const MNEMONIC: &str = "entire habit bottom mention spoil clown finger wheat motion fox axis mechanic country make garment bar blind stadium sugar water scissors canyon often ketchup";
+const PUBKEY_0: &str = "0x8666389c3fe6ff0bca9adba81504f380b9e2c719419760d561836472fafe295cb50696524e19cba084e1d788d66c80d6";
+const PUBKEY_1: &str = "0x974ec5bce4653f1f440ad07c5b363ad3b1616520e9680ff837f9ff7a8c10e3cc67dd49aa5089f714ed45d7ad56bc758a";
+const PUBKEY_2: &str = "0x8bf0a669a51d0cb6ff745e4b0aa7c41e8de8d179ff9267977e76c7188aaa2fb1b8b1bdfefcc545d9efdac0b4bc2239e6";
+
+/*
+    consolidate validators derived from mnemonic into compounding target validator
+*/
+#[test]
+fn test_consolidation_request_from_mnemonic() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("consolidation-request");
+    cmd.arg("--mnemonic");
+    cmd.arg(MNEMONIC);
+    cmd.arg("--source_seed_index");
+    cmd.arg("1");
+    cmd.arg("--num_validators");
+    cmd.arg("2");
+    cmd.arg("--target_seed_index");
+    cmd.arg("0");
+    cmd.arg("--target_withdrawal_credentials");
+    cmd.arg("0x0200000000000000000000000000000000000000000000000000000000000001");
+    cmd.arg("--fee_wei");
+    cmd.arg("1");
+
+    cmd.assert().success();
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    let requests: Vec<ConsolidationRequestExport> = serde_json::from_str(command_output)?;
+    assert_eq!(2, requests.len());
+    for (request, source_pubkey) in requests.iter().zip([PUBKEY_1, PUBKEY_2]) {
+        assert_eq!(source_pubkey, request.source_pubkey);
+        assert_eq!(PUBKEY_0, request.target_pubkey);
+        assert_eq!(
+            "0x0000BBdDc7CE488642fb579F8B00f3a590007251",
+            request.transaction.to
+        );
+        assert_eq!(
+            format!("{}{}", source_pubkey, &PUBKEY_0[2..]),
+            request.transaction.data
+        );
+    }
+
+    Ok(())
+}
+
+/*
+    switch target validator to compounding credentials looked up at beacon node
+*/
+#[test]
+fn test_consolidation_request_beacon_node() -> Result<(), Box<dyn std::error::Error>> {
+    let server = httpmock::MockServer::start();
+    let mock_validator = |pubkey: &str, withdrawal_credentials: &str| {
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path(format!("/eth/v1/beacon/states/head/validators/{pubkey}"));
+            then.status(200).json_body(serde_json::json!({
+                "execution_optimistic": false,
+                "finalized": false,
+                "data": {
+                    "index": "100",
+                    "status": "active_ongoing",
+                    "validator": {
+                        "pubkey": pubkey,
+                        "withdrawal_credentials": withdrawal_credentials,
+                    }
+                }
+            }));
+        })
+    };
+    let target_mock = mock_validator(
+        PUBKEY_0,
+        "0x0100000000000000000000000000000000000000000000000000000000000001",
+    );
+    let source_mock = mock_validator(
+        PUBKEY_1,
+        "0x0100000000000000000000000000000000000000000000000000000000000001",
+    );
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("consolidation-request");
+    cmd.arg("--source_pubkey");
+    cmd.arg(PUBKEY_1);
+    cmd.arg("--target_pubkey");
+    cmd.arg(PUBKEY_0);
+    cmd.arg("--beacon_node_uri");
+    cmd.arg(server.base_url());
+    cmd.arg("--fee_wei");
+    cmd.arg("2");
+
+    cmd.assert().success();
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    let requests: Vec<ConsolidationRequestExport> = serde_json::from_str(command_output)?;
+    assert_eq!(2, requests.len());
+    assert_eq!(PUBKEY_0, requests[0].source_pubkey);
+    assert_eq!(PUBKEY_0, requests[0].target_pubkey);
+    assert_eq!(PUBKEY_1, requests[1].source_pubkey);
+    assert_eq!("0x2", requests[1].transaction.value);
+
+    target_mock.assert_hits(2);
+    source_mock.assert_hits(2);
+
+    Ok(())
+}
+
+/*
+    attempt to consolidate into target validator with BLS withdrawal credentials
+*/
+#[test]
+fn test_error_consolidation_request_bls_target() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("consolidation-request");
+    cmd.arg("--source_pubkey");
+    cmd.arg(PUBKEY_1);
+    cmd.arg("--target_pubkey");
+    cmd.arg(PUBKEY_0);
+    cmd.arg("--target_withdrawal_credentials");
+    cmd.arg("0x00b1f3a64cd7f0f1e9f0a6bdc5a5e8fa6d4b7ae33c4a8ec0fb0a8c1a9c04e3b1");
+    cmd.arg("--fee_wei");
+    cmd.arg("1");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Target validator has BLS (0x00) withdrawal credentials",
+    ));
+
+    Ok(())
+}
+
+/*
+    attempt to generate consolidation request without fee
+*/
+#[test]
+fn test_error_consolidation_request_no_fee() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("consolidation-request");
+    cmd.arg("--source_pubkey");
+    cmd.arg(PUBKEY_1);
+    cmd.arg("--target_pubkey");
+    cmd.arg(PUBKEY_0);
+    cmd.arg("--target_withdrawal_credentials");
+    cmd.arg("0x0200000000000000000000000000000000000000000000000000000000000001");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--fee-wei <FEE_WEI>"));
+
+    Ok(())
+}
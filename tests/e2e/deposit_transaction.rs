@@ -0,0 +1,175 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::{path::PathBuf, process::Command};
+
+fn deposit_data_file() -> PathBuf {
+    let mut manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // generated with staking-deposit-cli for goerli
+    manifest.push("tests/e2e/expected_testdata/multiple_validators/deposit_data-1670231001.json");
+    manifest
+}
+
+#[test]
+fn test_deposit_transaction() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("deposit-transaction");
+    cmd.arg("--chain");
+    cmd.arg("goerli");
+    cmd.arg("--deposit_data_file");
+    cmd.arg(deposit_data_file());
+
+    cmd.assert().success();
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    let export: Vec<serde_json::Value> = serde_json::from_str(command_output)?;
+
+    assert_eq!(3, export.len());
+    assert_eq!(
+        "8c239d313e3f4efb1ed937e7560dfaabeb6def6b88001357d5e9a3c33fdb022f7b028085c09451667f06b6b849c71ce8",
+        export[0]["pubkey"]
+    );
+    for entry in export {
+        assert_eq!(5, entry["chain_id"]);
+        assert_eq!(
+            "0xff50ed3d0ec03ac01d4c79aad74928bff48a7b2b",
+            entry["transaction"]["to"]
+        );
+        assert_eq!("0x1bc16d674ec800000", entry["transaction"]["value"]);
+        let data = entry["transaction"]["data"].as_str().unwrap();
+        assert!(data.starts_with("0x22895118"));
+        assert!(data.contains(entry["deposit_data_root"].as_str().unwrap()));
+        assert!(data.contains(entry["pubkey"].as_str().unwrap()));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_deposit_transaction_wrong_chain() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("deposit-transaction");
+    cmd.arg("--chain");
+    cmd.arg("mainnet");
+    cmd.arg("--deposit_data_file");
+    cmd.arg(deposit_data_file());
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(
+            "fork version does not match the chain",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_batch_deposit_transaction() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("deposit-transaction");
+    cmd.arg("--chain");
+    cmd.arg("goerli");
+    cmd.arg("--deposit_data_file");
+    cmd.arg(deposit_data_file());
+    cmd.arg("--batch_contract_address");
+    cmd.arg("0x0102030405060708090a0b0c0d0e0f1011121314");
+    cmd.arg("--batch_size");
+    cmd.arg("2");
+
+    cmd.assert().success();
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    let export: Vec<serde_json::Value> = serde_json::from_str(command_output)?;
+
+    assert_eq!(2, export.len());
+    assert_eq!(2, export[0]["pubkeys"].as_array().unwrap().len());
+    assert_eq!(64_000_000_000u64, export[0]["amount"]);
+    assert_eq!("0x3782dace9d9000000", export[0]["transaction"]["value"]);
+    assert_eq!(1, export[1]["pubkeys"].as_array().unwrap().len());
+    assert_eq!("0x1bc16d674ec800000", export[1]["transaction"]["value"]);
+    for entry in export {
+        assert_eq!(
+            "batchDeposit(bytes,bytes,bytes,bytes32[])",
+            entry["function_signature"]
+        );
+        assert_eq!(
+            "0x0102030405060708090a0b0c0d0e0f1011121314",
+            entry["transaction"]["to"]
+        );
+        assert!(entry["transaction"]["data"]
+            .as_str()
+            .unwrap()
+            .starts_with("0xc82655b7"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_batch_deposit_transaction_custom_function() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("deposit-transaction");
+    cmd.arg("--chain");
+    cmd.arg("goerli");
+    cmd.arg("--deposit_data_file");
+    cmd.arg(deposit_data_file());
+    cmd.arg("--batch_contract_address");
+    cmd.arg("0x0102030405060708090a0b0c0d0e0f1011121314");
+    cmd.arg("--batch_function");
+    cmd.arg("deposit(bytes pubkeys,bytes32 withdrawal_credentials,bytes signatures,bytes32[] deposit_data_roots)");
+
+    cmd.assert().success().stdout(predicate::str::contains(
+        "\"function_signature\": \"deposit(bytes,bytes32,bytes,bytes32[])\"",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_batch_deposit_transaction_unsupported_function() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("deposit-transaction");
+    cmd.arg("--chain");
+    cmd.arg("goerli");
+    cmd.arg("--deposit_data_file");
+    cmd.arg(deposit_data_file());
+    cmd.arg("--batch_contract_address");
+    cmd.arg("0x0102030405060708090a0b0c0d0e0f1011121314");
+    cmd.arg("--batch_function");
+    cmd.arg("batchDeposit(bytes pubkeys,uint256[] amounts)");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "unsupported parameter 'uint256[] amounts'",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_batch_deposit_transaction_wrong_chain() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("deposit-transaction");
+    cmd.arg("--chain");
+    cmd.arg("mainnet");
+    cmd.arg("--deposit_data_file");
+    cmd.arg(deposit_data_file());
+    cmd.arg("--batch_contract_address");
+    cmd.arg("0x0102030405060708090a0b0c0d0e0f1011121314");
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(
+            "fork version does not match the chain",
+        ));
+
+    Ok(())
+}
@@ -4,7 +4,7 @@ use eth2_keystore::{
     json_keystore::{Crypto, JsonKeystore},
     Keystore,
 };
-use eth_staking_smith::ValidatorExports;
+use eth_staking_smith::{utils::withdrawal_creds_from_pk, DepositExport, ValidatorExports};
 use predicates::prelude::*;
 use std::{
     env,
@@ -12,6 +12,7 @@ use std::{
     process::Command,
     str::FromStr,
 };
+use types::PublicKeyBytes;
 
 /*
     generate 1 validator with existing mnemonic
@@ -692,6 +693,147 @@ fn test_omitting_keystore_password() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/*
+    generate top-up deposit with custom amount
+*/
+#[test]
+fn test_custom_deposit_amount() -> Result<(), Box<dyn std::error::Error>> {
+    let chain = "holesky";
+    let expected_mnemonic = "satisfy suit expire castle fluid must electric genuine aim clock such under basic rabbit method";
+    let num_validators = "1";
+    let deposit_amount_gwei = "1000000000";
+    let execution_withdrawal_credentials =
+        "0x01000000000000000000000071c7656ec7ab88b098defb751b7401b5f6d8976f";
+
+    // run eth-staking-smith
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg(chain);
+    cmd.arg("--mnemonic");
+    cmd.arg(expected_mnemonic);
+    cmd.arg("--num_validators");
+    cmd.arg(num_validators);
+    cmd.arg("--withdrawal_credentials");
+    cmd.arg(execution_withdrawal_credentials);
+    cmd.arg("--deposit_amount_gwei");
+    cmd.arg(deposit_amount_gwei);
+
+    cmd.assert().success();
+
+    // read generated output
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    let generated_validator_json: ValidatorExports =
+        serde_json::from_str(command_output).expect("could not unmarshal command output");
+
+    let deposit_data = generated_validator_json.deposit_data.first().unwrap();
+    assert_eq!(1_000_000_000, deposit_data.amount);
+    deposit_data
+        .clone()
+        .validate(
+            eth_staking_smith::chain_spec::chain_spec_for_network(
+                &eth_staking_smith::networks::SupportedNetworks::Holesky,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    Ok(())
+}
+
+/*
+    generate compounding validator with 0x02 withdrawal credentials
+*/
+#[test]
+fn test_compounding_withdrawal_credentials() -> Result<(), Box<dyn std::error::Error>> {
+    let chain = "holesky";
+    let expected_mnemonic = "satisfy suit expire castle fluid must electric genuine aim clock such under basic rabbit method";
+    let num_validators = "1";
+    let deposit_amount_gwei = "64000000000";
+    let execution_address = "0x71C7656EC7ab88b098defB751B7401B5f6d8976F";
+
+    // run eth-staking-smith
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg(chain);
+    cmd.arg("--mnemonic");
+    cmd.arg(expected_mnemonic);
+    cmd.arg("--num_validators");
+    cmd.arg(num_validators);
+    cmd.arg("--withdrawal_credentials");
+    cmd.arg(execution_address);
+    cmd.arg("--compounding");
+    cmd.arg("--deposit_amount_gwei");
+    cmd.arg(deposit_amount_gwei);
+
+    cmd.assert().success();
+
+    // read generated output
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    let generated_validator_json: ValidatorExports =
+        serde_json::from_str(command_output).expect("could not unmarshal command output");
+
+    let deposit_data = generated_validator_json.deposit_data.first().unwrap();
+    assert_eq!(
+        "02000000000000000000000071c7656ec7ab88b098defb751b7401b5f6d8976f",
+        deposit_data.withdrawal_credentials
+    );
+    assert_eq!(64_000_000_000, deposit_data.amount);
+    deposit_data
+        .clone()
+        .validate(
+            eth_staking_smith::chain_spec::chain_spec_for_network(
+                &eth_staking_smith::networks::SupportedNetworks::Holesky,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    Ok(())
+}
+
+/*
+    attempt to generate deposit below minimum deposit amount
+*/
+#[test]
+fn test_error_deposit_amount_too_small() -> Result<(), Box<dyn std::error::Error>> {
+    let chain = "holesky";
+    let expected_mnemonic = "satisfy suit expire castle fluid must electric genuine aim clock such under basic rabbit method";
+    let num_validators = "1";
+    let deposit_amount_gwei = "100";
+    let execution_withdrawal_credentials =
+        "0x01000000000000000000000071c7656ec7ab88b098defb751b7401b5f6d8976f";
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg(chain);
+    cmd.arg("--mnemonic");
+    cmd.arg(expected_mnemonic);
+    cmd.arg("--num_validators");
+    cmd.arg(num_validators);
+    cmd.arg("--withdrawal_credentials");
+    cmd.arg(execution_withdrawal_credentials);
+    cmd.arg("--deposit_amount_gwei");
+    cmd.arg(deposit_amount_gwei);
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Invalid amount of deposit data, should be at least 1000000000 gwei",
+    ));
+
+    Ok(())
+}
+
 /*
     custom testnet config
 */
@@ -801,6 +943,87 @@ fn test_error_unsupported_network() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/*
+    generate 1 validator with cheap scrypt parameters in insecure mode,
+    as used for test fixtures
+*/
+#[test]
+fn test_keystore_kdf_params_insecure() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg("mainnet");
+    cmd.arg("--keystore_password");
+    cmd.arg("testtest");
+    cmd.arg("--mnemonic");
+    cmd.arg("ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say");
+    cmd.arg("--num_validators");
+    cmd.arg("1");
+    cmd.arg("--kdf");
+    cmd.arg("scrypt");
+    cmd.arg("--scrypt_n");
+    cmd.arg("1024");
+    cmd.arg("--insecure_kdf");
+
+    cmd.assert().success().stderr(predicate::str::contains(
+        "Insecure KDF mode is enabled, keystores may use weak parameters",
+    ));
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    let generated_validator_json: ValidatorExports = serde_json::from_str(command_output)?;
+    let keystore = generated_validator_json.keystores.get(0).unwrap();
+    let keystore_json: JsonKeystore = serde_json::from_str(&keystore.to_json_string().unwrap())?;
+
+    assert_eq!("scrypt", parse_kdf_function(keystore));
+    assert_eq!(
+        Some(1024),
+        serde_json::to_value(&keystore_json.crypto.kdf.params)?
+            .get("n")
+            .and_then(|n| n.as_u64())
+    );
+    assert_eq!(
+        "6d446ca271eb229044b9039354ecdfa6244d1a11615ec1a46fc82a800367de5d",
+        hex::encode(
+            keystore
+                .decrypt_keypair(b"testtest")
+                .unwrap()
+                .sk
+                .serialize()
+                .as_bytes()
+        )
+    );
+
+    Ok(())
+}
+
+/*
+    attempt to generate validator with kdf parameters below safe lower bounds
+*/
+#[test]
+fn test_error_weak_kdf_params() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg("mainnet");
+    cmd.arg("--keystore_password");
+    cmd.arg("testtest");
+    cmd.arg("--mnemonic");
+    cmd.arg("ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say");
+    cmd.arg("--num_validators");
+    cmd.arg("1");
+    cmd.arg("--pbkdf2_c");
+    cmd.arg("1000");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "KDF parameter pbkdf2_c must be at least 262144",
+    ));
+
+    Ok(())
+}
+
 /*
     attempt to generate validator with unsupported kdf
 */
@@ -978,6 +1201,638 @@ fn test_regenerate_from_seed_index() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/*
+    generate validators with mnemonic protected by BIP-39 passphrase
+*/
+#[test]
+fn test_mnemonic_passphrase_file() -> Result<(), Box<dyn std::error::Error>> {
+    let chain = "mainnet";
+    let expected_mnemonic = "entire habit bottom mention spoil clown finger wheat motion fox axis mechanic country make garment bar blind stadium sugar water scissors canyon often ketchup";
+    let num_validators = "2";
+
+    let passphrase_file = std::env::temp_dir().join(format!(
+        "eth-staking-smith-passphrase-{}.txt",
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::write(&passphrase_file, "TREZOR\n")?;
+
+    // run eth-staking-smith
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg(chain);
+    cmd.arg("--mnemonic");
+    cmd.arg(expected_mnemonic);
+    cmd.arg("--mnemonic_passphrase_file");
+    cmd.arg(&passphrase_file);
+    cmd.arg("--num_validators");
+    cmd.arg(num_validators);
+
+    cmd.assert().success();
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    std::fs::remove_file(&passphrase_file)?;
+    let generated_validator_json: ValidatorExports = serde_json::from_str(command_output)?;
+
+    let generated_pubkeys: Vec<String> = generated_validator_json
+        .deposit_data
+        .iter()
+        .map(|deposit_data| deposit_data.pubkey.clone())
+        .collect();
+    assert_eq!(
+        vec![
+            "a2f9fe8766d5fca6dd04c117f0063b244eed951885e13bef16dcd6958478de9aa55e0694573950192cc793f83d361a96",
+            "88ebbac790e3d588fc0f91a75af3da69694a97ec300944d48bb6537430a4a3b3d7f7ba1e74656c2e2ba216b5b80e6002",
+        ],
+        generated_pubkeys
+    );
+
+    Ok(())
+}
+
+/*
+    generate validators with mnemonic in language other than English
+*/
+#[test]
+fn test_non_english_mnemonic() -> Result<(), Box<dyn std::error::Error>> {
+    let chain = "mainnet";
+    let expected_mnemonic =
+        "ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco abierto";
+    let num_validators = "2";
+
+    // run eth-staking-smith
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg(chain);
+    cmd.arg("--mnemonic");
+    cmd.arg(expected_mnemonic);
+    cmd.arg("--num_validators");
+    cmd.arg(num_validators);
+
+    cmd.assert().success();
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    let generated_validator_json: ValidatorExports = serde_json::from_str(command_output)?;
+
+    let generated_pubkeys: Vec<String> = generated_validator_json
+        .deposit_data
+        .iter()
+        .map(|deposit_data| deposit_data.pubkey.clone())
+        .collect();
+    assert_eq!(
+        vec![
+            "99385b5b7c92a7b7f0e01190e4d241634924c03e344d27aa3fb564ba3993e8272fc375b93c09dbe2a0483ee5fd0bbca8",
+            "b73ac5b1182ee4d08c0cc3a64512b01b0f0f7787a57f16b22b97e352fa6ec257dd3cd645014c405121f23d3158dce4cc",
+        ],
+        generated_pubkeys
+    );
+
+    Ok(())
+}
+
+/*
+    read mnemonic and keystore password from sources other than plain arguments
+*/
+#[test]
+fn test_secret_sources() -> Result<(), Box<dyn std::error::Error>> {
+    let chain = "goerli";
+    let expected_decryption_password = "testtest";
+    let expected_mnemonic = "ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say";
+    let num_validators = "1";
+
+    let password_file = std::env::temp_dir().join(format!(
+        "eth-staking-smith-password-{}.txt",
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::write(&password_file, format!("{expected_decryption_password}\n"))?;
+
+    let mut outputs = vec![];
+    for (mnemonic_args, password_args) in [
+        (
+            vec!["--mnemonic_env", "ETH_STAKING_SMITH_TEST_MNEMONIC"],
+            vec!["--password_file", password_file.to_str().unwrap()],
+        ),
+        (
+            vec!["--mnemonic_fd", "0"],
+            vec!["--password_env", "ETH_STAKING_SMITH_TEST_PASSWORD"],
+        ),
+    ] {
+        // assert_cmd command is used to write mnemonic to stdin
+        let mut cmd = assert_cmd::Command::cargo_bin("eth-staking-smith")?;
+
+        cmd.arg("existing-mnemonic");
+        cmd.arg("--chain");
+        cmd.arg(chain);
+        cmd.args(&mnemonic_args);
+        cmd.args(&password_args);
+        cmd.arg("--num_validators");
+        cmd.arg(num_validators);
+        cmd.env("ETH_STAKING_SMITH_TEST_MNEMONIC", expected_mnemonic);
+        cmd.env(
+            "ETH_STAKING_SMITH_TEST_PASSWORD",
+            expected_decryption_password,
+        );
+        cmd.write_stdin(format!("{expected_mnemonic}\n"));
+
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let generated_validator_json: ValidatorExports =
+            serde_json::from_str(std::str::from_utf8(&output)?)?;
+        outputs.push(generated_validator_json);
+    }
+    std::fs::remove_file(&password_file)?;
+
+    // compare with keys generated from plain arguments
+    let test_dir = get_test_dir("withdrawal_credentials_bls");
+    let expected_keystore_json =
+        read_keystore_json(&test_dir, "keystore-m_12381_3600_0_0_0-1668613231.json");
+    let expected_private_key = decrypt_expected_keystore_file(
+        expected_decryption_password,
+        &expected_keystore_json.crypto,
+    );
+
+    for generated_validator_json in outputs {
+        assert_eq!(
+            expected_private_key,
+            generated_validator_json.private_keys[0]
+        );
+        let decrypted_keystore = generated_validator_json.keystores[0]
+            .decrypt_keypair(expected_decryption_password.as_bytes())
+            .expect("could not decrypt generated keystore");
+        assert_eq!(
+            expected_private_key,
+            hex::encode(decrypted_keystore.sk.serialize().as_bytes())
+        );
+    }
+
+    Ok(())
+}
+
+/*
+    only one mnemonic source is allowed
+*/
+#[test]
+fn test_error_multiple_mnemonic_sources() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg("mainnet");
+    cmd.arg("--mnemonic");
+    cmd.arg("ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say");
+    cmd.arg("--mnemonic_env");
+    cmd.arg("ETH_STAKING_SMITH_TEST_MNEMONIC");
+    cmd.arg("--num_validators");
+    cmd.arg("1");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    Ok(())
+}
+
+/*
+    private keys and mnemonic are left out of output with omit_secrets
+*/
+#[test]
+fn test_omit_secrets() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg("mainnet");
+    cmd.arg("--keystore_password");
+    cmd.arg("testtest");
+    cmd.arg("--mnemonic");
+    cmd.arg("ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say");
+    cmd.arg("--num_validators");
+    cmd.arg("1");
+    cmd.arg("--omit_secrets");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("private_keys").not())
+        .stdout(predicate::str::contains("mnemonic").not());
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    let generated_validator_json: ValidatorExports = serde_json::from_str(command_output)?;
+    assert!(generated_validator_json.private_keys.is_empty());
+    assert!(generated_validator_json.mnemonic.is_none());
+    assert_eq!(1, generated_validator_json.keystores.len());
+    assert_eq!(1, generated_validator_json.deposit_data.len());
+
+    Ok(())
+}
+
+/*
+    generate validator at custom derivation path, which is recorded in keystore
+*/
+#[test]
+fn test_custom_voting_path() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg("mainnet");
+    cmd.arg("--keystore_password");
+    cmd.arg("testtest");
+    cmd.arg("--mnemonic");
+    cmd.arg("ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say");
+    cmd.arg("--num_validators");
+    cmd.arg("1");
+    cmd.arg("--validator_start_index");
+    cmd.arg("2");
+    // EIP-2334 withdrawal key path, so voting key is the withdrawal key
+    cmd.arg("--voting_path");
+    cmd.arg("m/12381/3600/{i}/0");
+
+    cmd.assert().success().stderr(predicate::str::contains(
+        "Voting key derivation path m/12381/3600/{i}/0 deviates from EIP-2334 path m/12381/3600/{i}/0/0",
+    ));
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    let generated_validator_json: ValidatorExports = serde_json::from_str(command_output)?;
+    let keystore = generated_validator_json.keystores.first().unwrap();
+    assert_eq!(Some("m/12381/3600/2/0".to_string()), keystore.path());
+
+    let deposit_data = generated_validator_json.deposit_data.first().unwrap();
+    let voting_pubkey = PublicKeyBytes::from_str(&format!("0x{}", deposit_data.pubkey))?;
+    assert_eq!(
+        withdrawal_creds_from_pk(&voting_pubkey),
+        deposit_data.withdrawal_credentials
+    );
+
+    Ok(())
+}
+
+/*
+    attempt to generate multiple validators at raw derivation path
+*/
+#[test]
+fn test_error_raw_path_multiple_validators() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg("mainnet");
+    cmd.arg("--mnemonic");
+    cmd.arg("ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say");
+    cmd.arg("--num_validators");
+    cmd.arg("2");
+    cmd.arg("--voting_path");
+    cmd.arg("m/12381/3600/0/0/0");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "so it can only be used for a single validator",
+    ));
+
+    Ok(())
+}
+
+/*
+    generate top-up deposit data for existing keystore, instead of mnemonic
+*/
+#[test]
+fn test_deposit_data_existing_keystore() -> Result<(), Box<dyn std::error::Error>> {
+    let test_dir = get_test_dir("withdrawal_credentials_bls");
+    let password_file = env::temp_dir().join(format!(
+        "eth-staking-smith-keystore-password-{}",
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::write(&password_file, "testtest\n")?;
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg("mainnet");
+    cmd.arg("--keystore");
+    cmd.arg(test_dir.join("keystore-m_12381_3600_0_0_0-1668613231.json"));
+    cmd.arg("--keystore_password_file");
+    cmd.arg(&password_file);
+    cmd.arg("--withdrawal_credentials");
+    cmd.arg("0x0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d");
+    cmd.arg("--deposit_amount_gwei");
+    cmd.arg("1000000000");
+
+    cmd.assert().success();
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    std::fs::remove_file(&password_file)?;
+    let generated_validator_json: ValidatorExports = serde_json::from_str(command_output)?;
+
+    assert_eq!(1, generated_validator_json.keystores.len());
+    assert!(generated_validator_json.private_keys.is_empty());
+    let deposit_data = generated_validator_json.deposit_data.get(0).unwrap();
+    assert_eq!(
+        "8844cebb34d10e0e57f3c29ada375dafe14762ab85b2e408c3d6d55ce6d03317660bca9f2c2d17d8fbe14a2529ada1ea",
+        deposit_data.pubkey
+    );
+    assert_eq!(
+        "0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d",
+        deposit_data.withdrawal_credentials
+    );
+    assert_eq!(1000000000, deposit_data.amount);
+    assert!(!command_output.contains("mnemonic"));
+
+    Ok(())
+}
+
+/*
+    write top-up deposit data for existing keystore into output dir
+*/
+#[test]
+fn test_deposit_data_existing_keystore_output_dir() -> Result<(), Box<dyn std::error::Error>> {
+    let test_dir = get_test_dir("withdrawal_credentials_bls");
+    let output_dir =
+        env::temp_dir().join(format!("eth-staking-smith-topup-{}", uuid::Uuid::new_v4()));
+    let password_file = env::temp_dir().join(format!(
+        "eth-staking-smith-keystore-password-{}",
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::write(&password_file, "testtest\n")?;
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg("mainnet");
+    cmd.arg("--keystore");
+    cmd.arg(test_dir.join("keystore-m_12381_3600_0_0_0-1668613231.json"));
+    cmd.arg("--keystore_password_file");
+    cmd.arg(&password_file);
+    cmd.arg("--withdrawal_credentials");
+    cmd.arg("0x0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d");
+    cmd.arg("--deposit_amount_gwei");
+    cmd.arg("1000000000");
+    cmd.arg("--output_dir");
+    cmd.arg(&output_dir);
+
+    let assert = cmd.assert();
+    std::fs::remove_file(&password_file)?;
+    assert.success();
+
+    let mut files = std::fs::read_dir(&output_dir)?
+        .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    files.sort();
+    std::fs::remove_dir_all(&output_dir)?;
+    assert_eq!(2, files.len());
+    assert!(files[0].starts_with("deposit_data-"));
+    assert!(files[1].starts_with(
+        "keystore-8844cebb34d10e0e57f3c29ada375dafe14762ab85b2e408c3d6d55ce6d03317660bca9f2c2d17d8fbe14a2529ada1ea-"
+    ));
+
+    Ok(())
+}
+
+/*
+    write top-up deposit data for existing keystores without derivation path into output dir
+*/
+#[test]
+fn test_deposit_data_existing_keystore_without_path_output_dir(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let test_dir = get_test_dir("withdrawal_credentials_bls");
+    let tmp_dir = env::temp_dir().join(format!(
+        "eth-staking-smith-pathless-keystore-{}",
+        uuid::Uuid::new_v4()
+    ));
+    let keystore_dir = tmp_dir.join("keystores");
+    std::fs::create_dir_all(&keystore_dir)?;
+    let password_file = tmp_dir.join("password.txt");
+    std::fs::write(&password_file, "testtest\n")?;
+
+    // keystores written by some tools have no derivation path
+    let mut keystore_json: serde_json::Value = serde_json::from_slice(&std::fs::read(
+        test_dir.join("keystore-m_12381_3600_0_0_0-1668613231.json"),
+    )?)?;
+    keystore_json.as_object_mut().unwrap().remove("path");
+    std::fs::write(
+        keystore_dir.join("keystore-0.json"),
+        serde_json::to_string(&keystore_json)?,
+    )?;
+
+    let generate = |output_dir: &Path| {
+        let mut cmd = Command::cargo_bin("eth-staking-smith").unwrap();
+        cmd.arg("existing-mnemonic");
+        cmd.arg("--chain");
+        cmd.arg("mainnet");
+        cmd.arg("--keystore");
+        cmd.arg(&keystore_dir);
+        cmd.arg("--keystore_password_file");
+        cmd.arg(&password_file);
+        cmd.arg("--withdrawal_credentials");
+        cmd.arg("0x0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d");
+        cmd.arg("--deposit_amount_gwei");
+        cmd.arg("1000000000");
+        cmd.arg("--output_dir");
+        cmd.arg(output_dir);
+        cmd
+    };
+
+    let output_dir = tmp_dir.join("validator_keys");
+    generate(&output_dir).assert().success();
+    let mut files = std::fs::read_dir(&output_dir)?
+        .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    files.sort();
+    assert_eq!(2, files.len());
+    assert!(files[0].starts_with("deposit_data-"));
+    assert!(files[1].starts_with(
+        "keystore-8844cebb34d10e0e57f3c29ada375dafe14762ab85b2e408c3d6d55ce6d03317660bca9f2c2d17d8fbe14a2529ada1ea-"
+    ));
+
+    // the same keystore passed twice is rejected before anything is written
+    std::fs::copy(
+        keystore_dir.join("keystore-0.json"),
+        keystore_dir.join("keystore-1.json"),
+    )?;
+    let duplicate_output_dir = tmp_dir.join("duplicate_validator_keys");
+    let assert = generate(&duplicate_output_dir).assert();
+    let duplicate_output_written = duplicate_output_dir.exists();
+    std::fs::remove_dir_all(&tmp_dir)?;
+    assert.failure().stderr(predicate::str::contains(
+        "Keystores to export have the same file name",
+    ));
+    assert!(!duplicate_output_written);
+
+    Ok(())
+}
+
+/*
+    existing keystores can not be used along with mnemonic
+*/
+#[test]
+fn test_error_existing_keystore_with_mnemonic() -> Result<(), Box<dyn std::error::Error>> {
+    let test_dir = get_test_dir("withdrawal_credentials_bls");
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg("mainnet");
+    cmd.arg("--mnemonic");
+    cmd.arg("ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say");
+    cmd.arg("--keystore");
+    cmd.arg(test_dir.join("keystore-m_12381_3600_0_0_0-1668613231.json"));
+    cmd.arg("--keystore_password_file");
+    cmd.arg(test_dir.join("keystore-m_12381_3600_0_0_0-1668613231.json"));
+    cmd.arg("--withdrawal_credentials");
+    cmd.arg("0x0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    Ok(())
+}
+
+/*
+    generation into output dir is resumed from the last validator with recorded deposit data
+*/
+#[test]
+fn test_output_dir_resume() -> Result<(), Box<dyn std::error::Error>> {
+    let test_dir =
+        env::temp_dir().join(format!("eth-staking-smith-resume-{}", uuid::Uuid::new_v4()));
+    let complete_dir = test_dir.join("complete");
+    let resumed_dir = test_dir.join("resumed");
+
+    let generate = |output_dir: &Path, resume: bool, extra_args: &[&str]| {
+        let mut cmd = Command::cargo_bin("eth-staking-smith").unwrap();
+        cmd.arg("existing-mnemonic");
+        cmd.arg("--chain");
+        cmd.arg("mainnet");
+        cmd.arg("--keystore_password");
+        cmd.arg("testtest");
+        cmd.arg("--mnemonic");
+        cmd.arg("ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say");
+        cmd.arg("--num_validators");
+        cmd.arg("3");
+        cmd.arg("--pbkdf2_c");
+        cmd.arg("1024");
+        cmd.arg("--insecure_kdf");
+        cmd.arg("--output_dir");
+        cmd.arg(output_dir);
+        if resume {
+            cmd.arg("--resume");
+        }
+        cmd.args(extra_args);
+        cmd
+    };
+
+    generate(&complete_dir, false, &[]).assert().success();
+    let read_deposit_data = |dir: &Path| -> Vec<DepositExport> {
+        let deposit_data_file = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| {
+                let name = path.file_name().unwrap().to_str().unwrap();
+                name.starts_with("deposit_data-") && name.ends_with(".json")
+            })
+            .expect("no deposit data file");
+        serde_json::from_slice(&std::fs::read(deposit_data_file).unwrap()).unwrap()
+    };
+    let expected_deposit_data = read_deposit_data(&complete_dir);
+    assert_eq!(3, expected_deposit_data.len());
+
+    // simulate generation interrupted while recording deposit data of the second validator
+    std::fs::create_dir_all(&resumed_dir)?;
+    std::fs::write(
+        resumed_dir.join("deposit_data-1700000000.jsonl"),
+        format!(
+            "{}\n{{\"pubkey\": \"",
+            serde_json::to_string(&expected_deposit_data[0])?
+        ),
+    )?;
+
+    // interrupted generation is not overwritten without resume flag
+    generate(&resumed_dir, false, &[])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("use --resume to continue it"));
+
+    // recorded deposit data is checked against current arguments
+    generate(&resumed_dir, true, &["--deposit_amount_gwei", "1000000000"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Recorded deposit data of validator 0 does not match validators being generated, \
+            amount differ",
+        ));
+    generate(&resumed_dir, true, &["--validator_start_index", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Recorded deposit data of validator 1 does not match validators being generated, \
+            pubkey, withdrawal_credentials differ",
+        ));
+
+    let output = generate(&resumed_dir, true, &[]).output()?;
+    assert!(output.status.success());
+    let written: Vec<PathBuf> = std::str::from_utf8(&output.stdout)?
+        .lines()
+        .map(PathBuf::from)
+        .collect();
+    assert_eq!(
+        vec![
+            resumed_dir.join("keystore-m_12381_3600_1_0_0-1700000000.json"),
+            resumed_dir.join("keystore-m_12381_3600_2_0_0-1700000000.json"),
+            resumed_dir.join("deposit_data-1700000000.json"),
+        ],
+        written
+    );
+    assert!(!resumed_dir.join("deposit_data-1700000000.jsonl").exists());
+    assert_eq!(expected_deposit_data, read_deposit_data(&resumed_dir));
+
+    std::fs::remove_dir_all(&test_dir)?;
+
+    Ok(())
+}
+
+/*
+    invalid deposit parameters are rejected before anything is written into output dir
+*/
+#[test]
+fn test_error_output_dir_invalid_deposit_amount() -> Result<(), Box<dyn std::error::Error>> {
+    let output_dir = env::temp_dir().join(format!(
+        "eth-staking-smith-invalid-amount-{}",
+        uuid::Uuid::new_v4()
+    ));
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg("mainnet");
+    cmd.arg("--keystore_password");
+    cmd.arg("testtest");
+    cmd.arg("--mnemonic");
+    cmd.arg("ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say");
+    cmd.arg("--num_validators");
+    cmd.arg("1");
+    cmd.arg("--withdrawal_credentials");
+    cmd.arg("0x0100000000000000000000000000000000000000000000000000000000000001");
+    cmd.arg("--deposit_amount_gwei");
+    cmd.arg("33000000000");
+    cmd.arg("--output_dir");
+    cmd.arg(&output_dir);
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "unless withdrawal credentials are compounding (0x02)",
+    ));
+    assert!(!output_dir.exists());
+
+    Ok(())
+}
+
 fn read_keystore_json(test_path: &PathBuf, keystore_filename: &str) -> JsonKeystore {
     let keystore_path = test_path.join(Path::new(&keystore_filename));
     let keystore_file =
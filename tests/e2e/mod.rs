@@ -1,7 +1,13 @@
 mod bls_to_execution_change;
+mod consolidation_request;
+mod deposit_transaction;
 mod existing_mnemonic;
 mod new_mnemonic;
 mod presigned_exit_message;
+mod recover_seed_index;
+mod reencrypt_keystore;
+mod verify_deposit_data;
+mod withdrawal_request;
 
 use serde::{Deserialize, Serialize};
 
@@ -1,6 +1,6 @@
 use assert_cmd::prelude::*;
 use eth2_keystore::{json_keystore::JsonKeystore, Keystore};
-use eth_staking_smith::{networks::SupportedNetworks, ValidatorExports};
+use eth_staking_smith::{networks::SupportedNetworks, DepositExport, ValidatorExports};
 use predicates::prelude::*;
 use std::{path::PathBuf, process::Command};
 
@@ -50,7 +50,8 @@ fn test_deposit_data_keystore() -> Result<(), Box<dyn std::error::Error>> {
     let keystore = generated_validator_json.keystores.get(0).unwrap();
 
     generated_deposit_data
-        .validate(eth_staking_smith::chain_spec::chain_spec_for_network(&chain).unwrap());
+        .validate(eth_staking_smith::chain_spec::chain_spec_for_network(&chain).unwrap())
+        .unwrap();
 
     // decrypt keystore with expected password to derive private key
     let encoded_private_key = decrypt_generated_keystore(
@@ -107,7 +108,7 @@ fn test_multliple_validators() -> Result<(), Box<dyn std::error::Error>> {
         eth_staking_smith::chain_spec::chain_spec_for_network(&SupportedNetworks::Goerli).unwrap();
 
     for deposit_data in generated_deposit_datas {
-        deposit_data.validate(spec.clone());
+        deposit_data.validate(spec.clone()).unwrap();
     }
 
     for index in 0..generated_private_keys.len() {
@@ -176,7 +177,8 @@ fn test_withdrawal_address_execution() -> Result<(), Box<dyn std::error::Error>>
         .to_owned();
 
     generated_deposit_data
-        .validate(eth_staking_smith::chain_spec::chain_spec_for_network(&chain).unwrap());
+        .validate(eth_staking_smith::chain_spec::chain_spec_for_network(&chain).unwrap())
+        .unwrap();
 
     // decrypt keystore with expected password to derive private key
     let encoded_private_key = decrypt_generated_keystore(
@@ -234,7 +236,8 @@ fn test_withdrawal_credentials_bls() -> Result<(), Box<dyn std::error::Error>> {
         .to_owned();
 
     generated_deposit_data
-        .validate(eth_staking_smith::chain_spec::chain_spec_for_network(&chain).unwrap());
+        .validate(eth_staking_smith::chain_spec::chain_spec_for_network(&chain).unwrap())
+        .unwrap();
 
     // decrypt keystore with expected password to derive private key
     let encoded_private_key = decrypt_generated_keystore(
@@ -294,9 +297,12 @@ fn test_new_custom_testnet_config() -> Result<(), Box<dyn std::error::Error>> {
         .expect("could not get generated deposit data")
         .to_owned();
 
-    generated_deposit_data.validate(
-        eth_staking_smith::chain_spec::chain_spec_from_file(testnet_config.to_string()).unwrap(),
-    );
+    generated_deposit_data
+        .validate(
+            eth_staking_smith::chain_spec::chain_spec_from_file(testnet_config.to_string())
+                .unwrap(),
+        )
+        .unwrap();
 
     // decrypt keystore with expected password to derive private key
     let encoded_private_key = decrypt_generated_keystore(
@@ -355,7 +361,8 @@ fn test_withdrawal_credentials_execution() -> Result<(), Box<dyn std::error::Err
         .to_owned();
 
     generated_deposit_data
-        .validate(eth_staking_smith::chain_spec::chain_spec_for_network(&chain).unwrap());
+        .validate(eth_staking_smith::chain_spec::chain_spec_for_network(&chain).unwrap())
+        .unwrap();
 
     // decrypt keystore with expected password to derive private key
     let encoded_private_key = decrypt_generated_keystore(
@@ -414,7 +421,8 @@ fn test_keystore_kdf_pbkdf2() -> Result<(), Box<dyn std::error::Error>> {
     let keystore = generated_validator_json.keystores.get(0).unwrap();
 
     generated_deposit_data
-        .validate(eth_staking_smith::chain_spec::chain_spec_for_network(&chain).unwrap());
+        .validate(eth_staking_smith::chain_spec::chain_spec_for_network(&chain).unwrap())
+        .unwrap();
 
     // decrypt keystore with expected password to derive private key
     let encoded_private_key = decrypt_generated_keystore(
@@ -476,7 +484,8 @@ fn test_keystore_kdf_scrypt() -> Result<(), Box<dyn std::error::Error>> {
     let keystore = generated_validator_json.keystores.get(0).unwrap();
 
     generated_deposit_data
-        .validate(eth_staking_smith::chain_spec::chain_spec_for_network(&chain).unwrap());
+        .validate(eth_staking_smith::chain_spec::chain_spec_for_network(&chain).unwrap())
+        .unwrap();
 
     // decrypt keystore with expected password to derive private key
     let encoded_private_key = decrypt_generated_keystore(
@@ -527,6 +536,96 @@ fn test_omitting_keystore_password() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/*
+    generate validator with 12 word mnemonic in Spanish,
+    and derive the same validator from it with existing-mnemonic
+*/
+#[test]
+fn test_mnemonic_language_and_word_count() -> Result<(), Box<dyn std::error::Error>> {
+    let chain = "mainnet";
+    let num_validators = "1";
+
+    // run eth-staking-smith
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("new-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg(chain);
+    cmd.arg("--num_validators");
+    cmd.arg(num_validators);
+    cmd.arg("--mnemonic_language");
+    cmd.arg("spanish");
+    cmd.arg("--mnemonic_word_count");
+    cmd.arg("12");
+
+    cmd.assert().success();
+
+    // read generated output
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    let generated_json: serde_json::Value = serde_json::from_str(command_output)?;
+    let generated_mnemonic = generated_json["mnemonic"]["seed"].as_str().unwrap();
+    let generated_pubkey = generated_json["deposit_data"][0]["pubkey"]
+        .as_str()
+        .unwrap();
+
+    assert_eq!(12, generated_mnemonic.split_whitespace().count());
+
+    // mnemonic language is detected by existing-mnemonic
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("existing-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg(chain);
+    cmd.arg("--num_validators");
+    cmd.arg(num_validators);
+    cmd.arg("--mnemonic");
+    cmd.arg(generated_mnemonic);
+
+    cmd.assert().success();
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    let existing_json: serde_json::Value = serde_json::from_str(command_output)?;
+
+    assert_eq!(
+        generated_pubkey,
+        existing_json["deposit_data"][0]["pubkey"].as_str().unwrap()
+    );
+
+    Ok(())
+}
+
+/*
+    attempt to generate validator with unsupported mnemonic word count
+*/
+#[test]
+fn test_error_unsupported_mnemonic_word_count() -> Result<(), Box<dyn std::error::Error>> {
+    let chain = "mainnet";
+    let num_validators = "1";
+
+    // run eth-staking-smith
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("new-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg(chain);
+    cmd.arg("--num_validators");
+    cmd.arg(num_validators);
+    cmd.arg("--mnemonic_word_count");
+    cmd.arg("13");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "invalid value '13' for '--mnemonic-word-count <MNEMONIC_WORD_COUNT>'",
+    ));
+
+    Ok(())
+}
+
 /*
     attempt to generate validator with non-supported network
 */
@@ -630,3 +729,86 @@ fn parse_kdf_function(keystore: &Keystore) -> String {
         .unwrap();
     kdf_function
 }
+
+/*
+    generate 2 validators into validator_keys folder
+*/
+#[test]
+fn test_output_dir() -> Result<(), Box<dyn std::error::Error>> {
+    let chain = SupportedNetworks::Holesky;
+    let decryption_password = "testtest";
+    let num_validators = "2";
+    let output_dir = std::env::temp_dir().join(format!(
+        "eth-staking-smith-output-dir-{}",
+        uuid::Uuid::new_v4()
+    ));
+
+    // run eth-staking-smith
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("new-mnemonic");
+    cmd.arg("--chain");
+    cmd.arg(chain.to_string());
+    cmd.arg("--keystore_password");
+    cmd.arg(decryption_password);
+    cmd.arg("--num_validators");
+    cmd.arg(num_validators);
+    cmd.arg("--output_dir");
+    cmd.arg(output_dir.to_str().unwrap());
+
+    let output = cmd.output()?;
+    assert!(output.status.success());
+
+    // stdout only contains paths of written files
+    let command_output = std::str::from_utf8(&output.stdout)?;
+    let written: Vec<PathBuf> = command_output.lines().map(PathBuf::from).collect();
+    assert_eq!(3, written.len());
+    for path in written.iter() {
+        assert!(path.starts_with(&output_dir));
+    }
+
+    let mut keystore_files = vec![];
+    let mut deposit_data_files = vec![];
+    for entry in std::fs::read_dir(&output_dir)? {
+        let path = entry?.path();
+        let filename = path.file_name().unwrap().to_str().unwrap().to_owned();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(
+                0o440,
+                std::fs::metadata(&path)?.permissions().mode() & 0o777
+            );
+        }
+        if filename.starts_with("keystore-m_12381_3600_") {
+            keystore_files.push(path);
+        } else if filename.starts_with("deposit_data-") {
+            deposit_data_files.push(path);
+        }
+    }
+    assert_eq!(2, keystore_files.len());
+    assert_eq!(1, deposit_data_files.len());
+
+    let deposit_data: Vec<DepositExport> =
+        serde_json::from_slice(&std::fs::read(&deposit_data_files[0])?)?;
+    assert_eq!(2, deposit_data.len());
+    let spec = eth_staking_smith::chain_spec::chain_spec_for_network(&chain).unwrap();
+    for deposit in deposit_data.iter() {
+        deposit.clone().validate(spec.clone()).unwrap();
+    }
+
+    for keystore_file in keystore_files {
+        let keystore = Keystore::from_json_file(&keystore_file).unwrap();
+        let keypair = keystore
+            .decrypt_keypair(decryption_password.as_bytes())
+            .unwrap();
+        assert!(deposit_data
+            .iter()
+            .any(|d| format!("0x{}", d.pubkey) == keypair.pk.as_hex_string()));
+    }
+
+    std::fs::remove_dir_all(&output_dir)?;
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use assert_cmd::prelude::*;
+use predicates::prelude::*;
 use std::process::Command;
 use types::SignedVoluntaryExit;
 
@@ -111,6 +112,8 @@ fn test_presigned_exit_message_send_beacon_node() -> Result<(), Box<dyn std::err
 
     let server = httpmock::MockServer::start();
 
+    let validator_mock = mock_validator_index(&server, validator_index);
+
     let beacon_node_mock = server.mock(|when, then| {
         when.method(httpmock::Method::POST)
             .path("/eth/v1/beacon/pool/voluntary_exits")
@@ -145,11 +148,256 @@ fn test_presigned_exit_message_send_beacon_node() -> Result<(), Box<dyn std::err
 
     // verify request path and body
 
+    validator_mock.assert();
+    beacon_node_mock.assert();
+
+    Ok(())
+}
+
+#[test]
+fn test_presigned_exit_message_beacon_index_lookup() -> Result<(), Box<dyn std::error::Error>> {
+    let chain = "mainnet";
+    let expected_mnemonic = "ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say";
+    let validator_start_index = "0";
+    let epoch = "305658";
+
+    let server = httpmock::MockServer::start();
+
+    let validator_mock = mock_validator_index(&server, "100");
+
+    let beacon_node_mock = server.mock(|when, then| {
+        when.method(httpmock::Method::POST)
+            .path("/eth/v1/beacon/pool/voluntary_exits")
+            .json_body(serde_json::json!({
+              "message": {
+                  "epoch": epoch.parse::<u32>().unwrap(),
+                  "validator_index": 100,
+              },
+              "signature": "0xa74f22d26da9934c2a9c783799fb9e7bef49b3d7c3759a0683b52ee5d71516c0ecdbcc47703f11959c5e701a6c47194410bed800217bd4dd0dab1e0587b14551771accd04ff1c78302f9605f44c3894976c5b3537b70cb7ac9dcb5398dc22079"
+          }));
+        then.status(200);
+    });
+
+    // run eth-staking-smith without validator beacon index
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("presigned-exit-message");
+    cmd.arg("--chain");
+    cmd.arg(chain);
+    cmd.arg("--validator_seed_index");
+    cmd.arg(validator_start_index);
+    cmd.arg("--mnemonic");
+    cmd.arg(expected_mnemonic);
+    cmd.arg("--epoch");
+    cmd.arg(epoch);
+    cmd.arg("--beacon-node-uri");
+    cmd.arg(server.base_url());
+
+    cmd.assert().success();
+
+    validator_mock.assert();
     beacon_node_mock.assert();
 
     Ok(())
 }
 
+#[test]
+fn test_presigned_exit_message_beacon_index_mismatch() -> Result<(), Box<dyn std::error::Error>> {
+    let chain = "mainnet";
+    let expected_mnemonic = "ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say";
+    let validator_start_index = "0";
+    let epoch = "305658";
+
+    let server = httpmock::MockServer::start();
+
+    mock_validator_index(&server, "100");
+
+    let beacon_node_mock = server.mock(|when, then| {
+        when.method(httpmock::Method::POST)
+            .path("/eth/v1/beacon/pool/voluntary_exits");
+        then.status(200);
+    });
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("presigned-exit-message");
+    cmd.arg("--chain");
+    cmd.arg(chain);
+    cmd.arg("--validator_seed_index");
+    cmd.arg(validator_start_index);
+    cmd.arg("--validator_beacon_index");
+    cmd.arg("101");
+    cmd.arg("--mnemonic");
+    cmd.arg(expected_mnemonic);
+    cmd.arg("--epoch");
+    cmd.arg(epoch);
+    cmd.arg("--beacon-node-uri");
+    cmd.arg(server.base_url());
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("ValidatorIndexMismatch"));
+
+    beacon_node_mock.assert_hits(0);
+
+    Ok(())
+}
+
+#[test]
+fn test_presigned_exit_message_custom_testnet_from_beacon_node(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let expected_mnemonic = "ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say";
+
+    let server = httpmock::MockServer::start();
+    let (genesis_mock, spec_mock) = mock_custom_testnet(&server);
+    mock_validator_index(&server, "100");
+    let beacon_node_mock = server.mock(|when, then| {
+        when.method(httpmock::Method::POST)
+            .path("/eth/v1/beacon/pool/voluntary_exits");
+        then.status(200);
+    });
+
+    // neither chain, nor testnet config and genesis validators root are passed
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("presigned-exit-message");
+    cmd.arg("--validator_seed_index");
+    cmd.arg("0");
+    cmd.arg("--mnemonic");
+    cmd.arg(expected_mnemonic);
+    cmd.arg("--epoch");
+    cmd.arg("305658");
+    cmd.arg("--beacon-node-uri");
+    cmd.arg(server.base_url());
+
+    cmd.assert().success();
+
+    genesis_mock.assert();
+    spec_mock.assert();
+    beacon_node_mock.assert();
+
+    Ok(())
+}
+
+#[test]
+fn test_presigned_exit_message_testnet_config_mismatch_warning(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let expected_mnemonic = "ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say";
+    let mut testnet_config = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    testnet_config.push("tests/resources/helder-testnet.yaml");
+
+    let server = httpmock::MockServer::start();
+    mock_custom_testnet(&server);
+    mock_validator_index(&server, "100");
+    server.mock(|when, then| {
+        when.method(httpmock::Method::POST)
+            .path("/eth/v1/beacon/pool/voluntary_exits");
+        then.status(200);
+    });
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("presigned-exit-message");
+    cmd.arg("--testnet_config");
+    cmd.arg(testnet_config.to_str().unwrap());
+    cmd.arg("--genesis_validators_root");
+    cmd.arg("0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95");
+    cmd.arg("--validator_seed_index");
+    cmd.arg("0");
+    cmd.arg("--mnemonic");
+    cmd.arg(expected_mnemonic);
+    cmd.arg("--epoch");
+    cmd.arg("305658");
+    cmd.arg("--beacon-node-uri");
+    cmd.arg(server.base_url());
+
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Supplied testnet config disagrees with beacon node on GENESIS_FORK_VERSION",
+        ))
+        .stderr(predicate::str::contains("Supplied genesis validators root"));
+
+    Ok(())
+}
+
+#[test]
+fn test_presigned_exit_message_invalid_private_key() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("presigned-exit-message");
+    cmd.arg("--chain");
+    cmd.arg("mainnet");
+    cmd.arg("--validator_beacon_index");
+    cmd.arg("100");
+    cmd.arg("--private-key");
+    cmd.arg("0xnothex");
+    cmd.arg("--epoch");
+    cmd.arg("305658");
+
+    // error is reported without panicking
+    cmd.assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Error: Invalid private key hex input",
+        ))
+        .stderr(predicate::str::contains("panicked").not());
+
+    Ok(())
+}
+
+/// Mock beacon node genesis and chain config endpoints
+/// for the custom testnet with minimal preset
+fn mock_custom_testnet(server: &httpmock::MockServer) -> (httpmock::Mock, httpmock::Mock) {
+    let mut testnet_config = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    testnet_config.push("tests/resources/minimal.yaml");
+    let config = types::Config::from_file(testnet_config.as_path()).unwrap();
+
+    let genesis_mock = server.mock(|when, then| {
+        when.method(httpmock::Method::GET)
+            .path("/eth/v1/beacon/genesis");
+        then.status(200).json_body(serde_json::json!({
+            "data": {
+                "genesis_time": "1590832934",
+                "genesis_validators_root": "0x9143aa7c615a7f7115e2b6aac319c03529df8242ae705fba9df39b79c59fa8b1",
+                "genesis_fork_version": format!("0x{}", hex::encode(config.genesis_fork_version)),
+            }
+        }));
+    });
+    let spec_mock = server.mock(|when, then| {
+        when.method(httpmock::Method::GET)
+            .path("/eth/v1/config/spec");
+        then.status(200)
+            .json_body(serde_json::json!({ "data": config }));
+    });
+    (genesis_mock, spec_mock)
+}
+
+/// Mock beacon node validator endpoint for the validator
+/// with seed index 0 of the test mnemonic
+fn mock_validator_index<'a>(
+    server: &'a httpmock::MockServer,
+    validator_index: &str,
+) -> httpmock::Mock<'a> {
+    server.mock(|when, then| {
+        when.method(httpmock::Method::GET)
+            .path("/eth/v1/beacon/states/head/validators/0x8844cebb34d10e0e57f3c29ada375dafe14762ab85b2e408c3d6d55ce6d03317660bca9f2c2d17d8fbe14a2529ada1ea");
+        then.status(200).json_body(serde_json::json!({
+            "execution_optimistic": false,
+            "finalized": false,
+            "data": {
+                "index": validator_index,
+                "status": "active_ongoing",
+                "validator": {
+                    "pubkey": "0x8844cebb34d10e0e57f3c29ada375dafe14762ab85b2e408c3d6d55ce6d03317660bca9f2c2d17d8fbe14a2529ada1ea",
+                    "withdrawal_credentials": "0x0045b91b2f60b88e7392d49ae1364b55e713d06f30e563f9f99e10994b26221d",
+                }
+            }
+        }));
+    })
+}
+
 #[test]
 fn test_presigned_exit_message_private_key() -> Result<(), Box<dyn std::error::Error>> {
     let chain = "mainnet";
@@ -183,3 +431,140 @@ fn test_presigned_exit_message_private_key() -> Result<(), Box<dyn std::error::E
 
     Ok(())
 }
+
+#[test]
+fn test_presigned_exit_message_batch() -> Result<(), Box<dyn std::error::Error>> {
+    let chain = "mainnet";
+    let expected_mnemonic = "ski interest capable knee usual ugly duty exercise tattoo subway delay upper bid forget say";
+    let validator_start_index = "0";
+    let num_validators = "2";
+    let epoch = "305658";
+
+    let validator_index_file = std::env::temp_dir().join(format!(
+        "eth-staking-smith-exit-batch-{}.csv",
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::write(
+        &validator_index_file,
+        "validator_seed_index,validator_beacon_index\n0,100\n1,101\n",
+    )?;
+
+    // run eth-staking-smith
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("presigned-exit-message");
+    cmd.arg("--chain");
+    cmd.arg(chain);
+    cmd.arg("--validator_seed_index");
+    cmd.arg(validator_start_index);
+    cmd.arg("--num_validators");
+    cmd.arg(num_validators);
+    cmd.arg("--validator_index_file");
+    cmd.arg(&validator_index_file);
+    cmd.arg("--mnemonic");
+    cmd.arg(expected_mnemonic);
+    cmd.arg("--epoch");
+    cmd.arg(epoch);
+
+    cmd.assert().success();
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    std::fs::remove_file(&validator_index_file)?;
+
+    let signed_voluntary_exits: Vec<SignedVoluntaryExit> = serde_json::from_str(command_output)?;
+    assert_eq!(2, signed_voluntary_exits.len());
+    assert_eq!(100, signed_voluntary_exits[0].message.validator_index);
+    assert_eq!(101, signed_voluntary_exits[1].message.validator_index);
+    assert_eq!(
+        signed_voluntary_exits[0].signature.to_string(),
+        "0xa74f22d26da9934c2a9c783799fb9e7bef49b3d7c3759a0683b52ee5d71516c0ecdbcc47703f11959c5e701a6c47194410bed800217bd4dd0dab1e0587b14551771accd04ff1c78302f9605f44c3894976c5b3537b70cb7ac9dcb5398dc22079"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_presigned_exit_message_keystore_directory() -> Result<(), Box<dyn std::error::Error>> {
+    let chain = "mainnet";
+    let keystore_filename = "keystore-m_12381_3600_0_0_0-1668613231.json";
+    let validator_index = "100";
+    let epoch = "305658";
+
+    // keystore directory with password file per keystore
+    let test_dir = std::env::temp_dir().join(format!(
+        "eth-staking-smith-exit-keystores-{}",
+        uuid::Uuid::new_v4()
+    ));
+    let keystore_dir = test_dir.join("keystores");
+    let password_dir = test_dir.join("passwords");
+    std::fs::create_dir_all(&keystore_dir)?;
+    std::fs::create_dir_all(&password_dir)?;
+    std::fs::copy(
+        format!("tests/e2e/expected_testdata/withdrawal_credentials_bls/{keystore_filename}"),
+        keystore_dir.join(keystore_filename),
+    )?;
+    std::fs::write(
+        password_dir.join("keystore-m_12381_3600_0_0_0-1668613231.txt"),
+        "testtest\n",
+    )?;
+
+    // run eth-staking-smith
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("presigned-exit-message");
+    cmd.arg("--chain");
+    cmd.arg(chain);
+    cmd.arg("--validator_beacon_index");
+    cmd.arg(validator_index);
+    cmd.arg("--keystore");
+    cmd.arg(&keystore_dir);
+    cmd.arg("--keystore_password_file");
+    cmd.arg(&password_dir);
+    cmd.arg("--epoch");
+    cmd.arg(epoch);
+
+    cmd.assert().success();
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    std::fs::remove_dir_all(&test_dir)?;
+
+    let signed_voluntary_exit: SignedVoluntaryExit = serde_json::from_str(command_output)?;
+    assert_eq!(
+        signed_voluntary_exit.signature.to_string(),
+        "0xa74f22d26da9934c2a9c783799fb9e7bef49b3d7c3759a0683b52ee5d71516c0ecdbcc47703f11959c5e701a6c47194410bed800217bd4dd0dab1e0587b14551771accd04ff1c78302f9605f44c3894976c5b3537b70cb7ac9dcb5398dc22079"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_presigned_exit_message_keystore_wrong_password() -> Result<(), Box<dyn std::error::Error>> {
+    let password_file = std::env::temp_dir().join(format!(
+        "eth-staking-smith-exit-password-{}",
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::write(&password_file, "wrongpassword")?;
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("presigned-exit-message");
+    cmd.arg("--chain");
+    cmd.arg("mainnet");
+    cmd.arg("--validator_beacon_index");
+    cmd.arg("100");
+    cmd.arg("--keystore");
+    cmd.arg("tests/e2e/expected_testdata/withdrawal_credentials_bls/keystore-m_12381_3600_0_0_0-1668613231.json");
+    cmd.arg("--keystore_password_file");
+    cmd.arg(&password_file);
+    cmd.arg("--epoch");
+    cmd.arg("305658");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Can not decrypt keystore"));
+    std::fs::remove_file(&password_file)?;
+
+    Ok(())
+}
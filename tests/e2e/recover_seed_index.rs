@@ -0,0 +1,66 @@
+use assert_cmd::prelude::*;
+use eth_staking_smith::seed_index::{SeedIndexMatch, SeedKeyType};
+use predicates::prelude::*;
+use std::process::Command;
+
+const MNEMONIC: &str = "window lottery throw arrange visit play gate open scare strategy sadness fame soul bronze soap";
+
+/*
+    find seed indices of multiple validators in one pass
+*/
+#[test]
+fn test_recover_seed_index() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("recover-seed-index");
+    cmd.arg("--mnemonic");
+    cmd.arg(MNEMONIC);
+    cmd.arg("--pubkey");
+    cmd.arg("a759ea6450a376507bebc596b8e52981a70467fd7a33c6a66e45b51afb388078eb1dd7e9a751a91d65fba793ab2430fd,0x80145bdb454a8f55f7275903108f516ade8747efff0906616febb11c6e307667431ee286d1a1a919ea22739e0e7f6e9a");
+    cmd.arg("--num_validators");
+    cmd.arg("10");
+
+    cmd.assert().success();
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    let matches: Vec<SeedIndexMatch> = serde_json::from_str(command_output)?;
+    assert_eq!(2, matches.len());
+    assert_eq!(2, matches[0].validator_seed_index);
+    assert_eq!(SeedKeyType::Voting, matches[0].key_type);
+    assert_eq!("m/12381/3600/2/0/0", matches[0].path);
+    assert_eq!(
+        "0xa759ea6450a376507bebc596b8e52981a70467fd7a33c6a66e45b51afb388078eb1dd7e9a751a91d65fba793ab2430fd",
+        matches[0].pubkey
+    );
+    assert_eq!(1, matches[1].validator_seed_index);
+    assert_eq!("m/12381/3600/1/0/0", matches[1].path);
+    assert!(matches[1].withdrawal_credentials.starts_with("0x00"));
+
+    Ok(())
+}
+
+/*
+    attempt to find seed index of validator outside of the searched range
+*/
+#[test]
+fn test_error_seed_index_not_found() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("recover-seed-index");
+    cmd.arg("--mnemonic");
+    cmd.arg(MNEMONIC);
+    cmd.arg("--pubkey");
+    cmd.arg("0xa759ea6450a376507bebc596b8e52981a70467fd7a33c6a66e45b51afb388078eb1dd7e9a751a91d65fba793ab2430fd");
+    cmd.arg("--num_validators");
+    cmd.arg("2");
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("[]"))
+        .stderr(predicate::str::contains(
+            "No seed index found in range 0..2 for public keys: 0xa759ea64",
+        ));
+
+    Ok(())
+}
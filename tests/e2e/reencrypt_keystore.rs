@@ -0,0 +1,110 @@
+use assert_cmd::prelude::*;
+use eth2_keystore::{json_keystore::JsonKeystore, Keystore};
+use predicates::prelude::*;
+use std::process::Command;
+
+const KEYSTORE_DIR: &str = "tests/e2e/expected_testdata/multiple_validators";
+
+#[test]
+fn test_reencrypt_keystore_directory() -> Result<(), Box<dyn std::error::Error>> {
+    let test_dir = std::env::temp_dir().join(format!(
+        "eth-staking-smith-reencrypt-{}",
+        uuid::Uuid::new_v4()
+    ));
+    let output_dir = test_dir.join("validator_keys");
+    let password_file = test_dir.join("password.txt");
+    let new_password_file = test_dir.join("new_password.txt");
+    std::fs::create_dir_all(&test_dir)?;
+    std::fs::write(&password_file, "blablatest\n")?;
+    std::fs::write(&new_password_file, "newpassword\n")?;
+
+    // run eth-staking-smith
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("reencrypt-keystore");
+    cmd.arg("--keystore");
+    cmd.arg(KEYSTORE_DIR);
+    cmd.arg("--keystore_password_file");
+    cmd.arg(&password_file);
+    cmd.arg("--password_file");
+    cmd.arg(&new_password_file);
+    cmd.arg("--kdf");
+    cmd.arg("pbkdf2");
+    cmd.arg("--keep_uuid");
+    cmd.arg("--output_dir");
+    cmd.arg(&output_dir);
+
+    cmd.assert().success();
+
+    let mut keystore_files: Vec<_> = std::fs::read_dir(&output_dir)?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<Result<_, _>>()?;
+    keystore_files.sort();
+    assert_eq!(3, keystore_files.len());
+
+    for keystore_file in keystore_files {
+        let original =
+            Keystore::from_json_file(std::path::Path::new(KEYSTORE_DIR).join(&keystore_file))
+                .unwrap();
+        let reencrypted = Keystore::from_json_file(output_dir.join(&keystore_file)).unwrap();
+        let reencrypted_json: JsonKeystore =
+            serde_json::from_str(&std::fs::read_to_string(output_dir.join(&keystore_file))?)?;
+        let kdf_function: String = reencrypted_json.crypto.kdf.function.try_into().unwrap();
+
+        assert_eq!("pbkdf2", kdf_function);
+        assert_eq!(original.pubkey(), reencrypted.pubkey());
+        assert_eq!(original.path(), reencrypted.path());
+        assert_eq!(original.uuid(), reencrypted.uuid());
+        assert_eq!(
+            original.decrypt_keypair(b"blablatest").unwrap().pk,
+            reencrypted.decrypt_keypair(b"newpassword").unwrap().pk
+        );
+    }
+
+    // existing re-encrypted keystores are never overwritten
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("reencrypt-keystore");
+    cmd.arg("--keystore");
+    cmd.arg(KEYSTORE_DIR);
+    cmd.arg("--keystore_password_file");
+    cmd.arg(&password_file);
+    cmd.arg("--password_file");
+    cmd.arg(&new_password_file);
+    cmd.arg("--output_dir");
+    cmd.arg(&output_dir);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Can not create"));
+
+    std::fs::remove_dir_all(&test_dir)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_error_reencrypt_keystore_without_new_password() -> Result<(), Box<dyn std::error::Error>> {
+    let password_file = std::env::temp_dir().join(format!(
+        "eth-staking-smith-reencrypt-password-{}",
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::write(&password_file, "blablatest")?;
+
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("reencrypt-keystore");
+    cmd.arg("--keystore");
+    cmd.arg(KEYSTORE_DIR);
+    cmd.arg("--keystore_password_file");
+    cmd.arg(&password_file);
+    cmd.arg("--output_dir");
+    cmd.arg(std::env::temp_dir());
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "New keystore password must be set",
+    ));
+    std::fs::remove_file(&password_file)?;
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::{path::PathBuf, process::Command};
+
+fn deposit_data_file() -> PathBuf {
+    let mut manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // generated with staking-deposit-cli for goerli
+    manifest.push("tests/e2e/expected_testdata/multiple_validators/deposit_data-1670231001.json");
+    manifest
+}
+
+#[test]
+fn test_verify_deposit_data() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("verify-deposit-data");
+    cmd.arg("--chain");
+    cmd.arg("goerli");
+    cmd.arg("--deposit_data_file");
+    cmd.arg(deposit_data_file());
+
+    cmd.assert().success();
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    let report: Vec<serde_json::Value> = serde_json::from_str(command_output)?;
+
+    assert_eq!(3, report.len());
+    for entry in report {
+        assert_eq!("ok", entry["status"]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_deposit_data_wrong_chain() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("verify-deposit-data");
+    cmd.arg("--chain");
+    cmd.arg("mainnet");
+    cmd.arg("--deposit_data_file");
+    cmd.arg(deposit_data_file());
+
+    cmd.assert().failure().stdout(predicate::str::contains(
+        "\"error\": \"fork version does not match the chain\"",
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use assert_cmd::prelude::*;
+use eth_staking_smith::withdrawal_request::WithdrawalRequestExport;
+use predicates::prelude::*;
+use std::process::Command;
+
+/*
+    generate full exit requests for validators derived from mnemonic
+*/
+#[test]
+fn test_withdrawal_request_from_mnemonic() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("withdrawal-request");
+    cmd.arg("--mnemonic");
+    cmd.arg("entire habit bottom mention spoil clown finger wheat motion fox axis mechanic country make garment bar blind stadium sugar water scissors canyon often ketchup");
+    cmd.arg("--validator_seed_index");
+    cmd.arg("0");
+    cmd.arg("--num_validators");
+    cmd.arg("2");
+    cmd.arg("--fee_wei");
+    cmd.arg("2");
+
+    cmd.assert().success();
+
+    let output = &cmd.output()?.stdout;
+    let command_output = std::str::from_utf8(output)?;
+    let requests: Vec<WithdrawalRequestExport> = serde_json::from_str(command_output)?;
+    assert_eq!(2, requests.len());
+    assert_eq!(
+        "0x8666389c3fe6ff0bca9adba81504f380b9e2c719419760d561836472fafe295cb50696524e19cba084e1d788d66c80d6",
+        requests[0].validator_pubkey
+    );
+    assert_eq!(
+        "0x974ec5bce4653f1f440ad07c5b363ad3b1616520e9680ff837f9ff7a8c10e3cc67dd49aa5089f714ed45d7ad56bc758a",
+        requests[1].validator_pubkey
+    );
+    for request in requests {
+        assert_eq!(0, request.amount);
+        assert_eq!(
+            "0x00000961Ef480Eb55e80D19ad83579A64c007002",
+            request.transaction.to
+        );
+        assert_eq!("0x2", request.transaction.value);
+        assert_eq!(
+            format!("{}0000000000000000", request.validator_pubkey),
+            request.transaction.data
+        );
+    }
+
+    Ok(())
+}
+
+/*
+    generate partial withdrawal request for public key passed directly
+*/
+#[test]
+fn test_withdrawal_request_from_pubkey() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("withdrawal-request");
+    cmd.arg("--pubkey");
+    cmd.arg("8bf0a669a51d0cb6ff745e4b0aa7c41e8de8d179ff9267977e76c7188aaa2fb1b8b1bdfefcc545d9efdac0b4bc2239e6");
+    cmd.arg("--amount_gwei");
+    cmd.arg("1000000000");
+    cmd.arg("--fee_wei");
+    cmd.arg("1");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"value\": \"0x1\""))
+        .stdout(predicate::str::contains(
+            "\"data\": \"0x8bf0a669a51d0cb6ff745e4b0aa7c41e8de8d179ff9267977e76c7188aaa2fb1b8b1bdfefcc545d9efdac0b4bc2239e6000000003b9aca00\"",
+        ));
+
+    Ok(())
+}
+
+/*
+    attempt to generate withdrawal request for invalid public key
+*/
+#[test]
+fn test_error_withdrawal_request_invalid_pubkey() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("withdrawal-request");
+    cmd.arg("--pubkey");
+    cmd.arg("0x8bf0a669");
+    cmd.arg("--fee_wei");
+    cmd.arg("1");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid public key 0x8bf0a669"));
+
+    Ok(())
+}
+
+/*
+    attempt to generate withdrawal request for public key which is not a valid BLS point
+*/
+#[test]
+fn test_error_withdrawal_request_pubkey_not_on_curve() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("withdrawal-request");
+    cmd.arg("--pubkey");
+    cmd.arg("0x8bf0a669a51d0cb6ff745e4b0aa7c41e8de8d179ff9267977e76c7188aaa2fb1b8b1bdfefcc545d9efdac0b4bc2239e7");
+    cmd.arg("--fee_wei");
+    cmd.arg("1");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Invalid public key 0x8bf0a669a51d0cb6ff745e4b0aa7c41e8de8d179ff9267977e76c7188aaa2fb1b8b1bdfefcc545d9efdac0b4bc2239e7",
+    ));
+
+    Ok(())
+}
+
+/*
+    attempt to generate withdrawal request without fee
+*/
+#[test]
+fn test_error_withdrawal_request_no_fee() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("eth-staking-smith")?;
+
+    cmd.arg("withdrawal-request");
+    cmd.arg("--pubkey");
+    cmd.arg("8bf0a669a51d0cb6ff745e4b0aa7c41e8de8d179ff9267977e76c7188aaa2fb1b8b1bdfefcc545d9efdac0b4bc2239e6");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--fee-wei <FEE_WEI>"));
+
+    Ok(())
+}